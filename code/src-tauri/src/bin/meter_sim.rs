@@ -0,0 +1,81 @@
+//! Virtual MASS meter for testing without hardware
+//! Run with: cargo run --bin meter_sim -- [--config <meter.json>] [--tcp <addr:port> | --pty]
+//! Examples:
+//!   cargo run --bin meter_sim -- --tcp 127.0.0.1:4001
+//!   cargo run --bin meter_sim -- --pty --config sim/meter.json   (single-phase, partial blocks, one BCC error)
+//!
+//! --tcp: connect the app with connection type "tcp" to that host and port
+//! --pty: (Unix) prints a /dev/pts path to use as the serial port
+//! Without --config the built-in three-phase meter is used; log level via RUST_LOG.
+
+use omnicore_meter_suite_lib::simulator::{MeterConfig, VirtualMeter};
+use std::net::TcpListener;
+use std::path::Path;
+
+enum Link {
+    Tcp(String),
+    Pty,
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: meter_sim [--config <meter.json>] [--tcp <addr:port> | --pty]");
+    std::process::exit(2);
+}
+
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let mut args = std::env::args().skip(1);
+    let mut config_path: Option<String> = None;
+    let mut link = Link::Tcp("127.0.0.1:4001".to_string());
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => config_path = Some(args.next().unwrap_or_else(|| usage())),
+            "--tcp" => link = Link::Tcp(args.next().unwrap_or_else(|| usage())),
+            "--pty" => link = Link::Pty,
+            _ => usage(),
+        }
+    }
+
+    let config = match &config_path {
+        Some(path) => MeterConfig::load(Path::new(path)).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }),
+        None => MeterConfig::default(),
+    };
+
+    println!("=== Omnicore Virtual Meter ===");
+    println!("Identification: {}", config.identification());
+    println!("Password: {}", config.password);
+    let mut meter = VirtualMeter::new(config);
+
+    let result = match link {
+        Link::Tcp(addr) => match TcpListener::bind(&addr) {
+            Ok(listener) => {
+                println!("Listening on {} (connection type \"tcp\")", addr);
+                omnicore_meter_suite_lib::simulator::serve_tcp(listener, &mut meter)
+            }
+            Err(e) => Err(e),
+        },
+        Link::Pty => serve_pty(&mut meter),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Simulator stopped: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(unix)]
+fn serve_pty(meter: &mut VirtualMeter) -> std::io::Result<()> {
+    let mut pty = omnicore_meter_suite_lib::simulator::Pty::open()?;
+    println!("Serial port: {} (connection type \"serial\" or \"optical\")", pty.path());
+    pty.serve(meter)
+}
+
+#[cfg(not(unix))]
+fn serve_pty(_meter: &mut VirtualMeter) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "--pty is only available on Unix"))
+}
@@ -12,22 +12,24 @@
 //!   auto-serial   - connectionType=serial, baudRate=auto -> tries [9600, 300, 19200]
 //!   300           - optical mode -> tries [300] only
 
-use std::io::{Read, Write};
-use std::time::{Duration, Instant};
+use omnicore_meter_suite_lib::iec62056::{self, Frame, ProtocolMode};
+use omnicore_meter_suite_lib::mass::{EventLog, MassData};
+use omnicore_meter_suite_lib::{ConnectionParams, MeterSession, ReadConfig, SessionOptions};
+use std::time::Instant;
 
-/// Determine which initial baud rates to try (mirrors io::resolve_initial_bauds)
-fn resolve_bauds(baud_arg: &str) -> Vec<u32> {
+/// Connection type and baud rate for the baud argument (see io::resolve_initial_bauds)
+fn resolve_connection(baud_arg: &str) -> (&'static str, u32) {
     match baud_arg {
-        "auto" => vec![9600, 300],           // connectionType=auto, baudRate=auto
-        "auto-serial" => vec![9600, 300, 19200], // connectionType=serial, baudRate=auto
-        other => {
-            let baud: u32 = other.parse().unwrap_or(9600);
-            vec![baud]  // specific baud
-        }
+        "auto" => ("auto", 0),          // tries [9600, 300]
+        "auto-serial" => ("serial", 0), // tries [9600, 300, 19200]
+        "300" => ("optical", 300),      // 300 only, negotiate up
+        other => ("serial", other.parse().unwrap_or(9600)), // specific baud
     }
 }
 
 fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
     let args: Vec<String> = std::env::args().collect();
 
     let port_name = args.get(1).map(|s| s.as_str()).unwrap_or("/dev/ttyS4");
@@ -36,7 +38,7 @@ fn main() {
     let password = args.get(4).and_then(|s| if s == "-" || s == "none" { None } else { Some(s.as_str()) });
     let date_range = args.get(5).map(|s| s.as_str()); // e.g. "yesterday", "26-02-05", "26-02-05,00:00;26-02-06,00:00"
 
-    let baud_rates = resolve_bauds(baud_arg);
+    let (connection_type, baud_rate) = resolve_connection(baud_arg);
 
     let is_profile = mode.starts_with("profile");
     let profile_number: u8 = if is_profile {
@@ -48,7 +50,7 @@ fn main() {
     println!("=== Omnicore Meter Test ===");
     println!("Port: {}", port_name);
     println!("Mode: {}", mode);
-    println!("Baud arg: {} -> try {:?}", baud_arg, baud_rates);
+    println!("Baud arg: {} -> {} @ {}", baud_arg, connection_type, if baud_rate == 0 { "auto".to_string() } else { baud_rate.to_string() });
     if is_profile {
         println!("Profile: P.{:02}", profile_number);
         println!("Password: {}", password.unwrap_or("(none - will try without)"));
@@ -74,265 +76,71 @@ fn main() {
     }
     println!();
 
-    // Step 2-4: Open port, handshake, read identification (with baud retry)
-    let mut port: Option<Box<dyn serialport::SerialPort>> = None;
-    let mut initial_baud: u32 = 0;
-    let mut ident_response = String::new();
-    #[allow(unused_assignments)]
-    let mut baud_char: char = '0';
-
-    for (attempt, &try_baud) in baud_rates.iter().enumerate() {
-        println!("[2] Opening port {} @ {} baud (7E1) [Attempt {}/{}]...",
-            port_name, try_baud, attempt + 1, baud_rates.len());
-
-        let mut current_port = match serialport::new(port_name, try_baud)
-            .data_bits(serialport::DataBits::Seven)
-            .parity(serialport::Parity::Even)
-            .stop_bits(serialport::StopBits::One)
-            .flow_control(serialport::FlowControl::None)
-            .timeout(Duration::from_millis(2000))
-            .open()
-        {
-            Ok(p) => {
-                println!("    Port opened successfully!");
-                p
-            }
-            Err(e) => {
-                eprintln!("    FAILED to open port @ {} baud: {}", try_baud, e);
-                if attempt == baud_rates.len() - 1 {
-                    eprintln!("    If on WSL2, COM5 = /dev/ttyS4");
-                    eprintln!("    You may need to run from Windows or use usbipd.");
-                    std::process::exit(1);
-                }
-                continue;
-            }
-        };
-
-        // Send handshake
-        println!("[3] Sending handshake request /?!\\r\\n ...");
-        let request = b"/?!\r\n";
-        print!("    TX: ");
-        print_hex(request);
-
-        if let Err(e) = current_port.write_all(request) {
-            eprintln!("    FAILED to send: {}", e);
-            continue;
-        }
-        let _ = current_port.flush();
-        println!("    Sent OK, waiting for response...");
-
-        // Read identification response
-        std::thread::sleep(Duration::from_millis(500));
-        println!("[4] Reading identification response...");
-
-        let mut response_buf = vec![0u8; 256];
-        let mut ident_read = 0;
-        let start = Instant::now();
-
-        loop {
-            match current_port.read(&mut response_buf[ident_read..]) {
-                Ok(n) if n > 0 => {
-                    ident_read += n;
-                    if ident_read >= 2
-                        && response_buf[ident_read - 2] == b'\r'
-                        && response_buf[ident_read - 1] == b'\n'
-                    {
-                        break;
-                    }
-                }
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    if ident_read > 0 {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    eprintln!("    Read error: {}", e);
-                    break;
-                }
-            }
-            if start.elapsed() > Duration::from_secs(5) {
-                println!("    Timeout after 5s");
-                break;
-            }
-        }
-
-        if ident_read > 0 {
-            let response = String::from_utf8_lossy(&response_buf[..ident_read]);
-            print!("    RX ({} bytes): ", ident_read);
-            print_hex(&response_buf[..ident_read]);
-            println!("    ASCII: {}", response.trim());
-
-            let resp_str = response.trim();
-            if resp_str.starts_with('/') && resp_str.len() >= 5 {
-                initial_baud = try_baud;
-                ident_response = resp_str.to_string();
-                port = Some(current_port);
-                println!("    -> Response received @ {} baud!", try_baud);
-                break;
-            } else {
-                println!("    -> Invalid identification response, trying next baud...");
-            }
-        } else {
-            println!("    -> No response @ {} baud", try_baud);
-        }
-        println!();
-    }
-
-    let mut port = match port {
-        Some(p) => p,
-        None => {
-            eprintln!("    No response from meter at any baud rate!");
+    let params = ConnectionParams {
+        connection_type: connection_type.to_string(),
+        port: port_name.to_string(),
+        baud_rate,
+        timeout_ms: 2000,
+        meter_address: None,
+        password: password.map(str::to_string),
+        bcc_retries: None,
+        protocol_mode: None,
+        host: None,
+        tcp_port: None,
+        tcp_baud_switch: false,
+    };
+    let options = match SessionOptions::from_params(&params) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("    {}", e);
             std::process::exit(1);
         }
     };
 
-    let resp_str = &ident_response;
-    let manufacturer = &resp_str[1..4.min(resp_str.len())];
-    baud_char = resp_str.chars().nth(4).unwrap_or('0');
-    let model_info = if resp_str.len() > 5 { &resp_str[5..] } else { "" };
-
-    println!("    Manufacturer: {}", manufacturer);
-    println!("    Baud char: {} (max baud: {})", baud_char, baud_from_char(baud_char));
-    println!("    Model: {}", model_info);
-    println!();
-
-    // Determine target baud: for auto modes, negotiate to meter's max; for explicit, keep it
-    let target_baud = if baud_arg.starts_with("auto") || baud_arg == "300" {
-        baud_from_char(baud_char) // negotiate to meter's max
-    } else {
-        let explicit: u32 = baud_arg.parse().unwrap_or(0);
-        if explicit > 0 { explicit } else { baud_from_char(baud_char) }
-    };
-
-    // Step 5: Send ACK with mode selection
-    // IEC 62056-21 ACK format: ACK V Z Y CR LF
-    // V = protocol control (mode), Z = baud rate, Y = mode control
-    let mode_char = if is_profile {
-        b'1'  // Mode 1 - Programming
-    } else {
-        match mode {
-            "full" => b'0',
-            "short" => b'6',
-            _ => b'0',
+    // Step 2-4: Open port, handshake, read identification (with baud retry)
+    println!("[2] Handshake...");
+    let mut session = MeterSession::new(options, None);
+    let ident = match session.identify() {
+        Ok(ident) => ident,
+        Err(e) => {
+            eprintln!("    No response from meter: {}", e);
+            eprintln!("    If on WSL2, COM5 = /dev/ttyS4");
+            eprintln!("    You may need to run from Windows or use usbipd.");
+            std::process::exit(1);
         }
     };
 
-    let target_baud_char = char_from_baud(target_baud);
-    println!("[5] Sending ACK (Mode {} @ {} baud, char '{}')...", mode_char - b'0', target_baud, target_baud_char);
-    let ack = [0x06, mode_char, target_baud_char as u8, mode_char, b'\r', b'\n'];
-    print!("    TX: ");
-    print_hex(&ack);
-
-    if let Err(e) = port.write_all(&ack) {
-        eprintln!("    FAILED to send ACK: {}", e);
-        std::process::exit(1);
-    }
-    let _ = port.flush();
-
-    // Wait and switch baud rate
-    std::thread::sleep(Duration::from_millis(300));
-
-    if target_baud != initial_baud {
-        println!("    Switching baud rate: {} -> {}", initial_baud, target_baud);
-        if let Err(e) = port.set_baud_rate(target_baud) {
-            eprintln!("    FAILED to set baud rate: {}", e);
-        }
-    } else {
-        println!("    Keeping baud rate at {}", initial_baud);
-    }
+    println!("    Manufacturer: {}", ident.manufacturer);
+    println!("    Baud char: {} (max baud: {})", ident.baud_char, ident.max_baud_rate);
+    println!("    Model: {} {}", ident.edas_id, ident.model);
+    println!("    Mode: {}", ident.mode.as_char());
     println!();
 
-    // For profile mode: handle programming mode setup
+    // Step 5: Option select
     if is_profile {
-        // Read meter's response (may be password request)
-        std::thread::sleep(Duration::from_millis(500));
-        println!("[5a] Reading programming mode response...");
-
-        let mut prog_buf = vec![0u8; 256];
-        let mut prog_read = 0;
-        let prog_start = Instant::now();
-
-        loop {
-            match port.read(&mut prog_buf[prog_read..]) {
-                Ok(n) if n > 0 => {
-                    prog_read += n;
-                    // Look for complete message
-                    if prog_read >= 2 {
-                        // Check for BCC (byte after ETX)
-                        if let Some(etx_pos) = prog_buf[..prog_read].iter().position(|&b| b == 0x03) {
-                            if etx_pos + 1 < prog_read {
-                                break; // Got full message with BCC
-                            }
-                        }
-                    }
-                }
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    if prog_read > 0 { break; }
-                }
-                Err(_) => break,
-            }
-            if prog_start.elapsed() > Duration::from_secs(3) {
-                break;
-            }
-        }
-
-        if prog_read > 0 {
-            print!("    RX ({} bytes): ", prog_read);
-            print_hex(&prog_buf[..prog_read]);
-            let prog_ascii = String::from_utf8_lossy(&prog_buf[..prog_read]);
-            println!("    ASCII: {}", prog_ascii.escape_default());
-
-            // Check if it's a password request (SOH P0 STX () ETX BCC)
-            if prog_read >= 2 && prog_buf[0] == 0x01 && prog_buf[1] == b'P' {
-                println!("    -> Meter is requesting password");
-            }
-        } else {
-            println!("    No response (meter may not require password)");
+        println!("[5] Entering programming mode...");
+        match session.enter_programming() {
+            Ok(Frame::PasswordPrompt { .. }) => println!("    -> Meter is requesting password"),
+            Ok(other) => println!("    -> {:?}", other),
+            Err(e) => exit_with(session, &format!("FAILED to enter programming mode: {}", e)),
         }
         println!();
 
         // Send password if provided
         if let Some(pwd) = password {
             println!("[5b] Sending password: {} ...", pwd);
-            let pwd_cmd = build_password_command(pwd);
-            print!("    TX: ");
-            print_hex(&pwd_cmd);
-
-            if let Err(e) = port.write_all(&pwd_cmd) {
-                eprintln!("    FAILED to send password: {}", e);
-                send_break_and_exit(&mut port);
-            }
-            let _ = port.flush();
-
-            // Wait for ACK/NAK
-            std::thread::sleep(Duration::from_millis(500));
-
-            let mut ack_buf = [0u8; 1];
-            match port.read(&mut ack_buf) {
-                Ok(1) if ack_buf[0] == 0x06 => {
-                    println!("    -> Password ACCEPTED (ACK)");
-                }
-                Ok(1) if ack_buf[0] == 0x15 => {
-                    eprintln!("    -> Password REJECTED (NAK)");
-                    send_break_and_exit(&mut port);
-                }
-                Ok(n) => {
-                    print!("    -> Unexpected response ({} bytes): ", n);
-                    print_hex(&ack_buf[..n]);
-                    println!("    Continuing anyway...");
-                }
+            match session.authenticate(1, pwd) {
+                Ok(true) => println!("    -> Password ACCEPTED (ACK)"),
+                Ok(false) => exit_with(session, "-> Password REJECTED (NAK)"),
                 Err(e) => {
-                    eprintln!("    -> No response: {}", e);
+                    println!("    -> {}", e);
                     println!("    Continuing anyway...");
                 }
             }
-            println!();
         } else {
             println!("[5b] No password provided, skipping authentication");
-            println!();
         }
+        println!();
 
         // Resolve date range
         let resolved_range = resolve_date_range(date_range);
@@ -341,104 +149,69 @@ fn main() {
         } else {
             println!("[5c] Sending R2 command for P.{:02} (all data)...", profile_number);
         }
-        let r2_cmd = build_load_profile_command(profile_number, resolved_range.as_deref());
+        let (start, end) = match resolved_range.as_deref().and_then(|r| r.split_once(';')) {
+            Some((start, end)) => (Some(start), Some(end)),
+            None => (None, None),
+        };
+        let r2_cmd = iec62056::build_load_profile_command(profile_number, start, end);
         print!("    TX: ");
         print_hex(&r2_cmd);
-        let r2_ascii = String::from_utf8_lossy(&r2_cmd);
-        println!("    ASCII: {}", r2_ascii.escape_default());
+        println!("    ASCII: {}", String::from_utf8_lossy(&r2_cmd).escape_default());
 
-        if let Err(e) = port.write_all(&r2_cmd) {
-            eprintln!("    FAILED to send R2 command: {}", e);
-            send_break_and_exit(&mut port);
+        if let Err(e) = session.send(&r2_cmd) {
+            exit_with(session, &format!("FAILED to send R2 command: {}", e));
+        }
+        println!();
+    } else {
+        let packet = if mode == "short" { ProtocolMode::ShortRead } else { ProtocolMode::Readout };
+        println!("[5] Selecting {:?} (Mode {})...", packet, packet.as_char());
+        if let Err(e) = session.enter_readout(packet) {
+            exit_with(session, &format!("FAILED: {}", e));
         }
-        let _ = port.flush();
         println!();
     }
 
-    // Step 6: Read data until ETX
-    let (buf_size, idle_timeout) = if is_profile {
-        (524288usize, 15000u64) // 512KB, 15s for profiles
+    // Step 6: Read data blocks until ETX
+    let config = if is_profile {
+        ReadConfig::load_profile()
+    } else if mode == "short" {
+        ReadConfig::short_read()
     } else {
-        match mode {
-            "full" => (131072, 5000),
-            "short" => (8192, 3000),
-            _ => (131072, 5000),
-        }
+        ReadConfig::full_read()
     };
 
-    println!("[6] Reading data (waiting for ETX, idle timeout: {}s)...", idle_timeout / 1000);
-    let mut data_buf = vec![0u8; buf_size];
-    let mut total_read = 0;
-    let mut found_etx = false;
+    println!("[6] Reading data (waiting for ETX, idle timeout: {}s)...", config.idle_timeout_ms / 1000);
     let read_start = Instant::now();
-    let mut last_read_time = Instant::now();
-
-    std::thread::sleep(Duration::from_millis(500));
-
-    loop {
-        match port.read(&mut data_buf[total_read..]) {
-            Ok(n) if n > 0 => {
-                total_read += n;
-                last_read_time = Instant::now();
-                // Only print every 100+ bytes to reduce spam
-                if total_read < 1000 || total_read % 1000 < n {
-                    print!("\r    Received: {} bytes ({:.1}s)    ", total_read, read_start.elapsed().as_secs_f32());
-                }
-
-                // Check for ETX (0x03)
-                for i in (total_read.saturating_sub(n)..total_read).rev() {
-                    if data_buf[i] == 0x03 {
-                        found_etx = true;
-                        break;
-                    }
-                }
-                if found_etx {
-                    println!();
-                    println!("    ETX found! Data complete.");
-                    break;
-                }
-            }
-            Ok(_) => {
-                std::thread::sleep(Duration::from_millis(100));
-            }
-            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                std::thread::sleep(Duration::from_millis(100));
-            }
-            Err(e) => {
-                println!();
-                eprintln!("    Read error: {}", e);
-                break;
-            }
+    let mut total_read = 0;
+    let transfer = session.read_blocks(&config, |chunk, _| {
+        total_read += chunk.len();
+        // Only print every 1000 bytes to reduce spam
+        if total_read < 1000 || total_read % 1000 < chunk.len() {
+            print!("\r    Received: {} bytes ({:.1}s)    ", total_read, read_start.elapsed().as_secs_f32());
         }
+    }, |_| {});
+    println!();
 
-        if last_read_time.elapsed() > Duration::from_millis(idle_timeout) {
-            println!();
-            if total_read == 0 {
-                eprintln!("    Timeout: No data received ({}ms idle)", idle_timeout);
-            } else {
-                println!("    Idle timeout: {} bytes received but no ETX", total_read);
+    let (data, found_etx) = match transfer {
+        Ok(transfer) => {
+            println!("    {} blocks, {} NAK, {} with BCC error", transfer.blocks, transfer.naks, transfer.bad_blocks);
+            let mut data = transfer.data;
+            if !transfer.complete {
+                println!("    Idle timeout: no ETX");
+                data.extend_from_slice(&session.decoder().take_pending());
             }
-            break;
+            (data, transfer.complete)
         }
-
-        // Overall timeout: 5 minutes for profiles
-        if is_profile && read_start.elapsed() > Duration::from_secs(300) {
-            println!();
-            println!("    Overall timeout: 5 minutes");
-            break;
+        Err(e) => {
+            eprintln!("    Read error: {}", e);
+            (session.decoder().take_pending(), false)
         }
-    }
+    };
     println!();
 
     // Step 7: Send Break command
     println!("[7] Sending Break command...");
-    let break_cmd = build_break_command();
-    print!("    TX: ");
-    print_hex(&break_cmd);
-    let _ = port.write_all(&break_cmd);
-    let _ = port.flush();
-    std::thread::sleep(Duration::from_millis(100));
-    drop(port);
+    session.close();
     println!("    Port closed.");
     println!();
 
@@ -449,87 +222,94 @@ fn main() {
     println!("Duration: {:.2}s", read_start.elapsed().as_secs_f32());
     println!();
 
-    if total_read > 0 {
-        let stx_pos = data_buf[..total_read].iter().position(|&b| b == 0x02);
-        let etx_pos = data_buf[..total_read].iter().position(|&b| b == 0x03);
+    if data.is_empty() {
+        return;
+    }
 
-        if let (Some(stx), Some(etx)) = (stx_pos, etx_pos) {
-            let data_slice = &data_buf[stx + 1..etx];
-            let data_str = String::from_utf8_lossy(data_slice);
+    let data_str = String::from_utf8_lossy(&data);
+    println!("--- Data ({} bytes) ---", data.len());
+    let mut line_count = 0;
+    for line in data_str.lines() {
+        let line = line.trim_matches(|c: char| c.is_ascii_control() || c.is_whitespace());
+        if line.is_empty() || line == "!" {
+            continue;
+        }
+        line_count += 1;
+        // For profiles, limit output to first 100 lines
+        if is_profile && line_count > 100 {
+            println!("  ... (truncated at 100 lines)");
+            // Count remaining
+            let remaining = data_str.lines()
+                .filter(|l| !l.trim().is_empty() && l.trim() != "!")
+                .count();
+            println!("  Total lines: {}", remaining);
+            break;
+        }
+        println!("  {}", line);
+    }
+    if !is_profile || line_count <= 100 {
+        println!("--- End ({} lines) ---", line_count);
+    }
 
-            // BCC check
-            if etx + 1 < total_read {
-                let received_bcc = data_buf[etx + 1];
-                let mut calculated_bcc: u8 = 0;
-                for &b in &data_buf[stx + 1..=etx] {
-                    calculated_bcc ^= b;
-                }
-                println!("BCC: received=0x{:02X}, calculated=0x{:02X} -> {}",
-                    received_bcc, calculated_bcc,
-                    if received_bcc == calculated_bcc { "OK" } else { "MISMATCH!" });
-            }
+    if !is_profile {
+        println!();
+        print_mass_summary(&MassData::parse(&iec62056::parse_data_block(&data_str)));
+    }
+}
 
-            println!();
-            println!("--- Data ({} bytes) ---", data_slice.len());
-            let mut line_count = 0;
-            for line in data_str.lines() {
-                let line = line.trim();
-                if line.is_empty() || line == "!" {
-                    continue;
-                }
-                line_count += 1;
-                // For profiles, limit output to first 100 lines
-                if is_profile && line_count > 100 {
-                    println!("  ... (truncated at 100 lines)");
-                    // Count remaining
-                    let remaining = data_str.lines()
-                        .filter(|l| !l.trim().is_empty() && l.trim() != "!")
-                        .count();
-                    println!("  Total lines: {}", remaining);
-                    break;
-                }
-                println!("  {}", line);
+/// Print what the shared MASS parsers found in the readout
+fn print_mass_summary(mass: &MassData) {
+    let print_log = |name: &str, log: &EventLog| {
+        println!("  {}: {} (records: {})", name, log.count, log.records.len());
+        for record in &log.records {
+            let duration = record.duration_minutes.map(|m| format!("{} min", m)).unwrap_or_else(|| "ongoing".to_string());
+            println!("    #{} {} -> {} ({})", record.index, record.start, record.end, duration);
+        }
+    };
+
+    println!("--- MASS ---");
+    print_log("Three-phase outages", &mass.outages.three_phase);
+    print_log("L1 outages", &mass.outages.phase_l1);
+    print_log("L2 outages", &mass.outages.phase_l2);
+    print_log("L3 outages", &mass.outages.phase_l3);
+    print_log("Voltage warnings", &mass.warnings.voltage);
+    print_log("Current warnings", &mass.warnings.current);
+    print_log("Magnetic warnings", &mass.warnings.magnetic);
+    println!("  Top cover openings: {}", mass.warnings.top_cover_count);
+
+    match &mass.dst {
+        Some(dst) => {
+            println!("  DST: {}", if dst.enabled { "enabled" } else { "disabled" });
+            for period in &dst.periods {
+                println!("    #{} {} {} -> {}", period.id, period.offset, period.forward, period.backward);
             }
-            if !is_profile || line_count <= 100 {
-                println!("--- End ({} lines) ---", line_count);
+        }
+        None => println!("  DST: not in readout"),
+    }
+
+    match &mass.tariffs {
+        Some(tariffs) => {
+            for (day, slots) in [("Weekdays", &tariffs.weekdays), ("Saturday", &tariffs.saturday), ("Sunday", &tariffs.sunday)] {
+                let slots: Vec<String> = slots.iter().map(|s| format!("{} T{}", s.start, s.tariff)).collect();
+                println!("  Tariffs {}: {}", day, slots.join(", "));
             }
-        } else {
-            println!("Could not find STX/ETX markers in data");
-            println!("First 500 bytes (hex):");
-            let show = total_read.min(500);
-            print_hex(&data_buf[..show]);
-            println!();
-            println!("First 500 bytes (ASCII):");
-            let ascii = String::from_utf8_lossy(&data_buf[..show]);
-            println!("{}", ascii.escape_default());
         }
+        None => println!("  Tariffs: not in readout"),
     }
-}
 
-/// Calculate BCC (XOR of all bytes)
-fn calculate_bcc(data: &[u8]) -> u8 {
-    let mut bcc: u8 = 0;
-    for &b in data {
-        bcc ^= b;
+    let resets = &mass.demand_resets;
+    println!("  Demand resets: {}", resets.count.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string()));
+    for period in &resets.periods {
+        let demand = period.max_demand.map(|d| format!("{:.3} kW", d)).unwrap_or_else(|| "-".to_string());
+        println!("    *{} reset {} max {} {}", period.period, period.reset_time, demand, period.max_demand_time);
     }
-    bcc
 }
 
-/// Build password command: SOH P1 STX (password) ETX BCC
-fn build_password_command(password: &str) -> Vec<u8> {
-    let mut msg = Vec::new();
-    msg.push(0x01); // SOH
-    msg.push(b'P');
-    msg.push(b'1');
-    msg.push(0x02); // STX
-    msg.push(b'(');
-    msg.extend_from_slice(password.as_bytes());
-    msg.push(b')');
-    msg.push(0x03); // ETX
-
-    let bcc = calculate_bcc(&msg[3..]); // BCC from STX to ETX
-    msg.push(bcc);
-    msg
+/// Print an error, end the session with a break and exit
+fn exit_with(mut session: MeterSession, message: &str) -> ! {
+    eprintln!("    {}", message);
+    session.close();
+    std::process::exit(1);
 }
 
 /// Resolve date range shorthand into IEC format
@@ -598,78 +378,6 @@ fn resolve_date_range(input: Option<&str>) -> Option<String> {
     }
 }
 
-/// Build load profile R2 command: SOH R2 STX P.XX(range) ETX BCC
-fn build_load_profile_command(profile_number: u8, date_range: Option<&str>) -> Vec<u8> {
-    let mut msg = Vec::new();
-    msg.push(0x01); // SOH
-    msg.push(b'R');
-    msg.push(b'2');
-    msg.push(0x02); // STX
-    let profile_str = format!("P.{:02}", profile_number);
-    msg.extend_from_slice(profile_str.as_bytes());
-    msg.push(b'(');
-    match date_range {
-        Some(range) if !range.is_empty() => {
-            msg.extend_from_slice(range.as_bytes());
-        }
-        _ => {
-            msg.push(b';'); // Read all data
-        }
-    }
-    msg.push(b')');
-    msg.push(0x03); // ETX
-
-    let bcc = calculate_bcc(&msg[3..]); // BCC from STX to ETX
-    msg.push(bcc);
-    msg
-}
-
-/// Build break command: SOH B0 ETX BCC
-fn build_break_command() -> Vec<u8> {
-    let mut msg = Vec::new();
-    msg.push(0x01); // SOH
-    msg.push(b'B');
-    msg.push(b'0');
-    msg.push(0x03); // ETX
-    let bcc = calculate_bcc(&msg[3..]); // BCC from ETX only
-    msg.push(bcc);
-    msg
-}
-
-fn send_break_and_exit(port: &mut Box<dyn serialport::SerialPort>) -> ! {
-    let break_cmd = build_break_command();
-    let _ = port.write_all(&break_cmd);
-    let _ = port.flush();
-    std::thread::sleep(Duration::from_millis(100));
-    std::process::exit(1);
-}
-
-fn baud_from_char(c: char) -> u32 {
-    match c {
-        '0' => 300,
-        '1' => 600,
-        '2' => 1200,
-        '3' => 2400,
-        '4' => 4800,
-        '5' => 9600,
-        '6' => 19200,
-        _ => 300,
-    }
-}
-
-fn char_from_baud(baud: u32) -> char {
-    match baud {
-        300 => '0',
-        600 => '1',
-        1200 => '2',
-        2400 => '3',
-        4800 => '4',
-        9600 => '5',
-        19200 => '6',
-        _ => '0',
-    }
-}
-
 fn print_hex(data: &[u8]) {
     for b in data {
         print!("{:02X} ", b);
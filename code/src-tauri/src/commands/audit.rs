@@ -0,0 +1,165 @@
+//! Audit trail of meter programming
+//!
+//! Every W2 write and every password login sent by the command layer is
+//! recorded in the `audit_log` table with the meter, the operator and the
+//! meter's reply; a login is recorded under its level (`P1`). Recording
+//! never fails a write: a database problem is logged and the write goes on.
+//! Passwords (the `96.96` register, in any notation) are recorded masked.
+
+use super::{password, CONNECTION_STATE};
+use crate::error::AppError;
+use crate::serial::ObisCode;
+use crate::storage::{self, AuditEntry};
+use std::path::Path;
+
+/// Setting holding the operator name recorded with each write
+pub const OPERATOR_SETTING: &str = "operator";
+
+/// Stands for a password in logs
+const MASKED: &str = "********";
+
+/// `value` as it may appear in logs: masked for the password registers
+pub fn loggable<'a>(obis_code: &str, value: &'a str) -> &'a str {
+    if is_password_register(obis_code) {
+        MASKED
+    } else {
+        value
+    }
+}
+
+/// Whether `obis_code` may be a password register, e.g. `96.96`,
+/// `0.0.96.96.0` or `0-0:96.96.0*255`; a code that does not parse cannot be
+/// ruled out and counts as one
+pub fn is_password_register(obis_code: &str) -> bool {
+    let Ok(code) = obis_code.parse::<ObisCode>() else {
+        return true;
+    };
+    password::REGISTERS.iter().any(|(_, register)| {
+        register.parse::<ObisCode>().map_or(true, |register| code.canonical_eq(&register))
+    })
+}
+
+/// Record a write of `new_value` to `obis_code` with the meter's reply
+pub fn record_write(obis_code: &str, previous_value: Option<&str>, new_value: &str, result: &Result<(), AppError>) {
+    let result = match result {
+        Ok(()) => "ACK",
+        Err(AppError::Nak { .. }) => "NAK",
+        Err(_) => "ERROR",
+    };
+    let previous_value = previous_value.map(|value| loggable(obis_code, value));
+    let new_value = loggable(obis_code, new_value);
+    if let Err(e) = save_entry(obis_code, previous_value, new_value, result) {
+        log::error!("Failed to record audit entry for {}: {}", obis_code, e);
+    }
+}
+
+/// Record a login with the level `level` password, masked, and the meter's reply
+pub fn record_login(level: u8, result: &Result<bool, AppError>) {
+    let result = match result {
+        Ok(true) => "ACK",
+        Ok(false) => "NAK",
+        Err(_) => "ERROR",
+    };
+    let command = format!("P{}", level);
+    if let Err(e) = save_entry(&command, None, MASKED, result) {
+        log::error!("Failed to record audit entry for {}: {}", command, e);
+    }
+}
+
+fn save_entry(obis_code: &str, previous_value: Option<&str>, new_value: &str, result: &str) -> Result<(), AppError> {
+    let identity = CONNECTION_STATE.lock()?.identity.clone();
+    let (meter_serial, meter_flag) = identity
+        .map(|identity| (identity.serial_number.unwrap_or_default(), identity.manufacturer))
+        .unwrap_or_default();
+
+    let guard = storage::get_database()?;
+    let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+    let operator = db.get_setting(OPERATOR_SETTING)?
+        .filter(|operator| !operator.trim().is_empty())
+        .or_else(|| std::env::var("USERNAME").or_else(|_| std::env::var("USER")).ok())
+        .unwrap_or_default();
+
+    db.save_audit_entry(&AuditEntry {
+        id: 0,
+        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        meter_serial,
+        meter_flag,
+        operator,
+        obis_code: obis_code.to_string(),
+        previous_value: previous_value.map(str::to_string),
+        new_value: new_value.to_string(),
+        result: result.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+    })?;
+    Ok(())
+}
+
+/// Export the audit log, optionally of one meter only, to a CSV file in the
+/// audit folder next to the executable and return its path
+#[tauri::command]
+pub async fn export_audit_log(meter_serial: Option<String>) -> Result<String, AppError> {
+    let entries = {
+        let guard = storage::get_database()?;
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+        db.get_audit_log(meter_serial.as_deref(), u32::MAX)?
+    };
+
+    let exe_path = std::env::current_exe()
+        .map_err(|e| AppError::File { path: String::new(), reason: format!("Failed to get exe path: {}", e) })?;
+    let audit_dir = exe_path.parent().unwrap_or(Path::new(".")).join("omnicore-audit-logs");
+    std::fs::create_dir_all(&audit_dir).map_err(|e| AppError::File {
+        path: audit_dir.display().to_string(),
+        reason: format!("Failed to create audit directory: {}", e),
+    })?;
+
+    // Generate filename: audit[-serialnumber]-YYYYmmddHHnn.csv
+    let serial = meter_serial
+        .map(|serial| format!("-{}", serial.replace(|c: char| !c.is_alphanumeric(), "_")))
+        .unwrap_or_default();
+    let file_path = audit_dir.join(format!("audit{}-{}.csv", serial, chrono::Local::now().format("%Y%m%d%H%M")));
+
+    std::fs::write(&file_path, to_csv(&entries)).map_err(|e| AppError::File {
+        path: file_path.display().to_string(),
+        reason: format!("Failed to write audit log: {}", e),
+    })?;
+
+    log::info!("Exported {} audit entries to {:?}", entries.len(), file_path);
+    Ok(file_path.display().to_string())
+}
+
+/// Oldest entry first, in the `;` separated UTF-8 BOM format of the page exports
+fn to_csv(entries: &[AuditEntry]) -> String {
+    let quote = |value: &str| format!("\"{}\"", value.replace('"', "\"\""));
+    let header = ["Zaman", "Seri No", "Flag", "Operatör", "OBIS", "Önceki Değer", "Yeni Değer", "Sonuç", "Sürüm"];
+
+    let mut rows = vec![header.map(quote).join(";")];
+    for entry in entries.iter().rev() {
+        rows.push([
+            entry.timestamp.as_str(),
+            &entry.meter_serial,
+            &entry.meter_flag,
+            &entry.operator,
+            &entry.obis_code,
+            entry.previous_value.as_deref().unwrap_or(""),
+            &entry.new_value,
+            &entry.result,
+            &entry.app_version,
+        ].map(quote).join(";"));
+    }
+    format!("\u{FEFF}{}\n", rows.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loggable() {
+        for code in ["96.96", "96.96.0", "0.0.96.96.0", "0-0:96.96.0*255", " 96.96 ", "0.0.96.96"] {
+            assert_eq!(loggable(code, "12345678"), "********", "{}", code);
+        }
+        for code in ["96.1.0", "0.9.1", "96.9", "96.96.1"] {
+            assert_eq!(loggable(code, "12345678"), "12345678", "{}", code);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Capture file management
+//!
+//! Starts and stops recording of the meter conversation and saves the
+//! capture next to the executable, for replay with the "replay" connection type.
+
+use crate::error::AppError;
+use crate::serial::capture;
+use std::path::Path;
+
+/// Get the captures folder path (next to executable)
+fn get_captures_folder() -> Result<std::path::PathBuf, AppError> {
+    let file_error = |path: &Path, reason: String| AppError::File { path: path.display().to_string(), reason };
+    let exe_path = std::env::current_exe()
+        .map_err(|e| file_error(Path::new(""), format!("Failed to get exe path: {}", e)))?;
+    let exe_dir = exe_path.parent()
+        .ok_or_else(|| file_error(&exe_path, "Failed to get exe directory".to_string()))?;
+    let captures_dir = exe_dir.join("omnicore-meter-captures");
+
+    if !captures_dir.exists() {
+        std::fs::create_dir_all(&captures_dir)
+            .map_err(|e| file_error(&captures_dir, format!("Failed to create captures directory: {}", e)))?;
+    }
+
+    Ok(captures_dir)
+}
+
+/// Start recording all port traffic (restarts a recording in progress)
+#[tauri::command]
+pub fn start_capture() -> Result<(), AppError> {
+    log::info!("Capture started");
+    capture::start_recording();
+    Ok(())
+}
+
+/// Stop recording and save the capture; returns the file path,
+/// or `None` if nothing was being recorded
+#[tauri::command]
+pub fn stop_capture() -> Result<Option<String>, AppError> {
+    let Some(capture) = capture::stop_recording() else {
+        return Ok(None);
+    };
+
+    let filename = format!("capture-{}.json", chrono::Local::now().format("%Y%m%d%H%M%S"));
+    let file_path = get_captures_folder()?.join(filename);
+    capture.save(&file_path)?;
+
+    log::info!("Capture saved to: {:?} ({} events)", file_path, capture.events.len());
+    Ok(Some(file_path.display().to_string()))
+}
+
+/// Whether a recording is in progress
+#[tauri::command]
+pub fn get_capture_status() -> Result<bool, AppError> {
+    Ok(capture::is_recording())
+}
@@ -2,10 +2,24 @@
 //!
 //! Provides a centralized way to emit communication events to the frontend,
 //! eliminating duplicate emit_log closures throughout the codebase.
+//! `emit_log` and `emit_progress` are the shorthands the commands use.
 
 use super::types::{LogEvent, ProgressEvent};
 use tauri::{Emitter, Window};
 
+/// Emit a `comm-log` event, or write to the log when there is no window
+pub fn emit_log<'a>(window: impl Into<Option<&'a Window>>, log_type: &str, message: &str) {
+    match window.into() {
+        Some(w) => EventEmitter::new(w).log_simple(log_type, message),
+        None => log::info!("[{}] {}", log_type, message),
+    }
+}
+
+/// Emit a `read-progress` event
+pub fn emit_progress(window: &Window, step: u32, total: u32, message: &str) {
+    EventEmitter::new(window).progress(step, total, message);
+}
+
 /// Event emitter for meter communication
 ///
 /// Wraps a Tauri Window and provides convenient methods for emitting
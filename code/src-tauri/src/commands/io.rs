@@ -1,11 +1,13 @@
 //! I/O utilities for serial communication
 //!
-//! Provides helper functions for reading data from the meter,
+//! Provides helper functions for reading frames from the meter,
 //! verifying BCC, and sending commands.
 
-use crate::serial::iec62056::{self, control};
-use serialport::SerialPort;
-use std::io::Read;
+use super::operations::CancelToken;
+use crate::error::AppError;
+use crate::serial::iec62056::{self, Frame, FrameDecoder, IecMode, MeterIdent};
+use crate::serial::transport::MeterTransport;
+use std::ops::Range;
 use std::time::{Duration, Instant};
 use tauri::{Emitter, Window};
 
@@ -17,9 +19,11 @@ use tauri::{Emitter, Window};
 /// - auto + auto (0): try 9600, then 300
 /// - serial + specific baud: use that baud
 /// - serial + auto (0): try 9600, then 300, then 19200
+/// - tcp: the converter's serial-side baud, 300 if not configured
 pub fn resolve_initial_bauds(connection_type: &str, configured_baud: u32) -> Vec<u32> {
     match connection_type {
         "optical" => vec![300],
+        "tcp" => vec![if configured_baud > 0 { configured_baud } else { 300 }],
         "auto" => {
             if configured_baud > 0 {
                 vec![configured_baud]
@@ -40,10 +44,10 @@ pub fn resolve_initial_bauds(connection_type: &str, configured_baud: u32) -> Vec
 
 /// Determine the target baud rate for data transfer after handshake.
 ///
-/// - optical or auto baud (0): negotiate to meter's max supported baud
+/// - optical, tcp or auto baud (0): negotiate to meter's max supported baud
 /// - serial with explicit baud: keep that baud (no switch needed)
 pub fn resolve_target_baud(connection_type: &str, configured_baud: u32, meter_max_baud: u32, meter_baud_char: char) -> (u32, char) {
-    let target = if connection_type == "optical" || configured_baud == 0 {
+    let target = if connection_type == "optical" || connection_type == "tcp" || configured_baud == 0 {
         meter_max_baud
     } else {
         configured_baud
@@ -53,17 +57,53 @@ pub fn resolve_target_baud(connection_type: &str, configured_baud: u32, meter_ma
     (target, baud_char)
 }
 
-/// Result of reading until ETX
-#[derive(Debug)]
-pub struct ReadResult {
-    /// The data buffer with received bytes
-    pub data: Vec<u8>,
-    /// Number of bytes actually read
-    pub bytes_read: usize,
-    /// Whether ETX was found
-    pub found_etx: bool,
-    /// Total read duration
-    pub duration: Duration,
+/// Determine the baud rate and option select character for a session on `port`.
+///
+/// A transport that cannot follow the switch (TCP converter with a fixed
+/// serial side) stays at the handshake baud; otherwise see `resolve_target_baud`.
+pub fn resolve_session_baud<T: MeterTransport + ?Sized>(
+    port: &T,
+    connection_type: &str,
+    configured_baud: u32,
+    handshake_baud: u32,
+    ident: &MeterIdent,
+) -> (u32, char) {
+    if port.can_switch_baud() {
+        return resolve_target_baud(connection_type, configured_baud, ident.max_baud_rate, ident.baud_char);
+    }
+    let baud_char = crate::serial::iec62056::char_from_baud_rate(handshake_baud)
+        .unwrap_or(ident.baud_char);
+    (handshake_baud, baud_char)
+}
+
+/// Determine the protocol mode of the session.
+///
+/// A configured mode ("A"-"D") wins; otherwise the mode announced by the
+/// identification is used. Mode D must be configured, its identification
+/// looks like Mode C at 2400 baud.
+pub fn resolve_protocol_mode(configured: Option<&str>, ident: &MeterIdent) -> IecMode {
+    configured
+        .and_then(IecMode::from_config)
+        .unwrap_or(ident.mode)
+}
+
+/// Enter the readout in a mode without option select (A, B, D).
+///
+/// No ACK is sent: the meter starts the readout on its own after the
+/// identification. In Mode B the port follows the meter to the announced baud
+/// rate; Modes A and D stay at the handshake rate. Returns the readout baud rate.
+pub fn enter_readout_without_ack<T: MeterTransport + ?Sized>(
+    port: &mut T,
+    mode: IecMode,
+    ident: &MeterIdent,
+    handshake_baud: u32,
+) -> Result<u32, AppError> {
+    if mode != IecMode::B || ident.max_baud_rate == handshake_baud {
+        return Ok(handshake_baud);
+    }
+    port.set_baud_rate(ident.max_baud_rate)
+        .map_err(|e| AppError::BaudSwitch { baud: ident.max_baud_rate, reason: e.to_string() })?;
+    Ok(ident.max_baud_rate)
 }
 
 /// Read configuration
@@ -77,6 +117,10 @@ pub struct ReadConfig {
     pub initial_delay_ms: u64,
     /// Sleep duration between read attempts (ms)
     pub read_interval_ms: u64,
+    /// How many times a block with a BCC error is requested again with NAK
+    pub bcc_retries: u32,
+    /// Stops the read when the operation is cancelled
+    pub cancel: Option<CancelToken>,
 }
 
 impl Default for ReadConfig {
@@ -86,6 +130,8 @@ impl Default for ReadConfig {
             idle_timeout_ms: 3000,  // 3 seconds idle timeout
             initial_delay_ms: 300,  // 300ms initial delay
             read_interval_ms: 100,  // 100ms between reads
+            bcc_retries: iec62056::DEFAULT_BCC_RETRIES,
+            cancel: None,
         }
     }
 }
@@ -98,6 +144,8 @@ impl ReadConfig {
             idle_timeout_ms: 3000,   // 3 seconds
             initial_delay_ms: 300,
             read_interval_ms: 100,
+            bcc_retries: iec62056::DEFAULT_BCC_RETRIES,
+            cancel: None,
         }
     }
 
@@ -108,6 +156,37 @@ impl ReadConfig {
             idle_timeout_ms: 5000,   // 5 seconds
             initial_delay_ms: 300,
             read_interval_ms: 100,
+            bcc_retries: iec62056::DEFAULT_BCC_RETRIES,
+            cancel: None,
+        }
+    }
+
+    /// Same configuration with a different BCC retry count
+    pub fn with_bcc_retries(mut self, bcc_retries: u32) -> Self {
+        self.bcc_retries = bcc_retries;
+        self
+    }
+
+    /// Same configuration, stopping when `cancel` is set
+    pub fn with_cancel(mut self, cancel: Option<CancelToken>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Whether the operation this read belongs to was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.is_cancelled())
+    }
+
+    /// Configuration for a single command response (identification, R2 answer, ACK/NAK)
+    pub fn response(timeout_ms: u64) -> Self {
+        Self {
+            buffer_size: 512,
+            idle_timeout_ms: timeout_ms,
+            initial_delay_ms: 0,
+            read_interval_ms: 0,
+            bcc_retries: iec62056::DEFAULT_BCC_RETRIES,
+            cancel: None,
         }
     }
 
@@ -118,141 +197,244 @@ impl ReadConfig {
             idle_timeout_ms: 15000,  // 15 seconds for initial response
             initial_delay_ms: 500,
             read_interval_ms: 100,
+            bcc_retries: iec62056::DEFAULT_BCC_RETRIES,
+            cancel: None,
         }
     }
 }
 
-/// Read from port until ETX byte is found or timeout occurs
+/// Read from port into the decoder until it yields a frame
 ///
-/// This function implements the common pattern of reading data blocks
-/// from the meter, looking for the ETX terminator.
-///
-/// # Arguments
-/// * `port` - The serial port to read from
-/// * `window` - Optional window for emitting activity events
-/// * `config` - Read configuration
-///
-/// # Returns
-/// * `Ok(ReadResult)` - The read data and metadata
-/// * `Err(String)` - Error message if read failed
-pub fn read_until_etx(
-    port: &mut Box<dyn SerialPort>,
+/// Returns `Ok(None)` when no complete frame arrives within the idle timeout
+/// (time since the last received byte); the partial data stays in the decoder.
+/// Returns `Err(AppError::Cancelled)` once `config.cancel` is set.
+pub fn read_frame<T: MeterTransport + ?Sized>(
+    port: &mut T,
+    decoder: &mut FrameDecoder,
     window: Option<&Window>,
     config: &ReadConfig,
-) -> Result<ReadResult, String> {
-    let mut data_buf = vec![0u8; config.buffer_size];
-    let mut total_read = 0;
-    let mut found_etx = false;
-    let read_start = Instant::now();
-    let mut last_read_time = Instant::now();
+) -> Result<Option<Frame>, AppError> {
+    read_frame_with(port, decoder, window, config, |_, _| {})
+}
+
+/// Same as `read_frame`, calling `on_rx(chunk, pending)` after every read
+/// with the new bytes and everything buffered for the frame so far
+pub fn read_frame_with<T: MeterTransport + ?Sized>(
+    port: &mut T,
+    decoder: &mut FrameDecoder,
+    window: Option<&Window>,
+    config: &ReadConfig,
+    mut on_rx: impl FnMut(&[u8], &[u8]),
+) -> Result<Option<Frame>, AppError> {
+    if let Some(frame) = decoder.next_frame() {
+        return Ok(Some(frame));
+    }
 
-    // Initial delay for data to start arriving
-    std::thread::sleep(Duration::from_millis(config.initial_delay_ms));
+    let mut chunk = [0u8; 8192];
+    let mut last_read_time = Instant::now();
 
     loop {
-        match port.read(&mut data_buf[total_read..]) {
+        if config.is_cancelled() {
+            return Err(AppError::Cancelled);
+        }
+
+        match port.read(&mut chunk) {
             Ok(n) if n > 0 => {
-                total_read += n;
                 last_read_time = Instant::now();
-
-                // Emit activity if window is provided
                 if let Some(w) = window {
                     let _ = w.emit("comm-activity", serde_json::json!({"type": "rx"}));
                 }
 
-                // Check for ETX
-                if total_read >= 2 {
-                    for i in 0..total_read - 1 {
-                        if data_buf[i] == control::ETX && i + 1 < total_read {
-                            found_etx = true;
-                            break;
-                        }
-                    }
-                    if found_etx {
-                        break;
-                    }
-                }
-
-                // Check if buffer is full
-                if total_read >= config.buffer_size {
-                    break;
+                decoder.push(&chunk[..n]);
+                on_rx(&chunk[..n], decoder.pending());
+                if let Some(frame) = decoder.next_frame() {
+                    return Ok(Some(frame));
                 }
             }
             Ok(_) => {
-                std::thread::sleep(Duration::from_millis(config.read_interval_ms));
+                std::thread::sleep(Duration::from_millis(config.read_interval_ms.max(1)));
             }
             Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
                 std::thread::sleep(Duration::from_millis(config.read_interval_ms));
             }
             Err(e) => {
-                return Err(format!("Okuma hatası: {}", e));
+                return Err(AppError::io(e));
             }
         }
 
-        // Check idle timeout
         if last_read_time.elapsed() > Duration::from_millis(config.idle_timeout_ms) {
-            break;
+            return Ok(None);
         }
     }
-
-    // Truncate buffer to actual size
-    data_buf.truncate(total_read);
-
-    Ok(ReadResult {
-        data: data_buf,
-        bytes_read: total_read,
-        found_etx,
-        duration: read_start.elapsed(),
-    })
 }
 
-/// Verify BCC (Block Check Character) in the received data
+/// Read the next frame sent by the meter, skipping echoes of our own messages
 ///
-/// Finds STX and ETX markers, calculates BCC from data between them,
-/// and compares with the received BCC byte after ETX.
+/// Optical heads and RS-485 adapters may echo what we transmit; request,
+/// option select and command frames only ever come from our side.
+pub fn read_reply<T: MeterTransport + ?Sized>(
+    port: &mut T,
+    decoder: &mut FrameDecoder,
+    window: Option<&Window>,
+    config: &ReadConfig,
+) -> Result<Option<Frame>, AppError> {
+    loop {
+        match read_frame(port, decoder, window, config)? {
+            Some(Frame::Request { .. } | Frame::OptionSelect { .. } | Frame::Command { .. }) => continue,
+            other => return Ok(other),
+        }
+    }
+}
+
+/// Read a single-block reply (e.g. an R2 answer), asking the meter to repeat
+/// it with NAK while the BCC is wrong, up to `config.bcc_retries` times
 ///
-/// # Returns
-/// * `Ok(true)` - BCC matches
-/// * `Ok(false)` - BCC mismatch (with calculated and received values)
-/// * `Err(String)` - Could not verify (missing markers)
-pub fn verify_bcc(data: &[u8]) -> Result<bool, String> {
-    let stx_idx = data.iter().position(|&b| b == control::STX)
-        .ok_or("STX bulunamadı")?;
-    let etx_idx = data.iter().position(|&b| b == control::ETX)
-        .ok_or("ETX bulunamadı")?;
-
-    if etx_idx + 1 >= data.len() {
-        return Err("BCC byte'ı yok".to_string());
+/// A returned `DataBlock` with `bcc_ok: false` never passed the check and
+/// its content must be treated as untrusted.
+pub fn read_checked_reply<T: MeterTransport + ?Sized>(
+    port: &mut T,
+    decoder: &mut FrameDecoder,
+    window: Option<&Window>,
+    config: &ReadConfig,
+) -> Result<Option<Frame>, AppError> {
+    let mut repeats = 0;
+    loop {
+        match read_reply(port, decoder, window, config)? {
+            Some(Frame::DataBlock { bcc_ok: false, .. }) if repeats < config.bcc_retries => {
+                repeats += 1;
+                send_control(port, window, &iec62056::build_block_nak())?;
+            }
+            other => return Ok(other),
+        }
     }
+}
 
-    let received_bcc = data[etx_idx + 1];
-    let calculated_bcc = iec62056::calculate_bcc(&data[stx_idx + 1..=etx_idx]);
+/// Read the identification message sent in answer to the request message
+///
+/// Returns `None` if nothing identifiable arrived before the timeout.
+pub fn read_identification<T: MeterTransport + ?Sized>(
+    port: &mut T,
+    window: Option<&Window>,
+    config: &ReadConfig,
+) -> Option<String> {
+    read_identification_with(port, &mut FrameDecoder::new(), window, config)
+}
 
-    Ok(calculated_bcc == received_bcc)
+/// Like `read_identification`, but bytes received after the identification
+/// stay in `decoder`. In Modes A and D the readout follows right behind it.
+pub fn read_identification_with<T: MeterTransport + ?Sized>(
+    port: &mut T,
+    decoder: &mut FrameDecoder,
+    window: Option<&Window>,
+    config: &ReadConfig,
+) -> Option<String> {
+    loop {
+        match read_reply(port, decoder, window, config) {
+            Ok(Some(Frame::Identification(ident))) => return Some(ident),
+            Ok(Some(_)) => continue,
+            Ok(None) | Err(_) => return None,
+        }
+    }
 }
 
-/// Extract the data portion between STX and ETX
+/// Data received in a (possibly multi-block) transfer
+#[derive(Debug, Default)]
+pub struct BlockTransfer {
+    /// Data of all blocks, without framing
+    pub data: Vec<u8>,
+    /// Number of blocks accepted so far
+    pub blocks: u32,
+    /// Number of NAKs sent for blocks with a BCC error
+    pub naks: u32,
+    /// Blocks kept despite a BCC error after all repeats were used up
+    pub bad_blocks: u32,
+    /// Byte ranges in `data` that come from those blocks; values parsed from
+    /// them must be treated as untrusted
+    pub untrusted: Vec<Range<usize>>,
+    /// Whether the final block (ending with ETX) was received
+    pub complete: bool,
+    /// Whether the transfer was stopped by cancelling the operation
+    pub cancelled: bool,
+}
+
+/// Receive a transfer of partial blocks (`STX data EOT BCC`) ending with a
+/// final block (`STX data ETX BCC`)
 ///
-/// # Returns
-/// The slice of data between STX (exclusive) and ETX (exclusive)
-pub fn extract_data_block(data: &[u8]) -> Option<&[u8]> {
-    let stx_idx = data.iter().position(|&b| b == control::STX)?;
-    let etx_idx = data.iter().position(|&b| b == control::ETX)?;
-
-    if stx_idx < etx_idx {
-        Some(&data[stx_idx + 1..etx_idx])
-    } else {
-        None
+/// Every block is BCC-checked. A good partial block is acknowledged with ACK so
+/// the meter sends the next one; a block with a BCC error is answered with NAK
+/// so the meter repeats it, up to `config.bcc_retries` times. `on_block` is
+/// called after each accepted block. On idle timeout or cancellation the
+/// transfer is returned incomplete and the unfinished block stays in the decoder.
+/// The caller sends the break to stop the meter.
+pub fn read_data_blocks<T: MeterTransport + ?Sized>(
+    port: &mut T,
+    decoder: &mut FrameDecoder,
+    window: Option<&Window>,
+    config: &ReadConfig,
+    mut on_rx: impl FnMut(&[u8], &[u8]),
+    mut on_block: impl FnMut(&BlockTransfer),
+) -> Result<BlockTransfer, AppError> {
+    let mut transfer = BlockTransfer::default();
+    let mut repeats = 0;
+
+    loop {
+        let frame = match read_frame_with(port, decoder, window, config, &mut on_rx) {
+            Err(AppError::Cancelled) => {
+                transfer.cancelled = true;
+                return Ok(transfer);
+            }
+            other => other?,
+        };
+        let (data, bcc_ok, last) = match frame {
+            Some(Frame::PartialBlock { data, bcc_ok }) => (data, bcc_ok, false),
+            Some(Frame::DataBlock { data, bcc_ok }) => (data, bcc_ok, true),
+            // Meter ended the session
+            Some(Frame::Break) => return Ok(transfer),
+            // Echoes of our own ACK/NAK and stray frames are not part of the data
+            Some(_) => continue,
+            None => return Ok(transfer),
+        };
+
+        if !bcc_ok && repeats < config.bcc_retries {
+            repeats += 1;
+            transfer.naks += 1;
+            send_control(port, window, &iec62056::build_block_nak())?;
+            continue;
+        }
+
+        if !bcc_ok {
+            transfer.bad_blocks += 1;
+            let start = transfer.data.len();
+            transfer.untrusted.push(start..start + data.len());
+        }
+        repeats = 0;
+        transfer.data.extend_from_slice(&data);
+        transfer.blocks += 1;
+        transfer.complete = last;
+        on_block(&transfer);
+
+        if last {
+            return Ok(transfer);
+        }
+        send_control(port, window, &iec62056::build_block_ack())?;
     }
 }
 
-/// Send break command to end the session
-pub fn send_break_command(port: &mut Box<dyn SerialPort>) -> Result<(), String> {
-    use std::io::Write;
+/// Send a single ACK/NAK control message
+fn send_control<T: MeterTransport + ?Sized>(port: &mut T, window: Option<&Window>, msg: &[u8]) -> Result<(), AppError> {
+    port.write_all(msg).map_err(AppError::io)?;
+    port.flush().map_err(AppError::io)?;
+    if let Some(w) = window {
+        let _ = w.emit("comm-activity", serde_json::json!({"type": "tx"}));
+    }
+    Ok(())
+}
 
+/// Send break command to end the session
+pub fn send_break_command<T: MeterTransport + ?Sized>(port: &mut T) -> Result<(), AppError> {
     let break_cmd = iec62056::build_break_command();
-    port.write_all(&break_cmd).map_err(|e| format!("Break komutu gönderilemedi: {}", e))?;
-    port.flush().map_err(|e| format!("Flush hatası: {}", e))?;
+    port.write_all(&break_cmd).map_err(AppError::io)?;
+    port.flush().map_err(AppError::io)?;
     std::thread::sleep(Duration::from_millis(100));
     Ok(())
 }
@@ -5,629 +5,203 @@ pub mod state;
 pub mod events;
 pub mod io;
 pub mod sessions;
+pub mod captures;
+pub mod session;
+pub mod operations;
+pub mod worker;
+pub mod packets;
+pub mod audit;
+pub mod password;
 
 pub use types::*;
+pub use crate::error::AppError;
 pub use state::CONNECTION_STATE;
-pub use events::EventEmitter;
-pub use io::{ReadConfig, ReadResult, read_until_etx, verify_bcc, extract_data_block, send_break_command, resolve_initial_bauds, resolve_target_baud};
+pub use session::{MeterSession, SessionOptions, SessionState};
+pub use operations::{CancelToken, Operation};
+pub use worker::{MeterWorker, OperationGuard, Readout};
+pub use events::{EventEmitter, emit_log, emit_progress};
+pub use io::{ReadConfig, send_break_command, resolve_initial_bauds, resolve_target_baud};
 pub use sessions::{save_session_file, list_session_files, load_session_file, delete_session_file};
+pub use audit::export_audit_log;
 
 use crate::{PortInfo, MeterIdentity, ConnectionParams};
-use crate::serial::iec62056::{self, ProtocolMode, control};
-use serialport::SerialPort;
-use std::io::{Read, Write};
+use crate::mass::{DstRule, DstSchedule, MassData, TariffSchedule};
+use crate::serial::iec62056::{self, MeterIdent, ObisDataItem, ProtocolMode, control};
+use crate::serial::{ObisCode, ObisValue};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
-use tauri::Emitter;
+
+/// How far the meter clock may run between writing it and reading it back
+const CLOCK_READ_BACK_TOLERANCE_S: i64 = 10;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Password level the programming commands log in with (P1)
+const PROGRAMMING_LEVEL: u8 = 1;
 
 /// List all available serial ports
 #[tauri::command]
-pub fn list_serial_ports() -> Result<Vec<PortInfo>, String> {
+pub fn list_serial_ports() -> Result<Vec<PortInfo>, AppError> {
     log::info!("Listing serial ports");
     crate::serial::list_ports()
 }
 
 /// Connect to a meter
+///
+/// Identifies the meter and selects the readout (Mode 0); the session is kept
+/// open so `read_full` can take the data the meter is already sending.
 #[tauri::command]
-pub async fn connect(params: ConnectionParams, window: tauri::Window) -> Result<MeterIdentity, String> {
-    log::info!("Connecting to meter on port: {} at {} baud", params.port, params.baud_rate);
-
-    let emit_log = |log_type: &str, message: &str, data: Option<&str>| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: data.map(|s| s.to_string()),
-        });
-    };
-
-    // Disconnect any existing connection first
-    {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.is_connected() {
-            manager.disconnect();
-        }
-    }
-
-    let port_name = params.port.clone();
-    let timeout_ms = if params.timeout_ms == 0 { 2000 } else { params.timeout_ms };
-    let meter_address = params.meter_address.clone();
-
-    // Determine initial baud rates based on connection type (Turkish MASS standard)
-    let baud_rates_to_try = io::resolve_initial_bauds(&params.connection_type, params.baud_rate);
-
-    let mut port: Option<Box<dyn SerialPort>> = None;
-    let mut successful_baud: u32 = 0;
-    let mut total_read: usize = 0;
-    let mut response_buf = vec![0u8; 256];
-
-    for (attempt, &try_baud) in baud_rates_to_try.iter().enumerate() {
-        emit_log("info", &format!("Seri port açılıyor: {} @ {} baud (7E1) [Deneme {}/{}]",
-            port_name, try_baud, attempt + 1, baud_rates_to_try.len()), None);
-
-        // Open serial port with IEC 62056-21 settings (7E1)
-        let mut current_port = match iec62056::open_port(&port_name, try_baud, timeout_ms as u64) {
-            Ok(p) => p,
-            Err(e) => {
-                emit_log("warn", &format!("Port açılamadı @ {} baud: {}", try_baud, e), None);
-                continue;
-            }
-        };
-
-        emit_log("success", &format!("Port açıldı @ {} baud", try_baud), None);
-
-        // Build and send handshake request
-        let request = iec62056::build_request_message(meter_address.as_deref());
-        let request_str = iec62056::format_bytes_for_display(&request);
-        emit_log("tx", &request_str, None);
-
-        if let Err(e) = current_port.write_all(&request) {
-            emit_log("warn", &format!("Handshake gönderilemedi: {}", e), None);
-            continue;
-        }
-        let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-        let _ = current_port.flush();
-
-        // Wait for response
-        emit_log("info", "Yanıt bekleniyor...", None);
-        std::thread::sleep(Duration::from_millis(500));
-
-        // Read identification response
-        response_buf.fill(0);
-        total_read = 0;
-        let start_time = std::time::Instant::now();
-
-        loop {
-            match current_port.read(&mut response_buf[total_read..]) {
-                Ok(n) if n > 0 => {
-                    total_read += n;
-                    // Check if we have a complete response (ends with CR LF)
-                    if total_read >= 2 &&
-                       response_buf[total_read - 2] == control::CR &&
-                       response_buf[total_read - 1] == control::LF {
-                        break;
-                    }
-                }
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    if total_read > 0 {
-                        break;
-                    }
-                }
-                Err(_) => break,
-            }
-
-            if start_time.elapsed() > Duration::from_millis(timeout_ms as u64) {
-                break;
-            }
-        }
-
-        if total_read > 0 {
-            // We got a response!
-            successful_baud = try_baud;
-            port = Some(current_port);
-            emit_log("success", &format!("Yanıt alındı @ {} baud ({} byte)", try_baud, total_read), None);
-            break;
-        } else {
-            emit_log("warn", &format!("{} baud'da yanıt alınamadı", try_baud), None);
-        }
-    }
-
-    let mut port = port.ok_or_else(|| {
-        emit_log("error", "Hiçbir baud hızında yanıt alınamadı", None);
-        "No response at any baud rate".to_string()
-    })?;
-
-    if total_read == 0 {
-        emit_log("error", "Sayaçtan yanıt alınamadı", None);
-        return Err("No response from meter".to_string());
-    }
+pub async fn connect(params: ConnectionParams, window: tauri::Window) -> Result<MeterIdentity, AppError> {
+    log::info!("Connecting to meter on {} ({}) at {} baud", params.port, params.connection_type, params.baud_rate);
 
-    let response_formatted = iec62056::format_bytes_for_display(&response_buf[..total_read]);
-    emit_log("rx", &response_formatted, None);
+    // Disconnect any existing connection first, once no other command runs on the port
+    let options = SessionOptions::from_params(&params)?;
+    let worker = CONNECTION_STATE.lock()?.worker_for(&options)?;
+    let _operation = worker.begin().await;
+    CONNECTION_STATE.lock()?.disconnect(&options);
 
-    // Parse identification (need string version for parsing)
-    let response = String::from_utf8_lossy(&response_buf[..total_read]);
-    let ident = iec62056::parse_identification(&response)
-        .ok_or_else(|| {
-            emit_log("error", "Sayaç tanımlama yanıtı ayrıştırılamadı", None);
-            "Failed to parse meter identification".to_string()
-        })?;
-
-    emit_log("success", &format!("Sayaç tanımlandı: {} — {} ({})",
-        ident.manufacturer, ident.edas_id, ident.model), None);
-
-    // Determine the target baud rate for data transfer
-    let (target_baud, baud_char) = io::resolve_target_baud(
-        &params.connection_type, params.baud_rate, ident.max_baud_rate, ident.baud_char
-    );
+    let identity = MeterIdentity::from(&worker.identify(options, Some(&window), None).await?);
 
     // Send ACK for full readout mode (Mode 0 - gets all data)
-    let ack = iec62056::build_ack_message(ProtocolMode::Readout, baud_char);
-    let ack_formatted = iec62056::format_bytes_for_display(&ack);
-    emit_log("tx", &ack_formatted, None);
-
-    port.write_all(&ack).map_err(|e| format!("Failed to send ACK: {}", e))?;
-    let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-    port.flush().map_err(|e| format!("Flush failed: {}", e))?;
-
-    // Wait before baud rate switch
-    emit_log("info", &format!("Baud hızı değiştiriliyor: {} -> {}", successful_baud, target_baud), None);
-    std::thread::sleep(Duration::from_millis(300));
-
-    // Switch baud rate if needed
-    if target_baud != successful_baud {
-        port.set_baud_rate(target_baud).map_err(|e| {
-            emit_log("error", &format!("Baud hızı değiştirilemedi: {}", e), None);
-            format!("Failed to switch baud rate: {}", e)
-        })?;
-        emit_log("success", &format!("Baud hızı {} olarak ayarlandı", target_baud), None);
-    }
-
-    // Create meter identity
-    let identity = MeterIdentity {
-        manufacturer: ident.manufacturer.clone(),
-        edas_id: ident.edas_id.clone(),
-        model: ident.model.clone(),
-        baud_rate_char: ident.baud_char.to_string(),
-        generation: ident.generation.clone(),
-        serial_number: None, // Will be read from short packet
-    };
+    worker.enter_readout(ProtocolMode::Readout).await?;
+    EventEmitter::new(&window).success("Bağlantı başarılı!");
 
-    // Store connection state
+    // Store connection state; the session stays open on the worker
     {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        manager.port = Some(port);
+        let mut manager = CONNECTION_STATE.lock()?;
         manager.params = Some(params);
         manager.identity = Some(identity.clone());
-        manager.connected = true;
-        manager.negotiated_baud = target_baud;
     }
 
-    emit_log("success", "Bağlantı başarılı!", None);
     Ok(identity)
 }
 
 /// Disconnect from the meter
 #[tauri::command]
-pub async fn disconnect() -> Result<(), String> {
+pub async fn disconnect() -> Result<(), AppError> {
     log::info!("Disconnecting from meter");
 
-    let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-
-    // Send break command if connected
-    if let Some(ref mut port) = manager.port {
-        let break_cmd = iec62056::build_break_command();
-        let _ = port.write_all(&break_cmd);
-        let _ = port.flush();
+    // Closing the session sends the break command; a running command finishes first
+    if let Ok((options, _worker, _operation)) = stored_worker().await {
+        CONNECTION_STATE.lock()?.disconnect(&options);
     }
-
-    manager.disconnect();
     Ok(())
 }
 
 /// Get current connection status
 #[tauri::command]
-pub fn get_connection_status() -> Result<bool, String> {
-    let manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-    Ok(manager.connected)
+pub fn get_connection_status() -> Result<bool, AppError> {
+    let manager = CONNECTION_STATE.lock()?;
+    Ok(manager.is_connected())
 }
 
 /// Get current meter identity
 #[tauri::command]
-pub fn get_meter_identity() -> Result<Option<MeterIdentity>, String> {
-    let manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
+pub fn get_meter_identity() -> Result<Option<MeterIdentity>, AppError> {
+    let manager = CONNECTION_STATE.lock()?;
     Ok(manager.identity.clone())
 }
 
+/// Set the language of backend messages ("tr" or "en")
+#[tauri::command]
+pub fn set_language(lang: String) -> Result<(), AppError> {
+    crate::i18n::set_language(crate::i18n::Lang::from_str(&lang));
+    Ok(())
+}
+
+/// Options of the configured connection and the I/O worker of its port, held
+/// for the calling command once no other command runs on it
+async fn stored_worker() -> Result<(SessionOptions, MeterWorker, OperationGuard), AppError> {
+    let options = SessionOptions::stored()?;
+    let worker = CONNECTION_STATE.lock()?.worker_for(&options)?;
+    let operation = worker.begin().await;
+    Ok((options, worker, operation))
+}
+
 /// Perform a full read operation (Mode 0 - all data)
 /// This is an ATOMIC operation: opens port, handshakes, reads, closes port
 #[tauri::command]
-pub async fn read_full(window: tauri::Window) -> Result<ShortReadResult, String> {
+pub async fn read_full(operation_id: Option<String>, window: tauri::Window) -> Result<FullReadResult, AppError> {
     log::info!("Starting full read operation (atomic)");
-
-    let emit_progress = |step: u32, total: u32, message: &str| {
-        let _ = window.emit("read-progress", ProgressEvent {
-            step,
-            total,
-            message: message.to_string(),
-        });
-    };
-
-    let emit_log = |log_type: &str, message: &str, data: Option<&str>| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: data.map(|s| s.to_string()),
-        });
-    };
-
+    let operation = Operation::start(operation_id)?;
     let total_steps = 6;
 
     // Step 1: Get connection parameters from stored state
-    emit_progress(1, total_steps, "Bağlantı parametreleri alınıyor...");
+    emit_progress(&window, 1, total_steps, "Bağlantı parametreleri alınıyor...");
 
-    let (timeout_ms, port_name, meter_address, connection_type, configured_baud) = {
-        let manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.params.is_none() {
-            return Err("Bağlantı parametresi yok. Önce 'Bağlan' butonuna tıklayın.".to_string());
-        }
-        let params = manager.params.as_ref().unwrap();
-        (
-            if params.timeout_ms == 0 { 2000 } else { params.timeout_ms },
-            params.port.clone(),
-            params.meter_address.clone(),
-            params.connection_type.clone(),
-            params.baud_rate,
-        )
-    };
+    let (options, worker, _operation) = stored_worker().await?;
 
-    // Step 2: Check if connect() already has an open port with data flowing.
-    // connect() sends ACK for Mode 0 and switches baud, so the meter is already
-    // transmitting data. We can just take the port and read directly.
-    let existing_port = {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.port.is_some() && manager.negotiated_baud > 0 {
-            emit_log("info", "Mevcut bağlantı kullanılıyor (sayaç zaten veri gönderiyor)...", None);
-            let port = manager.port.take(); // Take ownership, leave None
-            manager.connected = false;
-            port
-        } else {
-            // No open port — clean up any stale state
-            if manager.port.is_some() {
-                manager.disconnect();
-            }
-            None
-        }
-    };
+    // Step 2: connect() leaves the session in readout mode with the meter already
+    // transmitting data; read it directly
+    let existing = worker.state() == SessionState::Readout
+        && worker.attach(Some(&window), Some(operation.token())).await
+            .is_ok_and(|state| state == SessionState::Readout);
 
-    let (mut port, atomic_ident) = if let Some(p) = existing_port {
-        // Port already open from connect() — skip handshake/ACK, go straight to reading
-        emit_progress(4, total_steps, "Tam okuma paketi alınıyor...");
-        emit_log("info", "Tam okuma paketi bekleniyor (Mod 0 - Tüm veriler)...", None);
-        (p, None)
+    let atomic_ident = if existing {
+        emit_log(&window, "info", "Mevcut bağlantı kullanılıyor (sayaç zaten veri gönderiyor)...");
+        None
     } else {
-        // No existing port — do full atomic sequence
-        emit_progress(2, total_steps, "Seri port açılıyor...");
-
-        let baud_rates = io::resolve_initial_bauds(&connection_type, configured_baud);
-        let mut found_port: Option<Box<dyn SerialPort>> = None;
-        let mut ident: Option<iec62056::MeterIdent> = None;
-        let mut initial_baud: u32 = 0;
-
-        for (attempt, &try_baud) in baud_rates.iter().enumerate() {
-            emit_log("info", &format!("Port açılıyor: {} @ {} baud (7E1) [Deneme {}/{}]",
-                port_name, try_baud, attempt + 1, baud_rates.len()), None);
-
-            let mut current_port = match iec62056::open_port(&port_name, try_baud, timeout_ms as u64) {
-                Ok(p) => p,
-                Err(e) => {
-                    emit_log("warn", &format!("Port açılamadı @ {} baud: {}", try_baud, e), None);
-                    continue;
-                }
-            };
-
-            emit_log("success", &format!("Port açıldı @ {} baud", try_baud), None);
+        // No open session — do full atomic sequence
+        emit_progress(&window, 2, total_steps, "Seri port açılıyor...");
+        let ident = worker.identify(options, Some(&window), Some(operation.token())).await?;
 
-            let request = iec62056::build_request_message(meter_address.as_deref());
-            let request_str = iec62056::format_bytes_for_display(&request);
-            emit_log("tx", &request_str, None);
-
-            if let Err(e) = current_port.write_all(&request) {
-                emit_log("warn", &format!("Handshake gönderilemedi: {}", e), None);
-                continue;
-            }
-            let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-            let _ = current_port.flush();
-
-            emit_log("info", "Yanıt bekleniyor...", None);
-            std::thread::sleep(Duration::from_millis(500));
-
-            let mut response_buf = vec![0u8; 256];
-            let mut ident_read = 0;
-            let handshake_start = std::time::Instant::now();
-
-            loop {
-                match current_port.read(&mut response_buf[ident_read..]) {
-                    Ok(n) if n > 0 => {
-                        ident_read += n;
-                        let _ = window.emit("comm-activity", serde_json::json!({"type": "rx"}));
-                        if ident_read >= 2 &&
-                           response_buf[ident_read - 2] == control::CR &&
-                           response_buf[ident_read - 1] == control::LF {
-                            break;
-                        }
-                    }
-                    Ok(_) => {}
-                    Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                        if ident_read > 0 { break; }
-                    }
-                    Err(_) => break,
-                }
-                if handshake_start.elapsed() > Duration::from_millis(timeout_ms as u64) {
-                    break;
-                }
-            }
-
-            if ident_read > 0 {
-                let response_formatted = iec62056::format_bytes_for_display(&response_buf[..ident_read]);
-                emit_log("rx", &response_formatted, None);
-
-                let response = String::from_utf8_lossy(&response_buf[..ident_read]);
-                if let Some(parsed) = iec62056::parse_identification(&response) {
-                    emit_log("success", &format!("Sayaç tanımlandı: {} — {} ({})",
-                        parsed.manufacturer, parsed.edas_id, parsed.model), None);
-                    initial_baud = try_baud;
-                    ident = Some(parsed);
-                    found_port = Some(current_port);
-                    break;
-                } else {
-                    emit_log("warn", "Sayaç tanımlama yanıtı ayrıştırılamadı", None);
-                }
-            } else {
-                emit_log("warn", &format!("{} baud'da yanıt alınamadı", try_baud), None);
-            }
-        }
-
-        let mut p = found_port.ok_or_else(|| {
-            emit_log("error", "Hiçbir baud hızında yanıt alınamadı", None);
-            "Hiçbir baud hızında yanıt alınamadı".to_string()
-        })?;
-        let ident = ident.unwrap();
-
-        emit_progress(3, total_steps, "Tam okuma modu seçiliyor...");
-
-        // Send ACK with Mode 0 (Readout)
-        let (target_baud, baud_char) = io::resolve_target_baud(
-            &connection_type, configured_baud, ident.max_baud_rate, ident.baud_char
-        );
-
-        let ack = iec62056::build_ack_message(ProtocolMode::Readout, baud_char);
-        let ack_formatted = iec62056::format_bytes_for_display(&ack);
-        emit_log("tx", &ack_formatted, None);
-
-        p.write_all(&ack).map_err(|e| format!("ACK gönderilemedi: {}", e))?;
-        let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-        let _ = p.flush();
-
-        // Wait and switch baud rate
-        emit_log("info", &format!("Baud hızı değiştiriliyor: {} -> {}", initial_baud, target_baud), None);
-        std::thread::sleep(Duration::from_millis(300));
-
-        if target_baud != initial_baud {
-            p.set_baud_rate(target_baud).map_err(|e| {
-                emit_log("error", &format!("Baud hızı değiştirilemedi: {}", e), None);
-                format!("Baud hızı değiştirilemedi: {}", e)
-            })?;
-            emit_log("success", &format!("Baud hızı {} olarak ayarlandı", target_baud), None);
-        }
-
-        emit_progress(4, total_steps, "Tam okuma paketi alınıyor...");
-        emit_log("info", "Tam okuma paketi bekleniyor (Mod 0 - Tüm veriler)...", None);
-        (p, Some(ident))
+        emit_progress(&window, 3, total_steps, "Tam okuma modu seçiliyor...");
+        worker.enter_readout(ProtocolMode::Readout).await?;
+        Some(ident)
     };
 
-    // Step 6: Read the data block from meter
-    let mut data_buf = vec![0u8; 131072]; // 128KB buffer for full readout
-    let mut total_read = 0;
-    let mut found_etx = false;
-    let read_start = std::time::Instant::now();
-    let mut last_read_time = std::time::Instant::now();
-    let mut time_of_09x_read: Option<u64> = None;
-
-    // Wait a bit for data to start arriving
-    std::thread::sleep(Duration::from_millis(300));
-
-    loop {
-        match port.read(&mut data_buf[total_read..]) {
-            Ok(n) if n > 0 => {
-                total_read += n;
-                last_read_time = std::time::Instant::now();
-                let _ = window.emit("comm-activity", serde_json::json!({"type": "rx"}));
-
-                // Capture system time when both 0.9.1 and 0.9.2 have arrived in the buffer
-                if time_of_09x_read.is_none() && total_read >= 6 {
-                    let buf = &data_buf[..total_read];
-                    let has_091 = buf.windows(6).any(|w| w == b"0.9.1(");
-                    let has_092 = buf.windows(6).any(|w| w == b"0.9.2(");
-                    if has_091 && has_092 {
-                        time_of_09x_read = Some(
-                            std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_millis() as u64
-                        );
-                    }
-                }
+    emit_progress(&window, 4, total_steps, "Tam okuma paketi alınıyor...");
+    emit_log(&window, "info", "Tam okuma paketi bekleniyor (Mod 0 - Tüm veriler)...");
 
-                // Check for ETX
-                if total_read >= 2 {
-                    for i in 0..total_read-1 {
-                        if data_buf[i] == control::ETX && i + 1 < total_read {
-                            found_etx = true;
-                            break;
-                        }
-                    }
-                    if found_etx {
-                        emit_log("info", &format!("Veri alımı tamamlandı: {} byte, süre: {:.1}s",
-                            total_read, read_start.elapsed().as_secs_f32()), None);
-                        break;
-                    }
-                }
-            }
-            Ok(_) => {
-                std::thread::sleep(Duration::from_millis(100));
-            }
-            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                std::thread::sleep(Duration::from_millis(100));
-            }
-            Err(e) => {
-                emit_log("error", &format!("Okuma hatası: {}", e), None);
-                // Send break and close port before returning error
-                let break_cmd = iec62056::build_break_command();
-                let _ = port.write_all(&break_cmd);
-                let _ = port.flush();
-                return Err(format!("Okuma hatası: {}", e));
-            }
-        }
-
-        // Idle timeout: 5 seconds
-        if last_read_time.elapsed() > Duration::from_millis(5000) {
-            if total_read == 0 {
-                emit_log("error", "Zaman aşımı: Hiç veri alınamadı (5s boşta)", None);
-            } else {
-                emit_log("warn", &format!("Boşta kalma zaman aşımı: {} byte alındı ama ETX yok", total_read), None);
-            }
-            break;
-        }
-    }
+    // Steps 4-5: Modes A and D: the readout may have started arriving with the
+    // identification; those bytes are still in the session's decoder
+    let readout = worker.read_readout(ReadConfig::full_read(), &window, 4, total_steps).await?;
 
-    // Step 7: Send Break command and close port
-    emit_log("info", "Oturum sonlandırılıyor...", None);
-    let break_cmd = iec62056::build_break_command();
-    let _ = port.write_all(&break_cmd);
-    let _ = port.flush();
-    std::thread::sleep(Duration::from_millis(100));
-    drop(port); // Close the port
-    emit_log("info", "Port kapatıldı", None);
-
-    // Validate received data
-    if total_read == 0 {
-        emit_log("error", "Veri alınamadı", None);
-        return Err("Sayaçtan veri alınamadı".to_string());
-    }
-
-    if !found_etx {
-        emit_log("warn", &format!("Veri tam alınamadı: ETX bulunamadı ({} byte alındı)", total_read), None);
-    }
-
-    emit_progress(5, total_steps, "Veriler doğrulanıyor...");
-
-    // Verify BCC if we found ETX
-    if found_etx {
-        if let Some(etx_idx) = data_buf[..total_read].iter().position(|&b| b == control::ETX) {
-            if etx_idx + 1 < total_read {
-                let received_bcc = data_buf[etx_idx + 1];
-                if let Some(stx_idx) = data_buf[..total_read].iter().position(|&b| b == control::STX) {
-                    let calculated_bcc = iec62056::calculate_bcc(&data_buf[stx_idx+1..=etx_idx]);
-                    if calculated_bcc != received_bcc {
-                        emit_log("warn", &format!("BCC uyuşmazlığı: beklenen 0x{:02X}, alınan 0x{:02X}",
-                            calculated_bcc, received_bcc), None);
-                    } else {
-                        emit_log("success", &format!("BCC doğrulaması başarılı ({} byte)", total_read), None);
-                    }
-                }
-            }
-        }
+    if readout.cancelled {
+        emit_log(&window, "warn", "Tam okuma iptal edildi, alınan kısmi veri döndürülüyor");
+    } else {
+        emit_log(&window, "success", "Tam okuma başarıyla tamamlandı");
     }
+    emit_progress(&window, 6, total_steps, "Tamamlandı!");
 
-    // Convert to string for parsing and format for display
-    let raw_data = String::from_utf8_lossy(&data_buf[..total_read]).to_string();
-    let data_formatted = iec62056::format_bytes_for_display(&data_buf[..total_read]);
-    emit_log("rx", &data_formatted, None);
-
-    // Parse the OBIS data
-    let items = iec62056::parse_data_block(&raw_data);
-    emit_log("info", &format!("{} OBIS kodu ayrıştırıldı", items.len()), None);
-
-    // Extract values from parsed items
-    let get_value = |code: &str| -> String {
-        items.iter()
-            .find(|item| item.code == code || item.code.starts_with(&format!("{}*", code)))
-            .map(|item| item.value.clone())
-            .unwrap_or_default()
+    // Build result
+    let common = short_read_result(readout);
+    let items = &common.obis_items;
+    let get_optional = |code: &str| -> Option<f64> {
+        find_item(items, code).and_then(|item| item.parsed.as_f64())
     };
-
-    let get_float = |code: &str| -> f64 {
-        get_value(code).parse().unwrap_or(0.0)
+    let get_count = |code: &str| -> Option<u32> {
+        find_item(items, code).and_then(|item| item.value.trim().parse().ok())
     };
 
-    emit_log("success", "Tam okuma başarıyla tamamlandı", None);
-    emit_progress(6, total_steps, "Tamamlandı!");
-
-    // Build result
-    let result = ShortReadResult {
-        serial_number: {
-            let sn = get_value("0.0.0");
-            if sn.is_empty() { get_value("96.1.0") } else { sn }
-        },
-        program_version: get_value("0.2.0"),
-        production_date: get_value("96.1.3"),
-        calibration_date: get_value("96.2.5"),
-        meter_date: get_value("0.9.2"),
-        meter_time: get_value("0.9.1"),
-        day_of_week: get_value("0.9.5").parse().unwrap_or(0),
-        active_energy_import_total: get_float("1.8.0"),
-        active_energy_import_t1: get_float("1.8.1"),
-        active_energy_import_t2: get_float("1.8.2"),
-        active_energy_import_t3: get_float("1.8.3"),
-        active_energy_import_t4: get_float("1.8.4"),
-        max_demand_import: get_float("1.6.0"),
-        max_demand_import_timestamp: get_value("1.6.0"),
-        voltage_l1: get_float("32.7.0"),
-        voltage_l2: get_float("52.7.0"),
-        voltage_l3: get_float("72.7.0"),
-        current_l1: get_float("31.7.0"),
-        current_l2: get_float("51.7.0"),
-        current_l3: get_float("71.7.0"),
-        frequency: get_float("14.7.0"),
-        power_factor_l1: get_float("33.7.0"),
-        power_factor_l2: get_float("53.7.0"),
-        power_factor_l3: get_float("73.7.0"),
-        ff_code: get_value("F.F.0"),
-        gf_code: get_value("F.F.1"),
-        battery_status: if get_value("96.6.1").contains("0") { "low".to_string() } else { "full".to_string() },
-        relay_status: {
-            let relay_val = get_value("96.3.10");
-            if relay_val.is_empty() { "".to_string() }
-            else if relay_val.contains("1") { "active".to_string() }
-            else { "passive".to_string() }
-        },
-        raw_data: Some(raw_data),
-        time_of_09x_read,
+    let result = FullReadResult {
+        active_energy_export_total: get_optional("2.8.0"),
+        active_energy_export_t1: get_optional("2.8.1"),
+        active_energy_export_t2: get_optional("2.8.2"),
+        active_energy_export_t3: get_optional("2.8.3"),
+        active_energy_export_t4: get_optional("2.8.4"),
+        reactive_energy_import_total: get_optional("3.8.0"),
+        reactive_energy_export_total: get_optional("4.8.0"),
+        reactive_energy_inductive_import: get_optional("5.8.0"),
+        reactive_energy_capacitive_export: get_optional("6.8.0"),
+        reactive_energy_inductive_export: get_optional("7.8.0"),
+        reactive_energy_capacitive_import: get_optional("8.8.0"),
+        max_demand_export: get_optional("2.6.0"),
+        max_demand_export_timestamp: format_timestamp(items, "2.6.0"),
+        billing_periods: packets::parse_historical(items).periods,
+        demand_reset_count: get_count("0.1.0"),
+        outage_count: get_count("96.7.0"),
+        voltage_warning_count: get_count("96.7.4"),
+        current_warning_count: get_count("96.7.5"),
+        magnetic_warning_count: get_count("96.7.6"),
+        top_cover_openings: get_count("96.70"),
+        terminal_cover_openings: get_count("96.71"),
+        common,
     };
 
-    // Update stored identity with serial number (for display purposes)
-    {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        // If we did a fresh atomic read, store identity from handshake
-        if let Some(ident) = atomic_ident {
-            manager.identity = Some(MeterIdentity {
-                manufacturer: ident.manufacturer.clone(),
-                edas_id: ident.edas_id.clone(),
-                model: ident.model.clone(),
-                baud_rate_char: ident.baud_char.to_string(),
-                generation: ident.generation.clone(),
-                serial_number: Some(result.serial_number.clone()),
-            });
-        } else if let Some(ref mut identity) = manager.identity {
-            // Existing identity from connect() — just update serial number
-            identity.serial_number = Some(result.serial_number.clone());
-        }
-        // Mark as not connected since we closed the port
-        manager.connected = false;
-        manager.port = None;
-    }
+    // If we did a fresh atomic read, store identity from handshake
+    store_identity(atomic_ident.as_ref(), &result.common.serial_number)?;
 
     Ok(result)
 }
@@ -635,313 +209,129 @@ pub async fn read_full(window: tauri::Window) -> Result<ShortReadResult, String>
 /// Perform a short read operation (Mode 6 - short packet)
 /// This is an ATOMIC operation: opens port, handshakes, reads, closes port
 #[tauri::command]
-pub async fn read_short(window: tauri::Window) -> Result<ShortReadResult, String> {
+pub async fn read_short(operation_id: Option<String>, window: tauri::Window) -> Result<ShortReadResult, AppError> {
     log::info!("Starting short read operation (atomic)");
-
-    let emit_progress = |step: u32, total: u32, message: &str| {
-        let _ = window.emit("read-progress", ProgressEvent {
-            step,
-            total,
-            message: message.to_string(),
-        });
-    };
-
-    let emit_log = |log_type: &str, message: &str, data: Option<&str>| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: data.map(|s| s.to_string()),
-        });
-    };
-
+    let operation = Operation::start(operation_id)?;
     let total_steps = 6;
 
     // Step 1: Get connection parameters from stored state
-    emit_progress(1, total_steps, "Bağlantı parametreleri alınıyor...");
+    emit_progress(&window, 1, total_steps, "Bağlantı parametreleri alınıyor...");
 
-    let (timeout_ms, port_name, meter_address, connection_type, configured_baud) = {
-        let manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.params.is_none() {
-            return Err("Bağlantı parametresi yok. Önce 'Bağlan' butonuna tıklayın.".to_string());
-        }
-        let params = manager.params.as_ref().unwrap();
-        (
-            if params.timeout_ms == 0 { 2000 } else { params.timeout_ms },
-            params.port.clone(),
-            params.meter_address.clone(),
-            params.connection_type.clone(),
-            params.baud_rate,
-        )
-    };
+    let (options, worker, _operation) = fresh_worker(&window).await?;
 
-    // Step 2: Close any existing connection - we'll do a fresh atomic read
-    {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.port.is_some() {
-            emit_log("info", "Mevcut bağlantı kapatılıyor...", None);
-            manager.disconnect();
-        }
-    }
+    emit_progress(&window, 2, total_steps, "Seri port açılıyor...");
 
-    emit_progress(2, total_steps, "Seri port açılıyor...");
+    // Step 2: Open port and handshake with baud rate retry
+    let ident = worker.identify(options, Some(&window), Some(operation.token())).await?;
 
-    // Step 3-4: Open port and handshake with baud rate retry
-    let baud_rates = io::resolve_initial_bauds(&connection_type, configured_baud);
-    let mut port: Option<Box<dyn SerialPort>> = None;
-    let mut ident: Option<iec62056::MeterIdent> = None;
-    let mut initial_baud: u32 = 0;
+    emit_progress(&window, 3, total_steps, "Kısa okuma modu seçiliyor...");
 
-    for (attempt, &try_baud) in baud_rates.iter().enumerate() {
-        emit_log("info", &format!("Port açılıyor: {} @ {} baud (7E1) [Deneme {}/{}]",
-            port_name, try_baud, attempt + 1, baud_rates.len()), None);
+    // Step 3: Send ACK with Mode 6 (ShortRead)
+    worker.enter_readout(ProtocolMode::ShortRead).await?;
 
-        let mut current_port = match iec62056::open_port(&port_name, try_baud, timeout_ms as u64) {
-            Ok(p) => p,
-            Err(e) => {
-                emit_log("warn", &format!("Port açılamadı @ {} baud: {}", try_baud, e), None);
-                continue;
-            }
-        };
-
-        emit_log("success", &format!("Port açıldı @ {} baud", try_baud), None);
+    emit_progress(&window, 4, total_steps, "Kısa okuma paketi alınıyor...");
+    emit_log(&window, "info", "Kısa okuma paketi bekleniyor (Mod 6)...");
 
-        let request = iec62056::build_request_message(meter_address.as_deref());
-        let request_str = iec62056::format_bytes_for_display(&request);
-        emit_log("tx", &request_str, None);
+    // Steps 4-5: Read the data blocks from meter
+    let readout = worker.read_readout(ReadConfig::short_read(), &window, 4, total_steps).await?;
 
-        if let Err(e) = current_port.write_all(&request) {
-            emit_log("warn", &format!("Handshake gönderilemedi: {}", e), None);
-            continue;
-        }
-        let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-        let _ = current_port.flush();
-
-        emit_log("info", "Yanıt bekleniyor...", None);
-        std::thread::sleep(Duration::from_millis(500));
-
-        let mut response_buf = vec![0u8; 256];
-        let mut ident_read = 0;
-        let handshake_start = std::time::Instant::now();
-
-        loop {
-            match current_port.read(&mut response_buf[ident_read..]) {
-                Ok(n) if n > 0 => {
-                    ident_read += n;
-                    let _ = window.emit("comm-activity", serde_json::json!({"type": "rx"}));
-                    if ident_read >= 2 &&
-                       response_buf[ident_read - 2] == control::CR &&
-                       response_buf[ident_read - 1] == control::LF {
-                        break;
-                    }
-                }
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    if ident_read > 0 { break; }
-                }
-                Err(_) => break,
-            }
-            if handshake_start.elapsed() > Duration::from_millis(timeout_ms as u64) {
-                break;
-            }
-        }
-
-        if ident_read > 0 {
-            let response_formatted = iec62056::format_bytes_for_display(&response_buf[..ident_read]);
-            emit_log("rx", &response_formatted, None);
-
-            let response = String::from_utf8_lossy(&response_buf[..ident_read]);
-            if let Some(parsed) = iec62056::parse_identification(&response) {
-                emit_log("success", &format!("Sayaç tanımlandı: {} — {} ({})",
-                    parsed.manufacturer, parsed.edas_id, parsed.model), None);
-                initial_baud = try_baud;
-                ident = Some(parsed);
-                port = Some(current_port);
-                break;
-            } else {
-                emit_log("warn", "Sayaç tanımlama yanıtı ayrıştırılamadı", None);
-            }
-        } else {
-            emit_log("warn", &format!("{} baud'da yanıt alınamadı", try_baud), None);
-        }
+    if readout.cancelled {
+        emit_log(&window, "warn", "Kısa okuma iptal edildi, alınan kısmi veri döndürülüyor");
+    } else {
+        emit_log(&window, "success", "Kısa okuma başarıyla tamamlandı");
     }
+    emit_progress(&window, 6, total_steps, "Tamamlandı!");
 
-    let mut port = port.ok_or_else(|| {
-        emit_log("error", "Hiçbir baud hızında yanıt alınamadı", None);
-        "Hiçbir baud hızında yanıt alınamadı".to_string()
-    })?;
-    let ident = ident.unwrap();
+    let result = short_read_result(readout);
+    store_identity(Some(&ident), &result.serial_number)?;
 
-    emit_progress(3, total_steps, "Kısa okuma modu seçiliyor...");
+    Ok(result)
+}
 
-    // Step 5: Send ACK with Mode 6 (ShortRead)
-    let (target_baud, baud_char) = io::resolve_target_baud(
-        &connection_type, configured_baud, ident.max_baud_rate, ident.baud_char
-    );
+/// Read one MASS packet: 5 (technical quality), 7 (historical), 8 (warnings) or 9 (outages)
+/// This is an ATOMIC operation: opens port, handshakes, reads, closes port
+#[tauri::command]
+pub async fn read_packet(mode: u8, operation_id: Option<String>, window: tauri::Window) -> Result<PacketReadResult, AppError> {
+    log::info!("Starting packet {} read operation (atomic)", mode);
+    let protocol_mode = packets::packet_mode(mode)?;
+    let operation = Operation::start(operation_id)?;
+    let total_steps = 6;
 
-    let ack = iec62056::build_ack_message(ProtocolMode::ShortRead, baud_char);
-    let ack_formatted = iec62056::format_bytes_for_display(&ack);
-    emit_log("tx", &ack_formatted, None);
+    emit_progress(&window, 1, total_steps, "Bağlantı parametreleri alınıyor...");
 
-    port.write_all(&ack).map_err(|e| format!("ACK gönderilemedi: {}", e))?;
-    let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-    let _ = port.flush();
+    let (options, worker, _operation) = fresh_worker(&window).await?;
 
-    // Wait and switch baud rate
-    emit_log("info", &format!("Baud hızı değiştiriliyor: {} -> {}", initial_baud, target_baud), None);
-    std::thread::sleep(Duration::from_millis(300));
+    emit_progress(&window, 2, total_steps, "Seri port açılıyor...");
 
-    if target_baud != initial_baud {
-        port.set_baud_rate(target_baud).map_err(|e| {
-            emit_log("error", &format!("Baud hızı değiştirilemedi: {}", e), None);
-            format!("Baud hızı değiştirilemedi: {}", e)
-        })?;
-        emit_log("success", &format!("Baud hızı {} olarak ayarlandı", target_baud), None);
-    }
+    worker.identify(options, Some(&window), Some(operation.token())).await?;
 
-    emit_progress(4, total_steps, "Kısa okuma paketi alınıyor...");
-    emit_log("info", "Kısa okuma paketi bekleniyor (Mod 6)...", None);
+    emit_progress(&window, 3, total_steps, &format!("Paket {} seçiliyor...", mode));
 
-    // Step 6: Read the data block from meter
-    let mut data_buf = vec![0u8; 8192]; // 8KB buffer for short read
-    let mut total_read = 0;
-    let mut found_etx = false;
-    let read_start = std::time::Instant::now();
-    let mut last_read_time = std::time::Instant::now();
-    let mut time_of_09x_read: Option<u64> = None;
-
-    // Wait a bit for data to start arriving
-    std::thread::sleep(Duration::from_millis(300));
-
-    loop {
-        match port.read(&mut data_buf[total_read..]) {
-            Ok(n) if n > 0 => {
-                total_read += n;
-                last_read_time = std::time::Instant::now();
-                let _ = window.emit("comm-activity", serde_json::json!({"type": "rx"}));
-
-                // Capture system time when both 0.9.1 and 0.9.2 have arrived in the buffer
-                if time_of_09x_read.is_none() && total_read >= 6 {
-                    let buf = &data_buf[..total_read];
-                    let has_091 = buf.windows(6).any(|w| w == b"0.9.1(");
-                    let has_092 = buf.windows(6).any(|w| w == b"0.9.2(");
-                    if has_091 && has_092 {
-                        time_of_09x_read = Some(
-                            std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_millis() as u64
-                        );
-                    }
-                }
+    worker.enter_readout(protocol_mode).await?;
 
-                // Check for ETX
-                if total_read >= 2 {
-                    for i in 0..total_read-1 {
-                        if data_buf[i] == control::ETX && i + 1 < total_read {
-                            found_etx = true;
-                            break;
-                        }
-                    }
-                    if found_etx {
-                        emit_log("info", &format!("Veri alımı tamamlandı: {} byte, süre: {:.1}s",
-                            total_read, read_start.elapsed().as_secs_f32()), None);
-                        break;
-                    }
-                }
-            }
-            Ok(_) => {
-                std::thread::sleep(Duration::from_millis(100));
-            }
-            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                std::thread::sleep(Duration::from_millis(100));
-            }
-            Err(e) => {
-                emit_log("error", &format!("Okuma hatası: {}", e), None);
-                // Send break and close port before returning error
-                let break_cmd = iec62056::build_break_command();
-                let _ = port.write_all(&break_cmd);
-                let _ = port.flush();
-                return Err(format!("Okuma hatası: {}", e));
-            }
-        }
+    emit_progress(&window, 4, total_steps, &format!("Paket {} alınıyor...", mode));
+    emit_log(&window, "info", &format!("Paket {} bekleniyor (Mod {})...", mode, mode));
 
-        // Idle timeout: 3 seconds for short read
-        if last_read_time.elapsed() > Duration::from_millis(3000) {
-            if total_read == 0 {
-                emit_log("error", "Zaman aşımı: Hiç veri alınamadı (3s boşta)", None);
-            } else {
-                emit_log("warn", &format!("Boşta kalma zaman aşımı: {} byte alındı ama ETX yok", total_read), None);
-            }
-            break;
-        }
-    }
+    let readout = worker.read_readout(ReadConfig::short_read(), &window, 4, total_steps).await?;
 
-    // Step 7: Send Break command and close port
-    emit_log("info", "Oturum sonlandırılıyor...", None);
-    let break_cmd = iec62056::build_break_command();
-    let _ = port.write_all(&break_cmd);
-    let _ = port.flush();
-    std::thread::sleep(Duration::from_millis(100));
-    drop(port); // Close the port
-    emit_log("info", "Port kapatıldı", None);
-
-    // Validate received data
-    if total_read == 0 {
-        emit_log("error", "Veri alınamadı", None);
-        return Err("Sayaçtan veri alınamadı".to_string());
-    }
+    let serial_number = find_item(&readout.items, "0.0.0")
+        .map(|item| item.value.clone())
+        .unwrap_or_default();
 
-    if !found_etx {
-        emit_log("warn", &format!("Veri tam alınamadı: ETX bulunamadı ({} byte alındı)", total_read), None);
+    if readout.cancelled {
+        emit_log(&window, "warn", &format!("Paket {} okuması iptal edildi, alınan kısmi veri döndürülüyor", mode));
+    } else {
+        emit_log(&window, "success", &format!("Paket {} okuması başarıyla tamamlandı", mode));
     }
+    emit_progress(&window, 6, total_steps, "Tamamlandı!");
+
+    Ok(PacketReadResult {
+        mode,
+        serial_number,
+        data: packets::parse_packet(protocol_mode, &readout.items)?,
+        raw_data: String::from_utf8_lossy(&readout.data).to_string(),
+        untrusted_codes: readout.untrusted_codes,
+        obis_items: readout.items,
+        cancelled: readout.cancelled,
+    })
+}
 
-    emit_progress(5, total_steps, "Veriler doğrulanıyor...");
-
-    // Verify BCC if we found ETX
-    if found_etx {
-        if let Some(etx_idx) = data_buf[..total_read].iter().position(|&b| b == control::ETX) {
-            if etx_idx + 1 < total_read {
-                let received_bcc = data_buf[etx_idx + 1];
-                if let Some(stx_idx) = data_buf[..total_read].iter().position(|&b| b == control::STX) {
-                    let calculated_bcc = iec62056::calculate_bcc(&data_buf[stx_idx+1..=etx_idx]);
-                    if calculated_bcc != received_bcc {
-                        emit_log("warn", &format!("BCC uyuşmazlığı: beklenen 0x{:02X}, alınan 0x{:02X}",
-                            calculated_bcc, received_bcc), None);
-                    } else {
-                        emit_log("success", &format!("BCC doğrulaması başarılı ({} byte)", total_read), None);
-                    }
-                }
-            }
-        }
+/// Close the open connection, if any, for a fresh atomic sequence and return
+/// the stored options with the worker of their port, held as in `stored_worker`
+async fn fresh_worker(window: &tauri::Window) -> Result<(SessionOptions, MeterWorker, OperationGuard), AppError> {
+    let (options, worker, operation) = stored_worker().await?;
+    let mut manager = CONNECTION_STATE.lock()?;
+    if manager.is_connected() {
+        emit_log(window, "info", "Mevcut bağlantı kapatılıyor...");
+        manager.disconnect(&options);
     }
+    Ok((options, worker, operation))
+}
 
-    // Convert to string for parsing and format for display
-    let raw_data = String::from_utf8_lossy(&data_buf[..total_read]).to_string();
-    let data_formatted = iec62056::format_bytes_for_display(&data_buf[..total_read]);
-    emit_log("rx", &data_formatted, None);
+/// First item of `items` with the register `code`
+fn find_item<'a>(items: &'a [ObisDataItem], code: &str) -> Option<&'a ObisDataItem> {
+    let code: ObisCode = code.parse().ok()?;
+    items.iter().find(|item| item.matches(&code))
+}
 
-    // Parse the OBIS data
-    let items = iec62056::parse_data_block(&raw_data);
-    emit_log("info", &format!("{} OBIS kodu ayrıştırıldı", items.len()), None);
+/// Timestamp of the item with the register `code`, for display
+fn format_timestamp(items: &[ObisDataItem], code: &str) -> Option<String> {
+    find_item(items, code)
+        .and_then(|item| item.parsed.as_datetime())
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+}
 
-    // Extract values from parsed items
+/// The registers of the short read (Mode 6), also the common part of a full read
+fn short_read_result(readout: Readout) -> ShortReadResult {
+    let items = readout.items;
     let get_value = |code: &str| -> String {
-        items.iter()
-            .find(|item| item.code == code || item.code.starts_with(&format!("{}*", code)))
-            .map(|item| item.value.clone())
-            .unwrap_or_default()
+        find_item(&items, code).map(|item| item.value.clone()).unwrap_or_default()
     };
-
     let get_float = |code: &str| -> f64 {
-        get_value(code).parse().unwrap_or(0.0)
+        find_item(&items, code).and_then(|item| item.parsed.as_f64()).unwrap_or(0.0)
     };
 
-    emit_log("success", "Kısa okuma başarıyla tamamlandı", None);
-    emit_progress(6, total_steps, "Tamamlandı!");
-
-    // Build result
-    let result = ShortReadResult {
+    ShortReadResult {
         serial_number: {
             let sn = get_value("0.0.0");
             if sn.is_empty() { get_value("96.1.0") } else { sn }
@@ -958,7 +348,7 @@ pub async fn read_short(window: tauri::Window) -> Result<ShortReadResult, String
         active_energy_import_t3: get_float("1.8.3"),
         active_energy_import_t4: get_float("1.8.4"),
         max_demand_import: get_float("1.6.0"),
-        max_demand_import_timestamp: get_value("1.6.0"),
+        max_demand_import_timestamp: format_timestamp(&items, "1.6.0").unwrap_or_default(),
         voltage_l1: get_float("32.7.0"),
         voltage_l2: get_float("52.7.0"),
         voltage_l3: get_float("72.7.0"),
@@ -972,107 +362,96 @@ pub async fn read_short(window: tauri::Window) -> Result<ShortReadResult, String
         ff_code: get_value("F.F.0"),
         gf_code: get_value("F.F.1"),
         battery_status: if get_value("96.6.1").contains("0") { "low".to_string() } else { "full".to_string() },
-        relay_status: {
-            let relay_val = get_value("96.3.10");
-            if relay_val.is_empty() { "".to_string() }
-            else if relay_val.contains("1") { "active".to_string() }
-            else { "passive".to_string() }
-        },
-        raw_data: Some(raw_data),
-        time_of_09x_read,
-    };
-
-    // Update stored identity with serial number (for display purposes)
-    {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        // Update or create identity
-        if manager.identity.is_none() {
-            manager.identity = Some(MeterIdentity {
-                manufacturer: ident.manufacturer.clone(),
-                edas_id: ident.edas_id.clone(),
-                model: ident.model.clone(),
-                baud_rate_char: ident.baud_char.to_string(),
-                generation: ident.generation.clone(),
-                serial_number: Some(result.serial_number.clone()),
-            });
-        } else if let Some(ref mut identity) = manager.identity {
-            identity.serial_number = Some(result.serial_number.clone());
-        }
-        // Mark as not connected since we closed the port
-        manager.connected = false;
-        manager.port = None;
+        relay_status: RelayState::parse(&get_value("96.3.10")).map_or("", RelayState::status).to_string(),
+        raw_data: Some(String::from_utf8_lossy(&readout.data).to_string()),
+        time_of_09x_read: readout.time_of_09x_read,
+        untrusted_codes: readout.untrusted_codes,
+        mass: MassData::parse(&items),
+        obis_items: items,
+        cancelled: readout.cancelled,
     }
+}
 
-    Ok(result)
+/// Store the serial number read from the data with the meter identity; a fresh
+/// identification (`ident`) replaces the identity of an earlier connect()
+fn store_identity(ident: Option<&MeterIdent>, serial_number: &str) -> Result<(), AppError> {
+    let mut manager = CONNECTION_STATE.lock()?;
+    if let Some(ident) = ident {
+        manager.identity = Some(MeterIdentity {
+            serial_number: Some(serial_number.to_string()),
+            ..MeterIdentity::from(ident)
+        });
+    } else if let Some(ref mut identity) = manager.identity {
+        identity.serial_number = Some(serial_number.to_string());
+    }
+    Ok(())
 }
 
 /// Read a specific OBIS code
 #[tauri::command]
-pub async fn read_obis(obis_code: String, window: tauri::Window) -> Result<String, String> {
+pub async fn read_obis(obis_code: String, window: tauri::Window) -> Result<ObisReading, AppError> {
     log::info!("Reading OBIS code: {}", obis_code);
 
-    let emit_log = |log_type: &str, message: &str| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: None,
-        });
-    };
-
-    let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-    if !manager.connected {
-        return Err("Not connected to meter".to_string());
-    }
-
-    let port = manager.port.as_mut().ok_or("Port not available")?;
-
-    // Build and send read command
-    let cmd = iec62056::build_read_command(&obis_code);
-    emit_log("tx", &format!("R2 {}()", obis_code));
-
-    port.write_all(&cmd).map_err(|e| format!("Write failed: {}", e))?;
-    port.flush().map_err(|e| format!("Flush failed: {}", e))?;
-
-    // Read response
-    let mut buf = vec![0u8; 256];
-    let mut total = 0;
-
-    std::thread::sleep(Duration::from_millis(200));
-
-    match port.read(&mut buf) {
-        Ok(n) => total = n,
-        Err(e) => return Err(format!("Read failed: {}", e)),
-    }
+    let (_, worker, _operation) = stored_worker().await?;
+    worker.attach(Some(&window), None).await?;
+    read_on_session(&worker, &obis_code, Some(&window)).await
+}
 
-    if total == 0 {
-        return Err("No response".to_string());
-    }
+/// `read_obis` on the programming session of `worker`
+async fn read_on_session(
+    worker: &MeterWorker,
+    obis_code: &str,
+    window: Option<&tauri::Window>,
+) -> Result<ObisReading, AppError> {
+    // Build and send read command; the answer may arrive over several reads
+    let cmd = iec62056::build_read_command(obis_code);
+    emit_log(window, "tx", &format!("R2 {}()", obis_code));
+    let reply = worker.command(cmd).await?;
+
+    // Only the data between STX and ETX is used, so the BCC byte
+    // (which can be any value, including ')') never reaches the parser
+    let (data, bcc_ok) = match reply.frame {
+        Some(iec62056::Frame::DataBlock { data, bcc_ok }) => (data, bcc_ok),
+        Some(iec62056::Frame::Nak) => {
+            emit_log(window, "error", "NAK - Okuma reddedildi");
+            return Err(AppError::Nak { command: "R2".to_string() });
+        }
+        Some(other) => {
+            emit_log(window, "rx", &iec62056::format_bytes_for_display(&reply.raw));
+            return Err(AppError::unexpected_frame(&other));
+        }
+        None => return Err(AppError::Timeout { received: reply.pending }),
+    };
 
-    let raw = &buf[..total];
-    let response = String::from_utf8_lossy(raw).to_string();
-    emit_log("rx", &response);
+    let response = String::from_utf8_lossy(&data).to_string();
+    emit_log(window, "rx", audit::loggable(obis_code, &response));
 
-    // Extract data between STX and ETX to exclude protocol framing and BCC byte
-    // BCC can be any byte value including ')' (0x29) which corrupts parsing
-    let data_slice = match (raw.iter().position(|&b| b == control::STX),
-                            raw.iter().position(|&b| b == control::ETX)) {
-        (Some(s), Some(e)) if s < e => &raw[s + 1..e],
-        _ => raw,
-    };
-    let cleaned = String::from_utf8_lossy(data_slice)
-        .chars().filter(|c| !c.is_control()).collect::<String>();
+    let cleaned = response.chars().filter(|c| !c.is_control()).collect::<String>();
 
     // Parse the OBIS response
-    if let Some(item) = iec62056::parse_obis_response(cleaned.trim()) {
-        Ok(if let Some(unit) = item.unit {
+    let value = if let Some(item) = iec62056::parse_obis_response(cleaned.trim()) {
+        if let Some(unit) = item.unit {
             format!("{}*{}", item.value, unit)
         } else {
             item.value
-        })
+        }
     } else {
-        Ok(cleaned.trim().to_string())
+        cleaned.trim().to_string()
+    };
+
+    if !bcc_ok {
+        emit_log(window, "warn", "BCC hatası tekrar sonrasında da devam ediyor, değer güvenilmez");
+    }
+    Ok(ObisReading { value, untrusted: !bcc_ok })
+}
+
+/// `read_on_session` for a value the operation relies on: an untrusted reply is an error
+async fn read_trusted(worker: &MeterWorker, obis_code: &str, window: &tauri::Window) -> Result<String, AppError> {
+    let reading = read_on_session(worker, obis_code, Some(window)).await?;
+    if reading.untrusted {
+        return Err(AppError::BccMismatch { blocks: 1 });
     }
+    Ok(reading.value)
 }
 
 /// Batch-read multiple OBIS codes in a single atomic programming mode session.
@@ -1081,170 +460,24 @@ pub async fn read_obis(obis_code: String, window: tauri::Window) -> Result<Strin
 #[tauri::command]
 pub async fn read_obis_batch(
     obis_codes: Vec<String>,
+    operation_id: Option<String>,
     window: tauri::Window,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    log::info!("Batch OBIS read: {:?} (atomic)", obis_codes);
-
-    let emit_log = |log_type: &str, message: &str| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: None,
-        });
-    };
-
-    if obis_codes.is_empty() {
-        return Err("OBIS kodu listesi boş".to_string());
-    }
-
-    // Step 1: Get connection parameters from stored state
-    let (timeout_ms, port_name, meter_address, connection_type, configured_baud) = {
-        let manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.params.is_none() {
-            return Err("Bağlantı parametresi yok. Önce 'Bağlan' butonuna tıklayın.".to_string());
-        }
-        let params = manager.params.as_ref().unwrap();
-        (
-            if params.timeout_ms == 0 { 2000 } else { params.timeout_ms },
-            params.port.clone(),
-            params.meter_address.clone(),
-            params.connection_type.clone(),
-            params.baud_rate,
-        )
-    };
-
-    // Step 2: Close any existing connection
-    {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.port.is_some() {
-            emit_log("info", "Mevcut bağlantı kapatılıyor...");
-            manager.disconnect();
-        }
-    }
-
-    // Step 3: Open port and handshake with baud rate retry
-    let baud_rates = io::resolve_initial_bauds(&connection_type, configured_baud);
-    let mut port: Option<Box<dyn SerialPort>> = None;
-    let mut ident: Option<iec62056::MeterIdent> = None;
-    let mut initial_baud: u32 = 0;
-
-    for (attempt, &try_baud) in baud_rates.iter().enumerate() {
-        emit_log("info", &format!("Port açılıyor: {} @ {} baud (7E1) [Deneme {}/{}]",
-            port_name, try_baud, attempt + 1, baud_rates.len()));
-
-        let mut current_port = match iec62056::open_port(&port_name, try_baud, timeout_ms as u64) {
-            Ok(p) => p,
-            Err(e) => {
-                emit_log("warn", &format!("Port açılamadı @ {} baud: {}", try_baud, e));
-                continue;
-            }
-        };
-
-        emit_log("success", &format!("Port açıldı @ {} baud", try_baud));
-
-        let request = iec62056::build_request_message(meter_address.as_deref());
-        let request_str = iec62056::format_bytes_for_display(&request);
-        emit_log("tx", &request_str);
-
-        if let Err(e) = current_port.write_all(&request) {
-            emit_log("warn", &format!("Handshake gönderilemedi: {}", e));
-            continue;
-        }
-        let _ = current_port.flush();
-
-        emit_log("info", "Yanıt bekleniyor...");
-        std::thread::sleep(Duration::from_millis(500));
-
-        let mut response_buf = vec![0u8; 256];
-        let mut ident_read = 0;
-        let handshake_start = std::time::Instant::now();
-
-        loop {
-            match current_port.read(&mut response_buf[ident_read..]) {
-                Ok(n) if n > 0 => {
-                    ident_read += n;
-                    let _ = window.emit("comm-activity", serde_json::json!({"type": "rx"}));
-                    if ident_read >= 2 &&
-                       response_buf[ident_read - 2] == control::CR &&
-                       response_buf[ident_read - 1] == control::LF {
-                        break;
-                    }
-                }
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    if ident_read > 0 { break; }
-                }
-                Err(_) => break,
-            }
-            if handshake_start.elapsed() > Duration::from_millis(timeout_ms as u64) {
-                break;
-            }
-        }
-
-        if ident_read > 0 {
-            let response_formatted = iec62056::format_bytes_for_display(&response_buf[..ident_read]);
-            emit_log("rx", &response_formatted);
-
-            let response = String::from_utf8_lossy(&response_buf[..ident_read]);
-            if let Some(parsed) = iec62056::parse_identification(&response) {
-                emit_log("success", &format!("Sayaç tanımlandı: {} — {} ({})",
-                    parsed.manufacturer, parsed.edas_id, parsed.model));
-                initial_baud = try_baud;
-                ident = Some(parsed);
-                port = Some(current_port);
-                break;
-            } else {
-                emit_log("warn", "Sayaç tanımlama yanıtı ayrıştırılamadı");
-            }
-        } else {
-            emit_log("warn", &format!("{} baud'da yanıt alınamadı", try_baud));
-        }
-    }
-
-    let mut port = port.ok_or_else(|| {
-        emit_log("error", "Hiçbir baud hızında yanıt alınamadı");
-        "Hiçbir baud hızında yanıt alınamadı".to_string()
-    })?;
-    let ident = ident.unwrap();
-
-    // Step 4: Send ACK with Mode 1 (Programming mode)
-    let (target_baud, baud_char) = io::resolve_target_baud(
-        &connection_type, configured_baud, ident.max_baud_rate, ident.baud_char
-    );
-
-    let ack = iec62056::build_ack_message(ProtocolMode::Programming, baud_char);
-    let ack_formatted = iec62056::format_bytes_for_display(&ack);
-    emit_log("tx", &ack_formatted);
-
-    port.write_all(&ack).map_err(|e| format!("ACK gönderilemedi: {}", e))?;
-    let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-    let _ = port.flush();
-
-    // Wait and switch baud rate
-    emit_log("info", &format!("Baud hızı değiştiriliyor: {} -> {}", initial_baud, target_baud));
-    std::thread::sleep(Duration::from_millis(300));
+) -> Result<std::collections::HashMap<String, ObisReading>, AppError> {
+    log::info!("Batch OBIS read: {:?} (atomic)", obis_codes);
+    let operation = Operation::start(operation_id)?;
 
-    if target_baud != initial_baud {
-        port.set_baud_rate(target_baud).map_err(|e| {
-            emit_log("error", &format!("Baud hızı değiştirilemedi: {}", e));
-            format!("Baud hızı değiştirilemedi: {}", e)
-        })?;
-        emit_log("success", &format!("Baud hızı {} olarak ayarlandı", target_baud));
+    if obis_codes.is_empty() {
+        return Err(AppError::invalid_input("reason_empty_obis_list"));
     }
 
-    // Wait for meter to be ready
-    std::thread::sleep(Duration::from_millis(500));
+    // Steps 1-2: Get connection parameters from stored state, close any existing connection
+    let (options, worker, _operation) = fresh_worker(&window).await?;
 
-    // Read any response from meter (password request or acknowledgment)
-    let mut prog_buf = vec![0u8; 256];
-    let prog_read = port.read(&mut prog_buf).unwrap_or(0);
-    if prog_read > 0 {
-        let prog_formatted = iec62056::format_bytes_for_display(&prog_buf[..prog_read]);
-        emit_log("rx", &prog_formatted);
-    }
+    // Step 3: Open port and handshake with baud rate retry
+    worker.identify(options, Some(&window), Some(operation.token())).await?;
 
-    emit_log("success", "Programlama moduna geçildi");
+    // Step 4: Send ACK with Mode 1 (Programming mode)
+    worker.enter_programming().await?;
 
     // Step 5: Read each OBIS code via R2 commands
     let mut results = std::collections::HashMap::new();
@@ -1256,65 +489,46 @@ pub async fn read_obis_batch(
             continue;
         }
 
-        emit_log("info", &format!("[{}/{}] OBIS {} okunuyor...", i + 1, total_codes, trimmed));
+        // Codes not read before the cancel are reported as such
+        if operation.is_cancelled() {
+            results.insert(trimmed, ObisReading::error("Cancelled"));
+            continue;
+        }
+
+        emit_log(&window, "info", &format!("[{}/{}] OBIS {} okunuyor...", i + 1, total_codes, trimmed));
 
         let cmd = iec62056::build_read_command(&trimmed);
-        emit_log("tx", &format!("R2 {}()", trimmed));
+        emit_log(&window, "tx", &format!("R2 {}()", trimmed));
 
-        if let Err(e) = port.write_all(&cmd) {
-            emit_log("error", &format!("R2 komutu gönderilemedi: {}", e));
-            results.insert(trimmed, format!("ERROR: {}", e));
-            continue;
-        }
-        let _ = port.flush();
-        let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-
-        // Wait for response
-        std::thread::sleep(Duration::from_millis(300));
-
-        let mut buf = vec![0u8; 512];
-        let mut total = 0;
-        let read_start = std::time::Instant::now();
-
-        loop {
-            match port.read(&mut buf[total..]) {
-                Ok(n) if n > 0 => {
-                    total += n;
-                    let _ = window.emit("comm-activity", serde_json::json!({"type": "rx"}));
-                    // Check for ETX or CR+LF termination
-                    if total >= 2 {
-                        let last_bytes = &buf[..total];
-                        if last_bytes.contains(&control::ETX) ||
-                           (last_bytes[total - 2] == control::CR && last_bytes[total - 1] == control::LF) {
-                            break;
-                        }
-                    }
-                    if total >= buf.len() { break; }
-                }
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    if total > 0 { break; }
-                }
-                Err(_) => break,
-            }
-            if read_start.elapsed() > Duration::from_millis(timeout_ms as u64) {
-                break;
+        let reply = match worker.command(cmd).await {
+            Ok(reply) => reply,
+            Err(e) => {
+                emit_log(&window, "error", &e.to_string());
+                results.insert(trimmed, ObisReading::error(e));
+                continue;
             }
-        }
+        };
 
-        if total > 0 {
-            let raw = &buf[..total];
-            let response = String::from_utf8_lossy(raw).to_string();
-            emit_log("rx", &response);
-
-            // Extract data between STX and ETX to exclude protocol framing and BCC byte
-            // BCC can be any byte value including ')' (0x29) which corrupts parsing
-            let data_slice = match (raw.iter().position(|&b| b == control::STX),
-                                    raw.iter().position(|&b| b == control::ETX)) {
-                (Some(s), Some(e)) if s < e => &raw[s + 1..e],
-                _ => raw,
+        if let Some(frame) = reply.frame {
+            emit_log(&window, "rx", &iec62056::format_bytes_for_display(&reply.raw));
+
+            // Only the data between STX and ETX is parsed; the BCC byte
+            // can be any value including ')' (0x29) which corrupts parsing
+            let (data, bcc_ok) = match frame {
+                iec62056::Frame::DataBlock { data, bcc_ok } => (data, bcc_ok),
+                iec62056::Frame::Nak => {
+                    emit_log(&window, "warn", &format!("{} okuması reddedildi (NAK)", trimmed));
+                    results.insert(trimmed, ObisReading::error("NAK"));
+                    continue;
+                }
+                other => {
+                    let e = AppError::unexpected_frame(&other);
+                    emit_log(&window, "error", &format!("{}: {}", trimmed, e));
+                    results.insert(trimmed, ObisReading::error(e));
+                    continue;
+                }
             };
-            let cleaned = String::from_utf8_lossy(data_slice)
+            let cleaned = String::from_utf8_lossy(&data)
                 .chars().filter(|c| !c.is_control()).collect::<String>();
 
             if let Some(item) = iec62056::parse_obis_response(cleaned.trim()) {
@@ -1323,92 +537,95 @@ pub async fn read_obis_batch(
                 } else {
                     item.value
                 };
-                emit_log("success", &format!("{} = {}", trimmed, value));
-                results.insert(trimmed, value);
+                if bcc_ok {
+                    emit_log(&window, "success", &format!("{} = {}", trimmed, value));
+                } else {
+                    emit_log(&window, "warn", &format!("{} = {} (BCC hatalı, değer güvenilmez)", trimmed, value));
+                }
+                results.insert(trimmed, ObisReading { value, untrusted: !bcc_ok });
             } else {
-                results.insert(trimmed, cleaned.trim().to_string());
+                results.insert(trimmed, ObisReading { value: cleaned.trim().to_string(), untrusted: !bcc_ok });
             }
         } else {
-            emit_log("warn", &format!("{} için yanıt alınamadı", trimmed));
-            results.insert(trimmed, "ERROR: No response".to_string());
+            emit_log(&window, "warn", &format!("{} için yanıt alınamadı", trimmed));
+            results.insert(trimmed, ObisReading::error("No response"));
         }
 
         // Small delay between reads
-        std::thread::sleep(Duration::from_millis(100));
+        tokio::time::sleep(Duration::from_millis(100)).await;
     }
 
     // Step 6: Send Break command and close port
-    emit_log("info", "Oturum sonlandırılıyor...");
-    let break_cmd = iec62056::build_break_command();
-    let _ = port.write_all(&break_cmd);
-    let _ = port.flush();
-    std::thread::sleep(Duration::from_millis(100));
-    drop(port);
-    emit_log("info", "Port kapatıldı");
-
-    // Mark as not connected since we closed the port
-    {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        manager.connected = false;
-        manager.port = None;
-        manager.in_programming_mode = false;
-    }
+    worker.close().await?;
 
-    emit_log("success", &format!("OBIS toplu okuma tamamlandı: {} / {} başarılı",
-        results.values().filter(|v| !v.starts_with("ERROR:")).count(), total_codes));
+    let succeeded = results.values().filter(|v| !v.is_error()).count();
+    if operation.is_cancelled() {
+        emit_log(&window, "warn", &format!("OBIS toplu okuma iptal edildi: {} / {} okundu", succeeded, total_codes));
+    } else {
+        emit_log(&window, "success", &format!("OBIS toplu okuma tamamlandı: {} / {} başarılı", succeeded, total_codes));
+    }
 
     Ok(results)
 }
 
 /// Write a value to an OBIS code (requires programming mode)
 #[tauri::command]
-pub async fn write_obis(obis_code: String, value: String, window: tauri::Window) -> Result<(), String> {
-    log::info!("Writing OBIS code: {} = {}", obis_code, value);
-
-    let emit_log = |log_type: &str, message: &str| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: None,
-        });
-    };
+pub async fn write_obis(obis_code: String, value: String, window: tauri::Window) -> Result<(), AppError> {
+    let (_, worker, _operation) = stored_worker().await?;
+    worker.attach(Some(&window), None).await?;
+    let previous = read_previous(&worker, &obis_code, &window).await;
+    write_on_session(&worker, &obis_code, &value, previous.as_deref(), Some(&window)).await
+}
 
-    let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-    if !manager.connected {
-        return Err("Not connected to meter".to_string());
+/// Value of `obis_code` before a write, for the audit log; `None` when it
+/// cannot be read reliably. The password register is recorded masked
+/// anyway and is not read.
+async fn read_previous(worker: &MeterWorker, obis_code: &str, window: &tauri::Window) -> Option<String> {
+    if audit::is_password_register(obis_code) {
+        return None;
     }
-    if !manager.in_programming_mode {
-        return Err("Meter is not in programming mode".to_string());
+    match read_on_session(worker, obis_code, Some(window)).await {
+        Ok(reading) if !reading.untrusted => Some(reading.value),
+        _ => None,
     }
+}
 
-    let port = manager.port.as_mut().ok_or("Port not available")?;
-
-    // Build and send write command
-    let cmd = iec62056::build_write_command(&obis_code, &value);
-    emit_log("tx", &format!("W2 {}({})", obis_code, value));
-
-    port.write_all(&cmd).map_err(|e| format!("Write failed: {}", e))?;
-    port.flush().map_err(|e| format!("Flush failed: {}", e))?;
-
-    // Wait for ACK
-    std::thread::sleep(Duration::from_millis(200));
-
-    let mut buf = [0u8; 1];
-    match port.read(&mut buf) {
-        Ok(1) if buf[0] == control::ACK => {
-            emit_log("rx", "ACK");
+/// W2 `value` to `obis_code` on the programming session of `worker` and record
+/// the write in the audit log; `previous` is the value read before, when known
+async fn write_on_session(
+    worker: &MeterWorker,
+    obis_code: &str,
+    value: &str,
+    previous: Option<&str>,
+    window: Option<&tauri::Window>,
+) -> Result<(), AppError> {
+    log::info!("Writing OBIS code: {} = {}", obis_code, audit::loggable(obis_code, value));
+
+    // Build and send write command, wait for ACK
+    let cmd = iec62056::build_write_command(obis_code, value);
+    emit_log(window, "tx", &format!("W2 {}({})", obis_code, audit::loggable(obis_code, value)));
+
+    let result = worker.command(cmd).await.and_then(|reply| match reply.frame {
+        Some(iec62056::Frame::Ack) => {
+            emit_log(window, "rx", "ACK");
             Ok(())
         }
-        Ok(1) if buf[0] == control::NAK => {
-            emit_log("error", "NAK - Yazma reddedildi");
-            Err("Write rejected by meter (NAK)".to_string())
+        Some(iec62056::Frame::Nak) => {
+            emit_log(window, "error", "NAK - Yazma reddedildi");
+            Err(AppError::Nak { command: "W2".to_string() })
         }
-        _ => {
-            emit_log("error", "Geçersiz yanıt");
-            Err("Invalid response from meter".to_string())
+        Some(other) => {
+            emit_log(window, "error", "Geçersiz yanıt");
+            Err(AppError::unexpected_frame(&other))
         }
-    }
+        None => {
+            emit_log(window, "error", "Yanıt alınamadı");
+            Err(AppError::Timeout { received: reply.pending })
+        }
+    });
+
+    audit::record_write(obis_code, previous, value, &result);
+    result
 }
 
 /// Authenticate with the meter (enter programming mode)
@@ -1416,283 +633,445 @@ pub async fn write_obis(obis_code: String, value: String, window: tauri::Window)
 /// switches baud, sends password. Does NOT require a prior active connection —
 /// only needs stored params from a previous connect() call.
 #[tauri::command]
-pub async fn authenticate(password: String, window: tauri::Window) -> Result<bool, String> {
+pub async fn authenticate(password: String, window: tauri::Window) -> Result<bool, AppError> {
     log::info!("Authenticating with meter (atomic)");
 
-    let emit_log = |log_type: &str, message: &str| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: None,
-        });
-    };
+    // The session stays open on the worker for subsequent write_obis calls
+    match begin_programming(&password, &window).await {
+        Ok(_) => Ok(true),
+        Err(AppError::PasswordRejected) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
 
-    // Validate password format (8 digits)
-    if password.len() != 8 || !password.chars().all(|c| c.is_ascii_digit()) {
-        return Err("Password must be exactly 8 digits".to_string());
+/// Close any existing connection and log in on a new programming session;
+/// the worker stays held for the calling command by the returned guard
+async fn begin_programming(password: &str, window: &tauri::Window) -> Result<(MeterWorker, OperationGuard), AppError> {
+    if !is_valid_password(password) {
+        return Err(AppError::InvalidPassword);
     }
 
-    // Step 1: Get connection parameters from stored state
-    let (timeout_ms, port_name, meter_address, connection_type, configured_baud) = {
-        let manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.params.is_none() {
-            return Err("Bağlantı parametresi yok. Önce 'Bağlan' butonuna tıklayın.".to_string());
-        }
-        let params = manager.params.as_ref().unwrap();
-        (
-            if params.timeout_ms == 0 { 2000 } else { params.timeout_ms },
-            params.port.clone(),
-            params.meter_address.clone(),
-            params.connection_type.clone(),
-            params.baud_rate,
-        )
-    };
+    // Steps 1-2: Get connection parameters from stored state, close any existing connection
+    let (options, worker, operation) = fresh_worker(window).await?;
 
-    // Step 2: Close any existing connection
-    {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.port.is_some() {
-            emit_log("info", "Mevcut bağlantı kapatılıyor...");
-            manager.disconnect();
-        }
+    if !login(&worker, options, PROGRAMMING_LEVEL, password, Some(window)).await? {
+        return Err(AppError::PasswordRejected);
     }
+    Ok((worker, operation))
+}
 
-    // Step 3: Open port and handshake with baud rate retry
-    let baud_rates = io::resolve_initial_bauds(&connection_type, configured_baud);
-    let mut port: Option<Box<dyn SerialPort>> = None;
-    let mut ident: Option<iec62056::MeterIdent> = None;
-    let mut initial_baud: u32 = 0;
+/// Open a programming session on `worker` and send the `password` of `level`;
+/// the attempt is recorded in the audit log and a rejected session is closed
+async fn login(
+    worker: &MeterWorker,
+    options: SessionOptions,
+    level: u8,
+    password: &str,
+    window: Option<&tauri::Window>,
+) -> Result<bool, AppError> {
+    // Open port and handshake with baud rate retry
+    worker.identify(options, window, None).await?;
+
+    // Send ACK with Mode 1 (Programming mode)
+    worker.enter_programming().await?;
+
+    // Send the password command
+    emit_log(window, "info", "Şifre gönderiliyor...");
+    let accepted = worker.authenticate(level, password).await;
+    audit::record_login(level, &accepted);
+    if !accepted? {
+        finish_session(worker, window).await;
+        return Ok(false);
+    }
+    Ok(true)
+}
 
-    for (attempt, &try_baud) in baud_rates.iter().enumerate() {
-        emit_log("info", &format!("Port açılıyor: {} @ {} baud (7E1) [Deneme {}/{}]",
-            port_name, try_baud, attempt + 1, baud_rates.len()));
+/// Meter passwords are 8 digits
+fn is_valid_password(password: &str) -> bool {
+    password.len() == 8 && password.chars().all(|c| c.is_ascii_digit())
+}
 
-        let mut current_port = match iec62056::open_port(&port_name, try_baud, timeout_ms as u64) {
-            Ok(p) => p,
-            Err(e) => {
-                emit_log("warn", &format!("Port açılamadı @ {} baud: {}", try_baud, e));
-                continue;
-            }
-        };
+/// Sync meter time to computer time
+/// This is an ATOMIC operation: time (0.9.1), date (0.9.2) and day of week (0.9.5)
+/// are written as one `program_meter` transaction
+#[tauri::command]
+pub async fn sync_time(password: String, window: tauri::Window) -> Result<ProgramMeterResult, AppError> {
+    log::info!("Syncing meter time");
+
+    let now = chrono::Local::now();
+    let time_str = now.format("%H:%M:%S").to_string();
+    let date_str = now.format("%y-%m-%d").to_string();
+    let dow = now.format("%u").to_string(); // 1-7, Monday = 1
 
-        emit_log("success", &format!("Port açıldı @ {} baud", try_baud));
+    emit_log(&window, "info", &format!("Saat senkronizasyonu: {} {}", date_str, time_str));
 
-        let request = iec62056::build_request_message(meter_address.as_deref());
-        let request_str = iec62056::format_bytes_for_display(&request);
-        emit_log("tx", &request_str);
+    let writes = vec![
+        RegisterWrite { obis_code: "0.9.1".to_string(), value: time_str },
+        RegisterWrite { obis_code: "0.9.2".to_string(), value: date_str },
+        RegisterWrite { obis_code: "0.9.5".to_string(), value: dow },
+    ];
+    program_meter(password, writes, window).await
+}
 
-        if let Err(e) = current_port.write_all(&request) {
-            emit_log("warn", &format!("Handshake gönderilemedi: {}", e));
-            continue;
-        }
-        let _ = current_port.flush();
-
-        emit_log("info", "Yanıt bekleniyor...");
-        std::thread::sleep(Duration::from_millis(500));
-
-        let mut response_buf = vec![0u8; 256];
-        let mut ident_read = 0;
-        let handshake_start = std::time::Instant::now();
-
-        loop {
-            match current_port.read(&mut response_buf[ident_read..]) {
-                Ok(n) if n > 0 => {
-                    ident_read += n;
-                    let _ = window.emit("comm-activity", serde_json::json!({"type": "rx"}));
-                    if ident_read >= 2 &&
-                       response_buf[ident_read - 2] == control::CR &&
-                       response_buf[ident_read - 1] == control::LF {
-                        break;
-                    }
-                }
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    if ident_read > 0 { break; }
-                }
-                Err(_) => break,
-            }
-            if handshake_start.elapsed() > Duration::from_millis(timeout_ms as u64) {
-                break;
-            }
-        }
+/// End the programming session
+#[tauri::command]
+pub async fn end_session(window: tauri::Window) -> Result<(), AppError> {
+    log::info!("Ending programming session");
 
-        if ident_read > 0 {
-            let response_formatted = iec62056::format_bytes_for_display(&response_buf[..ident_read]);
-            emit_log("rx", &response_formatted);
-
-            let response = String::from_utf8_lossy(&response_buf[..ident_read]);
-            if let Some(parsed) = iec62056::parse_identification(&response) {
-                emit_log("success", &format!("Sayaç tanımlandı: {} — {} ({})",
-                    parsed.manufacturer, parsed.edas_id, parsed.model));
-                initial_baud = try_baud;
-                ident = Some(parsed);
-                port = Some(current_port);
-                break;
-            } else {
-                emit_log("warn", "Sayaç tanımlama yanıtı ayrıştırılamadı");
-            }
-        } else {
-            emit_log("warn", &format!("{} baud'da yanıt alınamadı", try_baud));
-        }
+    let (_, worker, _operation) = stored_worker().await?;
+    worker.attach(Some(&window), None).await?;
+    close_session(&worker, Some(&window)).await
+}
+
+/// Send the break and close the programming session of `worker`
+async fn close_session(worker: &MeterWorker, window: Option<&tauri::Window>) -> Result<(), AppError> {
+    // Send break command and close port — meter returns to idle after break
+    emit_log(window, "tx", "B0 (Break)");
+    worker.close().await?;
+    emit_log(window, "info", "Programlama oturumu sonlandırıldı, port kapatıldı");
+    Ok(())
+}
+
+/// `close_session` at the end of an atomic programming command; the outcome of
+/// the writes matters more than the break, so a failed close is only logged
+async fn finish_session(worker: &MeterWorker, window: Option<&tauri::Window>) {
+    if let Err(e) = close_session(worker, window).await {
+        emit_log(window, "warn", &format!("Programlama oturumu kapatılamadı: {}", e));
     }
+}
 
-    let mut port = port.ok_or_else(|| {
-        emit_log("error", "Hiçbir baud hızında yanıt alınamadı");
-        "Hiçbir baud hızında yanıt alınamadı".to_string()
-    })?;
-    let ident = ident.unwrap();
+/// Program the tariff tables (96.50-96.52 switching times, 96.60-96.62 tariffs)
+/// This is an ATOMIC operation: validates the schedule, authenticates, writes all
+/// six tables in one programming session, reads each back with R2 and closes the port
+#[tauri::command]
+pub async fn program_tariffs(
+    password: String,
+    schedule: TariffSchedule,
+    window: tauri::Window,
+) -> Result<ProgramResult, AppError> {
+    log::info!("Programming tariff tables (atomic)");
 
-    // Step 4: Send ACK with Mode 1 (Programming mode)
-    let (target_baud, baud_char) = io::resolve_target_baud(
-        &connection_type, configured_baud, ident.max_baud_rate, ident.baud_char
-    );
+    // Nothing is sent to the meter for an invalid schedule
+    schedule.validate()?;
+
+    let (worker, _operation) = begin_programming(&password, &window).await?;
+
+    let result = write_and_verify(&worker, &schedule.tables(), &window).await;
+    finish_session(&worker, Some(&window)).await;
+    result
+}
+
+/// Program the DST settings (96.90.0 enable flag, 96.90.1-96.90.12 periods)
+/// This is an ATOMIC operation: validates the schedule, authenticates, writes the
+/// flag and all 12 period slots, reads each back with R2 and closes the port
+#[tauri::command]
+pub async fn program_dst(
+    password: String,
+    schedule: DstSchedule,
+    window: tauri::Window,
+) -> Result<ProgramResult, AppError> {
+    log::info!("Programming DST schedule (atomic)");
+
+    // Nothing is sent to the meter for an invalid schedule
+    schedule.validate()?;
+
+    let (worker, _operation) = begin_programming(&password, &window).await?;
 
-    let ack = iec62056::build_ack_message(ProtocolMode::Programming, baud_char);
-    let ack_formatted = iec62056::format_bytes_for_display(&ack);
-    emit_log("tx", &ack_formatted);
+    let result = write_and_verify(&worker, &schedule.tables(), &window).await;
+    finish_session(&worker, Some(&window)).await;
+    result
+}
 
-    port.write_all(&ack).map_err(|e| format!("ACK gönderilemedi: {}", e))?;
-    let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-    let _ = port.flush();
+/// DST schedule of `rule` with one period per year from `first_year`
+#[tauri::command]
+pub fn generate_dst_schedule(rule: DstRule, first_year: i32) -> Result<DstSchedule, AppError> {
+    DstSchedule::generate(&rule, first_year)
+}
 
-    // Wait and switch baud rate
-    emit_log("info", &format!("Baud hızı değiştiriliyor: {} -> {}", initial_baud, target_baud));
-    std::thread::sleep(Duration::from_millis(300));
+/// Write several registers as one transaction
+/// This is an ATOMIC operation: authenticates, reads the current value of every
+/// register with R2, writes them in order and reads each back. When a write is not
+/// acknowledged or a register does not verify, the registers already written get
+/// their old values back where those could be read. Closes the port at the end.
+#[tauri::command]
+pub async fn program_meter(
+    password: String,
+    writes: Vec<RegisterWrite>,
+    window: tauri::Window,
+) -> Result<ProgramMeterResult, AppError> {
+    log::info!("Programming {} registers (atomic)", writes.len());
 
-    if target_baud != initial_baud {
-        port.set_baud_rate(target_baud).map_err(|e| {
-            emit_log("error", &format!("Baud hızı değiştirilemedi: {}", e));
-            format!("Baud hızı değiştirilemedi: {}", e)
-        })?;
-        emit_log("success", &format!("Baud hızı {} olarak ayarlandı", target_baud));
+    if writes.is_empty() {
+        return Err(AppError::invalid_input("reason_empty_write_list"));
+    }
+    if writes.iter().any(|w| w.obis_code.trim().is_empty()) {
+        return Err(AppError::invalid_input("reason_empty_obis_code"));
     }
 
-    // Wait for meter to be ready
-    std::thread::sleep(Duration::from_millis(500));
+    let (worker, _operation) = begin_programming(&password, &window).await?;
 
-    // Read any response from meter (password request or acknowledgment)
-    let mut prog_buf = vec![0u8; 256];
-    let prog_read = port.read(&mut prog_buf).unwrap_or(0);
-    if prog_read > 0 {
-        let prog_formatted = iec62056::format_bytes_for_display(&prog_buf[..prog_read]);
-        emit_log("rx", &prog_formatted);
+    // Step 1: Snapshot the current values; an untrusted value is not restored
+    emit_log(&window, "info", "Mevcut değerler okunuyor...");
+    let mut registers = Vec::with_capacity(writes.len());
+    for write in writes {
+        let obis_code = write.obis_code.trim().to_string();
+        let old_value = match read_on_session(&worker, &obis_code, Some(&window)).await {
+            Ok(reading) if !reading.untrusted => Some(reading.value),
+            Ok(_) => {
+                emit_log(&window, "warn", &format!("{} güvenilir okunamadı, hata durumunda geri alınamaz", obis_code));
+                None
+            }
+            Err(e) => {
+                emit_log(&window, "warn", &format!("{} okunamadı, hata durumunda geri alınamaz: {}", obis_code, e));
+                None
+            }
+        };
+        registers.push(RegisterReport {
+            obis_code,
+            old_value,
+            new_value: write.value,
+            reply: WriteReply::NotSent,
+            read_back: None,
+            verified: false,
+            restored: false,
+        });
     }
 
-    emit_log("info", "Programlama moduna geçildi, şifre gönderiliyor...");
-
-    // Step 5: Send P1 password command
-    let cmd = iec62056::build_password_command(&password);
-    emit_log("tx", "P1 (********)");
-
-    port.write_all(&cmd).map_err(|e| format!("Write failed: {}", e))?;
-    port.flush().map_err(|e| format!("Flush failed: {}", e))?;
-
-    // Wait for response
-    std::thread::sleep(Duration::from_millis(500));
-
-    let mut buf = [0u8; 1];
-    match port.read(&mut buf) {
-        Ok(1) if buf[0] == control::ACK => {
-            emit_log("success", "Şifre kabul edildi - Programlama modu aktif");
-            // Store port in CONNECTION_STATE for subsequent write_obis/sync_time calls
-            let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-            manager.port = Some(port);
-            manager.connected = true;
-            manager.in_programming_mode = true;
-            manager.negotiated_baud = target_baud;
-            Ok(true)
+    // Step 2: Write in order, stopping at the first write that is not acknowledged
+    let mut failed = false;
+    for register in registers.iter_mut() {
+        let write = write_on_session(&worker, &register.obis_code, &register.new_value, register.old_value.as_deref(), Some(&window));
+        register.reply = match write.await {
+            Ok(()) => WriteReply::Ack,
+            Err(AppError::Nak { .. }) => WriteReply::Nak,
+            Err(_) => WriteReply::Failed,
+        };
+        if register.reply != WriteReply::Ack {
+            emit_log(&window, "error", &format!("{} yazılamadı ({:?})", register.obis_code, register.reply));
+            failed = true;
+            break;
         }
-        Ok(1) if buf[0] == control::NAK => {
-            emit_log("error", "Şifre reddedildi!");
-            // Close port — authentication failed
-            drop(port);
-            Ok(false)
+    }
+
+    // Step 3: Read every register back
+    if !failed {
+        for register in registers.iter_mut() {
+            let verification = read_back(&worker, &register.obis_code, &register.new_value, &window).await;
+            register.verified = verification.verified;
+            register.read_back = verification.read_back;
+            failed |= !register.verified;
         }
-        _ => {
-            emit_log("error", "Geçersiz yanıt");
-            drop(port);
-            Err("Invalid response from meter".to_string())
+    }
+
+    // Step 4: Restore the snapshot, last write first; a write without a reply may have been applied
+    if failed {
+        emit_log(&window, "warn", "İşlem başarısız, yazılan kayıtlar eski değerlerine döndürülüyor...");
+        for register in registers.iter_mut().rev() {
+            if !register.may_be_written() {
+                continue;
+            }
+            let Some(old_value) = register.old_value.as_deref() else {
+                emit_log(&window, "warn", &format!("{} eski değeri bilinmediği için geri alınamadı", register.obis_code));
+                continue;
+            };
+            let restore = write_on_session(&worker, &register.obis_code, old_value, register.read_back.as_deref(), Some(&window));
+            register.restored = restore.await.is_ok();
         }
+    } else {
+        emit_log(&window, "success", &format!("{} kayıt yazıldı ve doğrulandı", registers.len()));
+    }
+
+    finish_session(&worker, Some(&window)).await;
+
+    // The password register is reported masked, as in the logs
+    for register in registers.iter_mut() {
+        let code = register.obis_code.clone();
+        let mask = |value: &str| audit::loggable(&code, value).to_string();
+        register.old_value = register.old_value.as_deref().map(mask);
+        register.new_value = mask(&register.new_value);
+        register.read_back = register.read_back.as_deref().map(mask);
     }
+
+    Ok(ProgramMeterResult::new(registers, failed))
 }
 
-/// Sync meter time to computer time
+/// Connect the relay (`96.3.10` = 1) of the meter with serial `expected_serial`
+/// `confirmation` must be `CONNECT:<expected_serial>`; see `switch_relay`
 #[tauri::command]
-pub async fn sync_time(window: tauri::Window) -> Result<(), String> {
-    log::info!("Syncing meter time");
+pub async fn relay_connect(
+    password: String,
+    expected_serial: String,
+    confirmation: String,
+    window: tauri::Window,
+) -> Result<RelayResult, AppError> {
+    switch_relay(RelayState::Connected, password, expected_serial, confirmation, window).await
+}
 
-    let emit_log = |log_type: &str, message: &str| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: None,
-        });
-    };
+/// Disconnect the relay (`96.3.10` = 0) of the meter with serial `expected_serial`
+/// `confirmation` must be `DISCONNECT:<expected_serial>`; see `switch_relay`
+#[tauri::command]
+pub async fn relay_disconnect(
+    password: String,
+    expected_serial: String,
+    confirmation: String,
+    window: tauri::Window,
+) -> Result<RelayResult, AppError> {
+    switch_relay(RelayState::Disconnected, password, expected_serial, confirmation, window).await
+}
 
-    {
-        let manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if !manager.connected {
-            return Err("Not connected to meter".to_string());
-        }
-        if !manager.in_programming_mode {
-            return Err("Meter is not in programming mode".to_string());
-        }
+/// This is an ATOMIC operation: checks the confirmation token, authenticates, reads the
+/// serial number and refuses to switch a different meter, writes 96.3.10, reads the
+/// state back and closes the port. The write is recorded in the audit log.
+async fn switch_relay(
+    target: RelayState,
+    password: String,
+    expected_serial: String,
+    confirmation: String,
+    window: tauri::Window,
+) -> Result<RelayResult, AppError> {
+    log::info!("Switching relay to {:?} (atomic)", target);
+
+    let expected_serial = expected_serial.trim().to_string();
+    if expected_serial.is_empty() {
+        return Err(AppError::invalid_input("reason_expected_serial_missing"));
+    }
+    if confirmation != target.confirmation(&expected_serial) {
+        return Err(AppError::invalid_input("reason_relay_confirmation"));
     }
 
-    let now = chrono::Local::now();
-    let time_str = now.format("%H:%M:%S").to_string();
-    let date_str = now.format("%y-%m-%d").to_string();
-    let dow = now.format("%u").to_string(); // 1-7, Monday = 1
+    let (worker, _operation) = begin_programming(&password, &window).await?;
+
+    let result = switch_relay_in_session(&worker, target, &expected_serial, &window).await;
+    finish_session(&worker, Some(&window)).await;
+    result
+}
 
-    emit_log("info", &format!("Saat senkronizasyonu: {} {}", date_str, time_str));
+async fn switch_relay_in_session(
+    worker: &MeterWorker,
+    target: RelayState,
+    expected_serial: &str,
+    window: &tauri::Window,
+) -> Result<RelayResult, AppError> {
+    // Step 1: Make sure this is the meter the operator meant; 96.1.0 if 0.0.0 is empty
+    let serial_number = match read_trusted(worker, "0.0.0", window).await {
+        Ok(serial) if !serial.trim().is_empty() => serial.trim().to_string(),
+        _ => read_trusted(worker, "96.1.0", window).await?.trim().to_string(),
+    };
+    if serial_number != expected_serial {
+        emit_log(window, "error", &format!("Seri numarası uyuşmuyor: sayaç {}, beklenen {}", serial_number, expected_serial));
+        return Err(AppError::MeterMismatch { expected: expected_serial.to_string(), actual: serial_number });
+    }
 
-    // Write time
-    write_obis("0.9.1".to_string(), time_str, window.clone()).await?;
-    // Write date
-    write_obis("0.9.2".to_string(), date_str, window.clone()).await?;
-    // Write day of week
-    write_obis("0.9.5".to_string(), dow, window).await?;
+    // Step 2: Current state, kept in the audit log as the previous value
+    let previous_value = read_trusted(worker, "96.3.10", window).await.ok();
+    let previous_state = previous_value.as_deref().and_then(RelayState::parse);
 
-    Ok(())
+    // Step 3: Switch
+    emit_log(window, "info", &format!("Röle durumu değiştiriliyor: 96.3.10 = {}", target.value()));
+    write_on_session(worker, "96.3.10", target.value(), previous_value.as_deref(), Some(window)).await?;
+
+    // Step 4: Read back
+    let state = match read_trusted(worker, "96.3.10", window).await {
+        Ok(value) => RelayState::parse(&value),
+        Err(e) => {
+            emit_log(window, "warn", &format!("Röle durumu geri okunamadı: {}", e));
+            None
+        }
+    };
+    let confirmed = state == Some(target);
+    if confirmed {
+        emit_log(window, "success", &format!("Röle durumu doğrulandı: 96.3.10 = {}", target.value()));
+    } else {
+        emit_log(window, "warn", &format!("Röle durumu doğrulanamadı: istenen {:?}, okunan {:?}", target, state));
+    }
+
+    Ok(RelayResult { serial_number, previous_state, requested_state: target, state, confirmed })
 }
 
-/// End the programming session
+/// Change the password of `level` (P1 is written to 96.96)
+/// This is an ATOMIC operation, see `password::change`. Levels without a
+/// documented register (see `password::REGISTERS`) are rejected.
 #[tauri::command]
-pub async fn end_session(window: tauri::Window) -> Result<(), String> {
-    log::info!("Ending programming session");
+pub async fn change_password(
+    level: u8,
+    old_password: String,
+    new_password: String,
+    window: tauri::Window,
+) -> Result<PasswordChangeResult, AppError> {
+    log::info!("Changing P{} password (atomic)", level);
 
-    let emit_log = |log_type: &str, message: &str| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: None,
-        });
-    };
+    password::register(level)?;
+    if !is_valid_password(&old_password) || !is_valid_password(&new_password) {
+        return Err(AppError::InvalidPassword);
+    }
+    if old_password == new_password {
+        return Err(AppError::invalid_input("reason_password_unchanged"));
+    }
+
+    let (options, worker, _operation) = fresh_worker(&window).await?;
+    password::change(&worker, options, level, &old_password, &new_password, Some(&window)).await
+}
 
-    let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-    if !manager.connected {
-        return Err("Not connected to meter".to_string());
+/// Write each `(code, value)` on the open programming session, reading the
+/// value before it for the audit log, then read every one back and compare.
+/// A rejected write stops the sequence.
+async fn write_and_verify(
+    worker: &MeterWorker,
+    writes: &[(String, String)],
+    window: &tauri::Window,
+) -> Result<ProgramResult, AppError> {
+    for (i, (code, value)) in writes.iter().enumerate() {
+        let previous = read_previous(worker, code, window).await;
+        if let Err(e) = write_on_session(worker, code, value, previous.as_deref(), Some(window)).await {
+            emit_log(window, "error", &format!("{} yazılamadı, {} / {} kayıt yazıldı", code, i, writes.len()));
+            return Err(e);
+        }
     }
 
-    // Send break command
-    if let Some(ref mut port) = manager.port {
-        let cmd = iec62056::build_break_command();
-        emit_log("tx", "B0 (Break)");
+    let mut verifications = Vec::with_capacity(writes.len());
+    for (code, value) in writes {
+        verifications.push(read_back(worker, code, value, window).await);
+    }
 
-        let _ = port.write_all(&cmd);
-        let _ = port.flush();
+    let verified = verifications.iter().all(|v| v.verified);
+    if verified {
+        emit_log(window, "success", &format!("{} kayıt yazıldı ve doğrulandı", writes.len()));
     }
+    Ok(ProgramResult { writes: verifications, verified })
+}
 
-    // Close port and mark disconnected — meter returns to idle after break
-    manager.port = None;
-    manager.connected = false;
-    manager.in_programming_mode = false;
-    emit_log("info", "Programlama oturumu sonlandırıldı, port kapatıldı");
+/// Read `obis_code` back on the open programming session after `written` was
+/// written to it; verified when the reading is trusted and is the written value
+async fn read_back(worker: &MeterWorker, obis_code: &str, written: &str, window: &tauri::Window) -> WriteVerification {
+    let reading = match read_on_session(worker, obis_code, Some(window)).await {
+        Ok(reading) => Some(reading),
+        Err(e) => {
+            emit_log(window, "warn", &format!("{} geri okunamadı: {}", obis_code, e));
+            None
+        }
+    };
+    let verified = reading.as_ref().is_some_and(|r| !r.untrusted && is_written_value(obis_code, written, &r.value));
 
-    Ok(())
+    // The password register is logged and reported masked
+    let written = audit::loggable(obis_code, written).to_string();
+    let read_back = reading.map(|r| audit::loggable(obis_code, &r.value).to_string());
+    if !verified {
+        emit_log(window, "warn", &format!("{} doğrulanamadı: yazılan {}, okunan {}", obis_code, written, read_back.as_deref().unwrap_or("-")));
+    }
+    WriteVerification { obis_code: obis_code.to_string(), written, read_back, verified }
+}
+
+/// Whether the value read back from `obis_code` is the `written` one, compared
+/// parsed (see `ObisValue::echoes`); the clock (0.9.1) may have run on a little
+fn is_written_value(obis_code: &str, written: &str, read_back: &str) -> bool {
+    let (written, read_back) = (ObisValue::parse(written), ObisValue::parse(read_back));
+    let is_clock = obis_code.parse::<ObisCode>().is_ok_and(|code| code.canonical_eq(&ObisCode::short(0, 9, 1)));
+    match (read_back.as_time(), written.as_time()) {
+        (Some(read_time), Some(written_time)) if is_clock => {
+            (read_time - written_time).num_seconds().rem_euclid(SECONDS_PER_DAY) <= CLOCK_READ_BACK_TOLERANCE_S
+        }
+        _ => read_back.echoes(&written),
+    }
 }
 
 /// Read load profile data from meter (Mode 1 - Programming mode)
@@ -1703,190 +1082,34 @@ pub async fn read_load_profile(
     profile_number: u8,
     start_time: Option<String>,
     end_time: Option<String>,
+    operation_id: Option<String>,
     window: tauri::Window,
-) -> Result<LoadProfileResult, String> {
+) -> Result<LoadProfileResult, AppError> {
     log::info!("Reading load profile {} with range: {:?} - {:?} (atomic)", profile_number, start_time, end_time);
-
-    let emit_progress = |step: u32, total: u32, message: &str| {
-        let _ = window.emit("read-progress", ProgressEvent {
-            step,
-            total,
-            message: message.to_string(),
-        });
-    };
-
-    let emit_log = |log_type: &str, message: &str, data: Option<&str>| {
-        let _ = window.emit("comm-log", LogEvent {
-            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
-            log_type: log_type.to_string(),
-            message: message.to_string(),
-            data: data.map(|s| s.to_string()),
-        });
-    };
+    let operation = Operation::start(operation_id)?;
 
     let total_steps = 7;
 
     // Step 1: Get connection parameters from stored state
-    emit_progress(1, total_steps, "Bağlantı parametreleri alınıyor...");
-
-    let (timeout_ms, port_name, meter_address, connection_type, configured_baud) = {
-        let manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.params.is_none() {
-            return Err("Bağlantı parametresi yok. Önce 'Bağlan' butonuna tıklayın.".to_string());
-        }
-        let params = manager.params.as_ref().unwrap();
-        (
-            if params.timeout_ms == 0 { 2000 } else { params.timeout_ms },
-            params.port.clone(),
-            params.meter_address.clone(),
-            params.connection_type.clone(),
-            params.baud_rate,
-        )
-    };
+    emit_progress(&window, 1, total_steps, "Bağlantı parametreleri alınıyor...");
 
     // Step 2: Close any existing connection - we'll do a fresh atomic read
-    {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        if manager.port.is_some() {
-            emit_log("info", "Mevcut bağlantı kapatılıyor...", None);
-            manager.disconnect();
-        }
-    }
+    let (options, worker, _operation) = fresh_worker(&window).await?;
 
-    emit_progress(2, total_steps, "Seri port açılıyor...");
+    emit_progress(&window, 2, total_steps, "Seri port açılıyor...");
 
     // Step 3-4: Open port and handshake with baud rate retry
-    let baud_rates = io::resolve_initial_bauds(&connection_type, configured_baud);
-    let mut port: Option<Box<dyn SerialPort>> = None;
-    let mut ident: Option<iec62056::MeterIdent> = None;
-    let mut initial_baud: u32 = 0;
-
-    for (attempt, &try_baud) in baud_rates.iter().enumerate() {
-        emit_log("info", &format!("Port açılıyor: {} @ {} baud (7E1) [Deneme {}/{}]",
-            port_name, try_baud, attempt + 1, baud_rates.len()), None);
+    worker.identify(options, Some(&window), Some(operation.token())).await?;
 
-        let mut current_port = match iec62056::open_port(&port_name, try_baud, timeout_ms as u64) {
-            Ok(p) => p,
-            Err(e) => {
-                emit_log("warn", &format!("Port açılamadı @ {} baud: {}", try_baud, e), None);
-                continue;
-            }
-        };
-
-        emit_log("success", &format!("Port açıldı @ {} baud", try_baud), None);
-
-        let request = iec62056::build_request_message(meter_address.as_deref());
-        let request_str = iec62056::format_bytes_for_display(&request);
-        emit_log("tx", &request_str, None);
-
-        if let Err(e) = current_port.write_all(&request) {
-            emit_log("warn", &format!("Handshake gönderilemedi: {}", e), None);
-            continue;
-        }
-        let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-        let _ = current_port.flush();
-
-        emit_log("info", "Yanıt bekleniyor...", None);
-        std::thread::sleep(Duration::from_millis(500));
-
-        let mut response_buf = vec![0u8; 256];
-        let mut ident_read = 0;
-        let handshake_start = std::time::Instant::now();
-
-        loop {
-            match current_port.read(&mut response_buf[ident_read..]) {
-                Ok(n) if n > 0 => {
-                    ident_read += n;
-                    let _ = window.emit("comm-activity", serde_json::json!({"type": "rx"}));
-                    if ident_read >= 2 &&
-                       response_buf[ident_read - 2] == control::CR &&
-                       response_buf[ident_read - 1] == control::LF {
-                        break;
-                    }
-                }
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    if ident_read > 0 { break; }
-                }
-                Err(_) => break,
-            }
-            if handshake_start.elapsed() > Duration::from_millis(timeout_ms as u64) {
-                break;
-            }
-        }
-
-        if ident_read > 0 {
-            let response_formatted = iec62056::format_bytes_for_display(&response_buf[..ident_read]);
-            emit_log("rx", &response_formatted, None);
-
-            let response = String::from_utf8_lossy(&response_buf[..ident_read]);
-            if let Some(parsed) = iec62056::parse_identification(&response) {
-                emit_log("success", &format!("Sayaç tanımlandı: {} — {} ({})",
-                    parsed.manufacturer, parsed.edas_id, parsed.model), None);
-                initial_baud = try_baud;
-                ident = Some(parsed);
-                port = Some(current_port);
-                break;
-            } else {
-                emit_log("warn", "Sayaç tanımlama yanıtı ayrıştırılamadı", None);
-            }
-        } else {
-            emit_log("warn", &format!("{} baud'da yanıt alınamadı", try_baud), None);
-        }
-    }
-
-    let mut port = port.ok_or_else(|| {
-        emit_log("error", "Hiçbir baud hızında yanıt alınamadı", None);
-        "Hiçbir baud hızında yanıt alınamadı".to_string()
-    })?;
-    let ident = ident.unwrap();
-
-    emit_progress(3, total_steps, "Programlama moduna geçiliyor...");
+    emit_progress(&window, 3, total_steps, "Programlama moduna geçiliyor...");
 
     // Step 5: Send ACK with Mode 1 (Programming mode)
-    let (target_baud, baud_char) = io::resolve_target_baud(
-        &connection_type, configured_baud, ident.max_baud_rate, ident.baud_char
-    );
-
-    let ack = iec62056::build_ack_message(ProtocolMode::Programming, baud_char);
-    let ack_formatted = iec62056::format_bytes_for_display(&ack);
-    emit_log("tx", &ack_formatted, None);
-
-    port.write_all(&ack).map_err(|e| format!("ACK gönderilemedi: {}", e))?;
-    let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-    let _ = port.flush();
-
-    // Wait and switch baud rate
-    emit_log("info", &format!("Baud hızı değiştiriliyor: {} -> {}", initial_baud, target_baud), None);
-    std::thread::sleep(Duration::from_millis(300));
-
-    if target_baud != initial_baud {
-        port.set_baud_rate(target_baud).map_err(|e| {
-            emit_log("error", &format!("Baud hızı değiştirilemedi: {}", e), None);
-            format!("Baud hızı değiştirilemedi: {}", e)
-        })?;
-        emit_log("success", &format!("Baud hızı {} olarak ayarlandı", target_baud), None);
-    }
-
-    // Wait for meter to be ready (it may send password request)
-    std::thread::sleep(Duration::from_millis(500));
-
-    // Read any response from meter (password request or acknowledgment)
-    let mut prog_buf = vec![0u8; 256];
-    let prog_read = port.read(&mut prog_buf).unwrap_or(0);
-    if prog_read > 0 {
-        let prog_formatted = iec62056::format_bytes_for_display(&prog_buf[..prog_read]);
-        emit_log("rx", &prog_formatted, None);
-
-        // Check if meter is requesting password
-        if prog_read >= 7 && prog_buf[0] == control::SOH && prog_buf[1] == b'P' {
-            emit_log("warn", "Sayaç şifre gerektiriyor - yük profili okumak için önce giriş yapın", None);
-        }
+    let prompt = worker.enter_programming().await?;
+    if matches!(prompt, iec62056::Frame::PasswordPrompt { .. }) {
+        emit_log(&window, "warn", "Sayaç şifre gerektiriyor - yük profili okumak için önce giriş yapın");
     }
 
-    emit_log("success", "Programlama moduna geçildi", None);
-
-    emit_progress(4, total_steps, &format!("P.{:02} yük profili sorgulanıyor...", profile_number));
+    emit_progress(&window, 4, total_steps, &format!("P.{:02} yük profili sorgulanıyor...", profile_number));
 
     // Step 6: Build and send load profile command
     let cmd = iec62056::build_load_profile_command(
@@ -1895,110 +1118,102 @@ pub async fn read_load_profile(
         end_time.as_deref(),
     );
     let cmd_formatted = iec62056::format_bytes_for_display(&cmd);
-    emit_log("tx", &cmd_formatted, None);
+    emit_log(&window, "tx", &cmd_formatted);
+    worker.send(cmd).await?;
 
-    port.write_all(&cmd).map_err(|e| format!("Komut gönderilemedi: {}", e))?;
-    let _ = window.emit("comm-activity", serde_json::json!({"type": "tx"}));
-    let _ = port.flush();
-
-    emit_progress(5, total_steps, "Veri blokları alınıyor...");
-    emit_log("info", "Yük profili verisi bekleniyor (bu işlem uzun sürebilir)...", None);
+    emit_progress(&window, 5, total_steps, "Veri blokları alınıyor...");
+    emit_log(&window, "info", "Yük profili verisi bekleniyor (bu işlem uzun sürebilir)...");
 
     // Step 7: Read response - load profile can be very large
     // Use growable buffer — profile 2 (10 columns) can exceed 1MB
+    let read_config = ReadConfig::load_profile();
     let mut data_buf: Vec<u8> = Vec::with_capacity(1048576); // Start with 1MB capacity
-    let mut chunk_buf = [0u8; 8192]; // Read in 8KB chunks
-    let mut found_etx = false;
     let read_start = std::time::Instant::now();
-    let mut last_read_time = std::time::Instant::now();
-    let mut block_count = 0;
-
-    std::thread::sleep(Duration::from_millis(500));
-
-    loop {
-        match port.read(&mut chunk_buf) {
-            Ok(n) if n > 0 => {
-                let old_len = data_buf.len();
-                data_buf.extend_from_slice(&chunk_buf[..n]);
-                last_read_time = std::time::Instant::now();
-                let _ = window.emit("comm-activity", serde_json::json!({"type": "rx"}));
-
-                // Count data blocks for progress indication
-                let new_blocks = data_buf[old_len..]
-                    .iter()
-                    .filter(|&&b| b == control::CR)
-                    .count();
-                if new_blocks > 0 {
-                    block_count += new_blocks;
-                    if block_count % 50 == 0 {
-                        emit_log("info", &format!("{} satır alındı ({} byte, {:.1}s)...",
-                            block_count, data_buf.len(), read_start.elapsed().as_secs_f32()), None);
-                    }
-                }
-
-                // Check for ETX in newly received bytes
-                for i in (old_len..data_buf.len()).rev() {
-                    if data_buf[i] == control::ETX {
-                        found_etx = true;
-                        break;
-                    }
-                }
-                if found_etx {
-                    emit_log("info", &format!("Veri alımı tamamlandı: {} byte, {} satır, süre: {:.1}s",
-                        data_buf.len(), block_count, read_start.elapsed().as_secs_f32()), None);
-                    break;
+    let block_count = Arc::new(AtomicUsize::new(0));
+
+    // Count data lines for progress indication
+    let on_rx = {
+        let window = window.clone();
+        let block_count = block_count.clone();
+        let mut received = 0;
+        move |chunk: &[u8], _: &[u8]| {
+            received += chunk.len();
+            let new_blocks = chunk.iter().filter(|&&b| b == control::CR).count();
+            if new_blocks > 0 {
+                let lines = block_count.fetch_add(new_blocks, Ordering::Relaxed) + new_blocks;
+                if lines.is_multiple_of(50) {
+                    EventEmitter::new(&window).info(&format!("{} satır alındı ({} byte, {:.1}s)...",
+                        lines, received, read_start.elapsed().as_secs_f32()));
                 }
             }
-            Ok(_) => {
-                std::thread::sleep(Duration::from_millis(100));
-            }
-            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                std::thread::sleep(Duration::from_millis(100));
-            }
-            Err(e) => {
-                emit_log("error", &format!("Okuma hatası: {}", e), None);
-                let break_cmd = iec62056::build_break_command();
-                let _ = port.write_all(&break_cmd);
-                let _ = port.flush();
-                return Err(format!("Okuma hatası: {}", e));
-            }
         }
+    };
 
-        // Sliding idle timeout only — resets with every data arrival
-        // No global timeout: we don't know the data size
-        if last_read_time.elapsed() > Duration::from_millis(15000) {
-            if data_buf.is_empty() {
-                emit_log("error", "Zaman aşımı: Hiç veri alınamadı (15s). Sayaç bu profili desteklemiyor olabilir.", None);
-                let break_cmd = iec62056::build_break_command();
-                let _ = port.write_all(&break_cmd);
-                let _ = port.flush();
-                return Err("15 saniye zaman aşımı. Sayaç bu profili desteklemiyor olabilir.".to_string());
-            } else {
-                emit_log("warn", &format!("Boşta kalma zaman aşımı: {} byte alındı, {} satır, süre: {:.1}s",
-                    data_buf.len(), block_count, read_start.elapsed().as_secs_f32()), None);
+    // Large profiles come as partial blocks; each one is BCC-checked and
+    // acknowledged before the meter sends the next
+    let on_block = {
+        let window = window.clone();
+        move |transfer: &io::BlockTransfer| {
+            EventEmitter::new(&window).progress(5, total_steps,
+                &format!("{} blok alındı ({} byte)", transfer.blocks, transfer.data.len()));
+        }
+    };
+
+    // Sliding idle timeout only — resets with every data arrival
+    // No global timeout: we don't know the data size; cancel_operation stops it
+    let mut untrusted = Vec::new();
+    let mut cancelled = false;
+    let mut pending = Vec::new();
+    let read_outcome = worker.read_blocks(read_config, on_rx, on_block).await
+        .map(|read| {
+            let transfer = read.transfer;
+            if transfer.naks > 0 {
+                emit_log(&window, "warn", &format!("{} blok BCC hatası nedeniyle tekrar istendi (NAK)", transfer.naks));
             }
-            break;
+            if transfer.bad_blocks > 0 {
+                emit_log(&window, "warn", &format!("{} blok tekrar sonrasında da BCC hatalı", transfer.bad_blocks));
+            }
+            data_buf.extend_from_slice(&transfer.data);
+            untrusted = transfer.untrusted;
+            cancelled = transfer.cancelled;
+            pending = read.pending;
+            transfer.complete
+        });
+    let block_count = block_count.load(Ordering::Relaxed);
+
+    match read_outcome {
+        Ok(true) => {
+            emit_log(&window, "info", &format!("Veri alımı tamamlandı: {} byte, {} satır, süre: {:.1}s",
+                data_buf.len(), block_count, read_start.elapsed().as_secs_f32()));
+        }
+        Ok(false) if cancelled && data_buf.is_empty() && pending.is_empty() => {
+            emit_log(&window, "warn", "İşlem iptal edildi: hiç veri alınmadı");
+            worker.close().await?;
+            return Err(AppError::Cancelled);
+        }
+        Ok(false) if data_buf.is_empty() && pending.is_empty() => {
+            emit_log(&window, "error", "Zaman aşımı: Hiç veri alınamadı (15s). Sayaç bu profili desteklemiyor olabilir.");
+            worker.close().await?;
+            return Err(AppError::Timeout { received: 0 });
+        }
+        Ok(false) => {
+            // Keep the part of the block that arrived before the timeout or cancel
+            data_buf.extend_from_slice(&pending);
+            let reason = if cancelled { "İşlem iptal edildi" } else { "Boşta kalma zaman aşımı" };
+            emit_log(&window, "warn", &format!("{}: {} byte alındı, {} satır, süre: {:.1}s",
+                reason, data_buf.len(), block_count, read_start.elapsed().as_secs_f32()));
+        }
+        Err(e) => {
+            // The worker sends the break and closes the port
+            emit_log(&window, "error", &e.to_string());
+            return Err(e);
         }
     }
 
     // Step 8: Send Break command and close port
-    emit_log("info", "Oturum sonlandırılıyor...", None);
-    let break_cmd = iec62056::build_break_command();
-    let _ = port.write_all(&break_cmd);
-    let _ = port.flush();
-    std::thread::sleep(Duration::from_millis(100));
-    drop(port); // Close the port
-    emit_log("info", "Port kapatıldı", None);
-
-    // Mark as not connected since we closed the port
-    {
-        let mut manager = CONNECTION_STATE.lock().map_err(|e| e.to_string())?;
-        manager.connected = false;
-        manager.port = None;
-        manager.in_programming_mode = false;
-    }
+    worker.close().await?;
 
-    emit_progress(6, total_steps, "Yük profili verileri ayrıştırılıyor...");
+    emit_progress(&window, 6, total_steps, "Yük profili verileri ayrıştırılıyor...");
 
     // Convert to string
     let total_read = data_buf.len();
@@ -2009,7 +1224,7 @@ pub async fn read_load_profile(
     } else {
         None
     };
-    emit_log("rx", &data_formatted, truncation_note.as_deref());
+    EventEmitter::new(&window).log("rx", &data_formatted, truncation_note.as_deref());
 
     // Parse load profile entries
     // Format varies by meter:
@@ -2017,7 +1232,11 @@ pub async fn read_load_profile(
     //   Type B (BYL): LPCH:1.8.0*kWh\r\n(yy-mm-dd,hh:mm)(value)\r\n...
     let mut entries: Vec<LoadProfileEntry> = Vec::new();
 
-    for line in raw_data.lines() {
+    let mut line_start = 0;
+    for line in raw_data.split_inclusive('\n') {
+        let line_range = line_start..line_start + line.len();
+        line_start = line_range.end;
+
         // Strip control chars (STX=0x02, ETX=0x03, SOH=0x01, etc.)
         let clean: String = line.chars()
             .filter(|c| !c.is_ascii_control())
@@ -2071,24 +1290,25 @@ pub async fn read_load_profile(
             let mut numeric_values: Vec<f64> = Vec::new();
             let mut status: Option<String> = None;
 
-            for val in &values[1..] {
+            for (i, val) in values.iter().enumerate().skip(1) {
                 // Values can be comma-separated inside parentheses:
                 //   Single: (000000.000) or (123.45*kWh)
                 //   Multi:  (220.61,000.52,003.02,000.028,0.00,50.0)
-                let parts: Vec<&str> = val.split(',').collect();
-                for part in &parts {
-                    let part = part.trim();
-                    if part.is_empty() { continue; }
-                    // Strip optional unit suffix: "123.45*kWh" -> "123.45"
-                    let num_str = if let Some(star_pos) = part.find('*') {
-                        &part[..star_pos]
-                    } else {
-                        part
-                    };
-                    if let Ok(num) = num_str.parse::<f64>() {
-                        numeric_values.push(num);
-                    } else if part.len() <= 16 && !part.is_empty() && part.chars().all(|c| c.is_ascii_hexdigit()) {
-                        status = Some(part.to_string());
+                // A trailing status word follows at least one value
+                let parsed = if i >= 2 && i == values.len() - 1 {
+                    ObisValue::parse_status(val)
+                } else {
+                    ObisValue::parse(val)
+                };
+                let parts = match parsed {
+                    ObisValue::Multi { values } => values,
+                    single => vec![single],
+                };
+                for part in parts {
+                    match part {
+                        ObisValue::Number { value, .. } => numeric_values.push(value),
+                        ObisValue::Hex { value } => status = Some(value),
+                        _ => {}
                     }
                 }
             }
@@ -2098,29 +1318,31 @@ pub async fn read_load_profile(
                     timestamp,
                     values: numeric_values,
                     status,
+                    untrusted: iec62056::overlaps_any(&line_range, &untrusted),
                 });
             }
         }
     }
 
-    emit_progress(7, total_steps, "Tamamlandı!");
+    emit_progress(&window, 7, total_steps, "Tamamlandı!");
 
     if entries.is_empty() && total_read > 0 {
-        emit_log("warn", &format!("Uyarı: {} byte veri alındı ama hiç kayıt ayrıştırılamadı. Veri formatı beklenenden farklı olabilir.", total_read), None);
+        emit_log(&window, "warn", &format!("Uyarı: {} byte veri alındı ama hiç kayıt ayrıştırılamadı. Veri formatı beklenenden farklı olabilir.", total_read));
         // Log first few lines for debugging
         let preview_lines: Vec<&str> = raw_data.lines().take(10).collect();
         for (i, line) in preview_lines.iter().enumerate() {
-            emit_log("info", &format!("Satır {}: {}", i+1, line), None);
+            emit_log(&window, "info", &format!("Satır {}: {}", i+1, line));
         }
     } else if entries.is_empty() {
-        emit_log("warn", "Hiç kayıt bulunamadı. Sayaç bu profil için veri döndürmedi.", None);
+        emit_log(&window, "warn", "Hiç kayıt bulunamadı. Sayaç bu profil için veri döndürmedi.");
     } else {
-        emit_log("success", &format!("Yük profili okundu: {} kayıt", entries.len()), None);
+        emit_log(&window, "success", &format!("Yük profili okundu: {} kayıt", entries.len()));
     }
 
     Ok(LoadProfileResult {
         profile_number,
         entries,
         raw_data,
+        cancelled,
     })
 }
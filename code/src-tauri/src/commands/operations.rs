@@ -0,0 +1,106 @@
+//! Cancellable long-running operations
+//!
+//! Long commands (full, short and load profile reads, batch OBIS reads) run
+//! under an operation ID chosen by the frontend. `cancel_operation` sets the
+//! operation's `CancelToken`; the read loop checks it between reads, so the
+//! command stops, sends the break and returns the data received so far.
+
+use crate::error::AppError;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Operations currently running, by ID
+static OPERATIONS: Lazy<Mutex<HashMap<String, CancelToken>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Shared flag telling an operation to stop
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// A running operation, registered until dropped
+pub struct Operation {
+    id: Option<String>,
+    token: CancelToken,
+}
+
+impl Operation {
+    /// Register operation `id`; without an ID the operation cannot be cancelled
+    pub fn start(id: Option<String>) -> Result<Self, AppError> {
+        let token = CancelToken::new();
+        if let Some(id) = &id {
+            let mut operations = OPERATIONS.lock()?;
+            if operations.contains_key(id) {
+                return Err(AppError::invalid_value("reason_operation_id_in_use", id));
+            }
+            operations.insert(id.clone(), token.clone());
+        }
+        Ok(Self { id, token })
+    }
+
+    pub fn token(&self) -> CancelToken {
+        self.token.clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            if let Ok(mut operations) = OPERATIONS.lock() {
+                operations.remove(id);
+            }
+        }
+    }
+}
+
+/// Cancel a running operation; `false` if no operation has that ID
+/// (it may have finished already)
+#[tauri::command]
+pub fn cancel_operation(operation_id: String) -> Result<bool, AppError> {
+    let operations = OPERATIONS.lock()?;
+    match operations.get(&operation_id) {
+        Some(token) => {
+            log::info!("Cancelling operation {}", operation_id);
+            token.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_operation() {
+        let operation = Operation::start(Some("test-op".to_string())).unwrap();
+        assert!(Operation::start(Some("test-op".to_string())).is_err());
+        assert!(!operation.is_cancelled());
+
+        assert!(cancel_operation("test-op".to_string()).unwrap());
+        assert!(operation.is_cancelled());
+        assert!(operation.token().is_cancelled());
+
+        drop(operation);
+        assert!(!cancel_operation("test-op".to_string()).unwrap());
+    }
+}
@@ -0,0 +1,203 @@
+//! Typed content of the MASS packets 5, 7, 8 and 9
+//!
+//! `read_packet` selects one packet with the option select mode character
+//! and gets only its OBIS lines, which is much quicker than a full readout
+//! over a slow optical link. The functions here turn those lines into the
+//! power-quality and historical structures the pages display; warnings and
+//! outages are parsed by [`crate::mass`], the same as in a full readout.
+
+use super::types::{BillingPeriodValues, Harmonic, HistoricalData, PacketData, PhaseQuality, TechQualityData};
+use crate::error::AppError;
+use crate::mass::{format_time, number, split_period, OutageRecords, WarningRecords};
+use crate::serial::iec62056::{ObisDataItem, ProtocolMode};
+use crate::serial::{ObisCode, ObisGroup};
+use std::collections::BTreeMap;
+
+/// Highest harmonic order in the E group; 124 and up are THD and similar totals
+const MAX_HARMONIC: u8 = 120;
+
+/// E group of total harmonic distortion
+const THD: u8 = 124;
+
+/// Protocol mode of a packet that `read_packet` can read
+pub fn packet_mode(mode: u8) -> Result<ProtocolMode, AppError> {
+    match mode {
+        5 => Ok(ProtocolMode::TechQuality),
+        7 => Ok(ProtocolMode::Historical),
+        8 => Ok(ProtocolMode::Warnings),
+        9 => Ok(ProtocolMode::Outages),
+        _ => Err(AppError::invalid_value("reason_unsupported_packet", mode)),
+    }
+}
+
+/// Typed content of the packet read in `mode`
+pub fn parse_packet(mode: ProtocolMode, items: &[ObisDataItem]) -> Result<PacketData, AppError> {
+    match mode {
+        ProtocolMode::TechQuality => Ok(PacketData::TechQuality(Box::new(parse_tech_quality(items)))),
+        ProtocolMode::Historical => Ok(PacketData::Historical(parse_historical(items))),
+        ProtocolMode::Warnings => Ok(PacketData::Warnings(WarningRecords::parse(items))),
+        ProtocolMode::Outages => Ok(PacketData::Outages(OutageRecords::parse(items))),
+        _ => Err(AppError::invalid_value("reason_not_a_packet", mode.as_char())),
+    }
+}
+
+/// Per-phase and total power-quality values (Packet 5)
+///
+/// Codes follow IEC 62056-61: C 21-33 for L1, 41-53 for L2, 61-73 for L3,
+/// E 1-120 for harmonic orders and 124 for THD, `81.7.E` for phase angles.
+pub fn parse_tech_quality(items: &[ObisDataItem]) -> TechQualityData {
+    TechQualityData {
+        l1: phase_quality(items, 20, 0, 40),
+        l2: phase_quality(items, 40, 10, 51),
+        l3: phase_quality(items, 60, 20, 62),
+        neutral_current: number(items, "91.7.0"),
+        frequency: number(items, "14.7.0"),
+        power_factor: number(items, "13.7.0"),
+        active_power: signed(number(items, "1.7.0"), number(items, "2.7.0")),
+        reactive_power: signed(number(items, "3.7.0"), number(items, "4.7.0")),
+        apparent_power: number(items, "9.7.0").or_else(|| number(items, "10.7.0")),
+    }
+}
+
+/// Values of the phase whose codes start at C = `base` + 1, with the E groups
+/// of its voltage and current angles in `81.7.E`
+fn phase_quality(items: &[ObisDataItem], base: u8, voltage_angle: u8, current_angle: u8) -> PhaseQuality {
+    let quantity = |offset: u8, e: u8| number(items, &format!("{}.7.{}", base + offset, e));
+    PhaseQuality {
+        voltage: quantity(12, 0),
+        current: quantity(11, 0),
+        power_factor: quantity(13, 0),
+        active_power: signed(quantity(1, 0), quantity(2, 0)),
+        reactive_power: signed(quantity(3, 0), quantity(4, 0)),
+        apparent_power: quantity(9, 0).or_else(|| quantity(10, 0)),
+        voltage_angle: number(items, &format!("81.7.{}", voltage_angle)),
+        current_angle: number(items, &format!("81.7.{}", current_angle)),
+        voltage_thd: quantity(12, THD),
+        current_thd: quantity(11, THD),
+        voltage_harmonics: harmonics(items, base + 12),
+        current_harmonics: harmonics(items, base + 11),
+    }
+}
+
+/// Harmonic orders of quantity `c` (`C.7.1` to `C.7.120`), lowest first
+fn harmonics(items: &[ObisDataItem], c: u8) -> Vec<Harmonic> {
+    let mut harmonics: Vec<Harmonic> = items.iter()
+        .filter_map(|item| {
+            let code: ObisCode = item.code.parse().ok()?;
+            let code = code.canonical();
+            if code.f.is_some() || code.c != ObisGroup::Value(c) || code.d != ObisGroup::Value(7) {
+                return None;
+            }
+            match code.e {
+                Some(ObisGroup::Value(order)) if (1..=MAX_HARMONIC).contains(&order) => {
+                    Some(Harmonic { order, value: item.parsed.as_f64()? })
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    harmonics.sort_by_key(|h| h.order);
+    harmonics
+}
+
+/// Import minus export, when the meter provides either
+fn signed(import: Option<f64>, export: Option<f64>) -> Option<f64> {
+    match (import, export) {
+        (None, None) => None,
+        (import, export) => Some(import.unwrap_or(0.0) - export.unwrap_or(0.0)),
+    }
+}
+
+/// Billing period values (`1.8.x*N`, `2.8.0*N`, `1.6.0*N`), most recent period first
+pub fn parse_historical(items: &[ObisDataItem]) -> HistoricalData {
+    let mut periods: BTreeMap<u8, BillingPeriodValues> = BTreeMap::new();
+    for item in items {
+        let Some((code, period)) = split_period(item) else { continue };
+        let values = periods.entry(period).or_insert_with(|| BillingPeriodValues { period, ..Default::default() });
+        let number = item.parsed.as_f64().unwrap_or(0.0);
+        match code.to_string().as_str() {
+            "1.8.0" => values.active_energy_import_total = number,
+            "1.8.1" => values.active_energy_import_t1 = number,
+            "1.8.2" => values.active_energy_import_t2 = number,
+            "1.8.3" => values.active_energy_import_t3 = number,
+            "1.8.4" => values.active_energy_import_t4 = number,
+            "2.8.0" => values.active_energy_export_total = Some(number),
+            "1.6.0" => {
+                values.max_demand_import = number;
+                values.max_demand_import_timestamp = format_time(&item.parsed);
+            }
+            _ => {}
+        }
+    }
+    HistoricalData { periods: periods.into_values().collect() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::iec62056::parse_data_block;
+
+    #[test]
+    fn test_parse_historical() {
+        let items = parse_data_block(
+            "0.0.0(12345678)\r\n\
+             1.8.0*2(001120.904*kWh)\r\n\
+             1.8.0*1(001180.230*kWh)\r\n\
+             1.8.1*1(000700.000*kWh)\r\n\
+             2.8.0*1(000012.500*kWh)\r\n\
+             1.6.0*1(000.850*kW)(24-05-14,18:30)\r\n",
+        );
+        let historical = parse_historical(&items);
+
+        let periods: Vec<u8> = historical.periods.iter().map(|p| p.period).collect();
+        assert_eq!(periods, vec![1, 2]);
+        assert_eq!(historical.periods[0].active_energy_import_total, 1180.23);
+        assert_eq!(historical.periods[0].active_energy_import_t1, 700.0);
+        assert_eq!(historical.periods[0].active_energy_export_total, Some(12.5));
+        assert_eq!(historical.periods[1].active_energy_export_total, None);
+        assert_eq!(historical.periods[0].max_demand_import, 0.85);
+        assert_eq!(historical.periods[0].max_demand_import_timestamp, "2024-05-14 18:30");
+        assert_eq!(historical.periods[1].active_energy_import_total, 1120.904);
+    }
+
+    #[test]
+    fn test_parse_tech_quality() {
+        let items = parse_data_block(
+            "32.7.0(230.1*V)\r\n\
+             52.7.0(229.8*V)\r\n\
+             31.7.0(004.20*A)\r\n\
+             14.7.0(50.0*Hz)\r\n\
+             21.7.0(0.950*kW)\r\n\
+             22.7.0(0.000*kW)\r\n\
+             81.7.10(120.0*deg)\r\n\
+             81.7.40(12.5*deg)\r\n\
+             32.7.124(2.1*%)\r\n\
+             32.7.5(1.8*%)\r\n\
+             32.7.3(0.9*%)\r\n\
+             24.7.0(0.120*kvar)\r\n",
+        );
+        let quality = parse_tech_quality(&items);
+
+        assert_eq!(quality.l1.voltage, Some(230.1));
+        assert_eq!(quality.l1.current, Some(4.2));
+        assert_eq!(quality.l1.active_power, Some(0.95));
+        assert_eq!(quality.l1.reactive_power, Some(-0.12));
+        assert_eq!(quality.l1.current_angle, Some(12.5));
+        assert_eq!(quality.l1.voltage_thd, Some(2.1));
+        let orders: Vec<u8> = quality.l1.voltage_harmonics.iter().map(|h| h.order).collect();
+        assert_eq!(orders, vec![3, 5]);
+        assert_eq!(quality.l2.voltage, Some(229.8));
+        assert_eq!(quality.l2.voltage_angle, Some(120.0));
+        assert_eq!(quality.l2.current, None);
+        assert_eq!(quality.l3.active_power, None);
+        assert_eq!(quality.frequency, Some(50.0));
+        assert_eq!(quality.neutral_current, None);
+    }
+
+    #[test]
+    fn test_packet_mode() {
+        assert_eq!(packet_mode(5).unwrap(), ProtocolMode::TechQuality);
+        assert_eq!(packet_mode(9).unwrap(), ProtocolMode::Outages);
+        assert!(matches!(packet_mode(6), Err(AppError::InvalidInput { .. })));
+    }
+}
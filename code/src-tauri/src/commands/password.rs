@@ -0,0 +1,142 @@
+//! Change of the meter passwords
+//!
+//! The new password of a level is written to its register (P1: 96.96) in one
+//! programming session and proven by logging in with it in a new one. Every refused login counts
+//! toward the meter's lockout (3 wrong passwords lock it for 6 hours), so the
+//! old password is never tried again on the operator's behalf.
+
+use super::events::emit_log;
+use super::session::SessionOptions;
+use super::types::PasswordChangeResult;
+use super::worker::MeterWorker;
+use super::{finish_session, login, write_on_session};
+use crate::error::AppError;
+use tauri::Window;
+
+/// Register each password level is written to (W2); the registers of the
+/// other levels are not documented
+pub const REGISTERS: &[(u8, &str)] = &[(1, "96.96")];
+
+/// Register the password of `level` is written to
+pub fn register(level: u8) -> Result<&'static str, AppError> {
+    REGISTERS.iter()
+        .find(|(l, _)| *l == level)
+        .map(|(_, register)| *register)
+        .ok_or_else(|| AppError::invalid_value("reason_password_level_unsupported", format!("P{}", level)))
+}
+
+/// Log in on `worker` with the `old_password` of `level`, write `new_password`
+/// to the level's register, end the session and log in again with the new password; the sessions are closed
+/// best-effort, so a failed close never hides the outcome
+///
+/// A NAKed write changed nothing and the old password stays. A write that
+/// was not acknowledged may still have been applied, so it is proven with the
+/// new login like an ACKed one. When that login is refused the result says so
+/// and the operator decides what to try next.
+pub async fn change(
+    worker: &MeterWorker,
+    options: SessionOptions,
+    level: u8,
+    old_password: &str,
+    new_password: &str,
+    window: Option<&Window>,
+) -> Result<PasswordChangeResult, AppError> {
+    let register = register(level)?;
+
+    // Step 1: Log in with the old password and write the new one
+    if !login(worker, options.clone(), level, old_password, window).await? {
+        return Err(AppError::PasswordRejected);
+    }
+    let write = write_on_session(worker, register, new_password, None, window).await;
+    finish_session(worker, window).await;
+
+    let mut result = PasswordChangeResult {
+        level,
+        written: write.is_ok(),
+        new_password_accepted: false,
+        old_password_kept: false,
+    };
+    if let Err(AppError::Nak { .. }) = write {
+        emit_log(window, "error", "Sayaç yeni şifreyi reddetti, mevcut şifre geçerli");
+        result.old_password_kept = true;
+        return Ok(result);
+    }
+
+    // Step 2: Prove the change with a new login
+    emit_log(window, "info", "Yeni şifre ile giriş deneniyor...");
+    result.new_password_accepted = login(worker, options, level, new_password, window).await?;
+    finish_session(worker, window).await;
+    if result.new_password_accepted {
+        emit_log(window, "success", &format!("P{} şifresi değiştirildi ve doğrulandı", level));
+    } else {
+        let message = format!("P{} için yeni şifre reddedildi, mevcut şifre kilitlenme riski nedeniyle denenmedi", level);
+        emit_log(window, "error", &message);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::{test_support, MeterConfig};
+
+    fn start_meter(config: MeterConfig) -> (SessionOptions, MeterWorker) {
+        let options = test_support::start_meter(config);
+        let worker = MeterWorker::spawn(&options.link.name()).unwrap();
+        (options, worker)
+    }
+
+    async fn logs_in(worker: &MeterWorker, options: &SessionOptions, password: &str) -> bool {
+        let accepted = login(worker, options.clone(), 1, password, None).await.unwrap();
+        worker.close().await.unwrap();
+        accepted
+    }
+
+    #[tokio::test]
+    async fn test_change_acknowledged() {
+        let (options, worker) = start_meter(MeterConfig::default());
+        let result = change(&worker, options.clone(), 1, "12345678", "87654321", None).await.unwrap();
+        assert!(result.written);
+        assert!(result.new_password_accepted);
+        assert!(!result.old_password_kept);
+        assert!(!logs_in(&worker, &options, "12345678").await);
+    }
+
+    #[tokio::test]
+    async fn test_change_refused() {
+        let config = MeterConfig { read_only: vec!["96.96".to_string()], ..Default::default() };
+        let (options, worker) = start_meter(config);
+        let result = change(&worker, options.clone(), 1, "12345678", "87654321", None).await.unwrap();
+        assert!(!result.written);
+        assert!(!result.new_password_accepted);
+        assert!(result.old_password_kept);
+        assert!(logs_in(&worker, &options, "12345678").await);
+    }
+
+    #[tokio::test]
+    async fn test_change_without_reply() {
+        // The meter applies the write but never acknowledges it
+        let config = MeterConfig { unacknowledged_writes: vec!["96.96".to_string()], ..Default::default() };
+        let (options, worker) = start_meter(config);
+        let result = change(&worker, options.clone(), 1, "12345678", "87654321", None).await.unwrap();
+        assert!(!result.written);
+        assert!(result.new_password_accepted);
+        assert!(!result.old_password_kept);
+    }
+
+    #[tokio::test]
+    async fn test_change_undocumented_level() {
+        let (options, worker) = start_meter(MeterConfig::default());
+        let result = change(&worker, options.clone(), 2, "12345678", "87654321", None).await;
+        assert_eq!(result.unwrap_err(), AppError::invalid_value("reason_password_level_unsupported", "P2"));
+        assert!(logs_in(&worker, &options, "12345678").await);
+    }
+
+    #[tokio::test]
+    async fn test_change_wrong_old_password() {
+        let (options, worker) = start_meter(MeterConfig::default());
+        let result = change(&worker, options.clone(), 1, "00000000", "87654321", None).await;
+        assert_eq!(result.unwrap_err(), AppError::PasswordRejected);
+        assert!(logs_in(&worker, &options, "12345678").await);
+    }
+}
@@ -0,0 +1,525 @@
+//! Meter session state machine
+//!
+//! One IEC 62056-21 conversation with a meter: open the link with baud rate
+//! retry, request message and identification, option select with baud switch
+//! into a readout/packet or programming mode, commands, and the closing break.
+//! Every command composes a `MeterSession` instead of repeating these steps.
+
+use super::io::{self, BlockTransfer, ReadConfig};
+use super::operations::CancelToken;
+use super::events::emit_log;
+use super::CONNECTION_STATE;
+use crate::error::AppError;
+use crate::serial::iec62056::{self, Frame, FrameDecoder, MeterIdent, ProtocolMode};
+use crate::serial::{Link, MeterTransport};
+use crate::{ConnectionParams, MeterIdentity};
+use std::time::Duration;
+use tauri::{Emitter, Window};
+
+/// Wait after the request message before reading the identification
+const IDENT_DELAY_MS: u64 = 500;
+/// Wait after option select before switching the baud rate
+const BAUD_SWITCH_DELAY_MS: u64 = 300;
+/// Wait after entering programming mode for the P0 prompt
+const PROGRAMMING_DELAY_MS: u64 = 500;
+/// Wait after a programming command before reading the reply
+const COMMAND_DELAY_MS: u64 = 200;
+
+/// Where a session is in the protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// Link not opened yet
+    Idle,
+    /// Identification received, option select pending
+    Identified,
+    /// The meter is sending a readout or a MASS packet
+    Readout,
+    /// Programming mode: P1/R2/W2 commands may be sent
+    Programming,
+    /// Break sent and link closed
+    Closed,
+}
+
+impl SessionState {
+    /// Whether the link is open
+    pub fn is_open(self) -> bool {
+        matches!(self, Self::Identified | Self::Readout | Self::Programming)
+    }
+}
+
+/// Connection settings of a session
+#[derive(Debug, Clone)]
+pub struct SessionOptions {
+    pub link: Link,
+    pub meter_address: Option<String>,
+    pub connection_type: String,
+    /// Configured baud rate, 0 for auto
+    pub baud_rate: u32,
+    pub timeout_ms: u64,
+    pub bcc_retries: u32,
+    /// Configured IEC 62056-21 mode ("A"-"D"), auto-detected if unset
+    pub protocol_mode: Option<String>,
+}
+
+impl SessionOptions {
+    pub fn from_params(params: &ConnectionParams) -> Result<Self, AppError> {
+        Ok(Self {
+            link: Link::from_params(params)?,
+            meter_address: params.meter_address.clone(),
+            connection_type: params.connection_type.clone(),
+            baud_rate: params.baud_rate,
+            timeout_ms: if params.timeout_ms == 0 { 2000 } else { params.timeout_ms as u64 },
+            bcc_retries: params.bcc_retries.unwrap_or(iec62056::DEFAULT_BCC_RETRIES),
+            protocol_mode: params.protocol_mode.clone(),
+        })
+    }
+
+    /// Options of the connection configured by the last `connect`
+    pub fn stored() -> Result<Self, AppError> {
+        let manager = CONNECTION_STATE.lock()?;
+        let params = manager.params.as_ref().ok_or(AppError::NotConnected)?;
+        Self::from_params(params)
+    }
+}
+
+/// An IEC 62056-21 session with one meter
+///
+/// Transitions: `identify` (Idle → Identified), then `enter_readout`
+/// (→ Readout) or `enter_programming` (→ Programming), and `close`
+/// (→ Closed). Dropping an open session sends the break and closes the link.
+pub struct MeterSession {
+    options: SessionOptions,
+    window: Option<Window>,
+    port: Option<Box<dyn MeterTransport>>,
+    state: SessionState,
+    ident: Option<MeterIdent>,
+    /// Baud rate the identification was received at
+    handshake_baud: u32,
+    /// Baud rate of the link after option select
+    baud_rate: u32,
+    /// Holds bytes received but not yet used (e.g. a readout that arrived
+    /// right behind the identification in Modes A and D)
+    decoder: FrameDecoder,
+    /// Cancellation of the operation the session runs for
+    cancel: Option<CancelToken>,
+}
+
+impl MeterSession {
+    /// New session; log and activity events go to `window`, or to the log if `None`
+    pub fn new(options: SessionOptions, window: Option<&Window>) -> Self {
+        Self {
+            options,
+            window: window.cloned(),
+            port: None,
+            state: SessionState::Idle,
+            ident: None,
+            handshake_baud: 0,
+            baud_rate: 0,
+            decoder: FrameDecoder::new(),
+            cancel: None,
+        }
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    pub fn options(&self) -> &SessionOptions {
+        &self.options
+    }
+
+    /// Identification of the meter, once identified
+    pub fn ident(&self) -> Option<&MeterIdent> {
+        self.ident.as_ref()
+    }
+
+    /// Current baud rate of the link
+    pub fn baud_rate(&self) -> u32 {
+        self.baud_rate
+    }
+
+    /// Decoder with the bytes received so far, for logging and partial data
+    pub fn decoder(&mut self) -> &mut FrameDecoder {
+        &mut self.decoder
+    }
+
+    /// Send later events of a kept-open session to the window of the current command
+    pub fn set_window(&mut self, window: Option<&Window>) {
+        self.window = window.cloned();
+    }
+
+    /// Stop reads and handshake steps once `cancel` is set; they return
+    /// `AppError::Cancelled`, except `read_blocks`, which returns the partial transfer
+    pub fn set_cancel(&mut self, cancel: Option<CancelToken>) {
+        self.cancel = cancel;
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.is_cancelled())
+    }
+
+    /// Emit a communication log event
+    pub fn log(&self, log_type: &str, message: &str) {
+        emit_log(self.window.as_ref(), log_type, message);
+    }
+
+    /// Open the link and read the identification, trying each initial baud rate
+    ///
+    /// An identification that cannot be parsed moves on to the next baud rate;
+    /// if none succeeds, the last parse error (or `NoIdentification`) is returned.
+    pub fn identify(&mut self) -> Result<MeterIdent, AppError> {
+        self.expect_state(SessionState::Idle, "identify")?;
+
+        let port_name = self.options.link.name();
+        let baud_rates = io::resolve_initial_bauds(&self.options.connection_type, self.options.baud_rate);
+        let ident_config = ReadConfig::response(self.options.timeout_ms).with_cancel(self.cancel.clone());
+        let mut parse_error = None;
+
+        for (attempt, &try_baud) in baud_rates.iter().enumerate() {
+            self.check_cancelled()?;
+            self.log("info", &format!("Port açılıyor: {} @ {} baud (7E1) [Deneme {}/{}]",
+                port_name, try_baud, attempt + 1, baud_rates.len()));
+
+            let mut port = match self.options.link.open(try_baud, self.options.timeout_ms) {
+                Ok(p) => p,
+                Err(e) => {
+                    self.log("warn", &format!("Port açılamadı @ {} baud: {}", try_baud, e));
+                    continue;
+                }
+            };
+
+            self.log("success", &format!("Port açıldı @ {} baud", try_baud));
+
+            let request = iec62056::build_request_message(self.options.meter_address.as_deref());
+            self.log("tx", &iec62056::format_bytes_for_display(&request));
+            if let Err(e) = write_message(&mut port, self.window.as_ref(), &request) {
+                self.log("warn", &format!("Handshake gönderilemedi: {}", e));
+                continue;
+            }
+
+            self.log("info", "Yanıt bekleniyor...");
+            std::thread::sleep(Duration::from_millis(IDENT_DELAY_MS));
+
+            let mut decoder = FrameDecoder::new();
+            let Some(response) = io::read_identification_with(&mut port, &mut decoder, self.window.as_ref(), &ident_config) else {
+                self.log("warn", &format!("{} baud'da yanıt alınamadı", try_baud));
+                continue;
+            };
+            self.log("rx", &iec62056::format_bytes_for_display(response.as_bytes()));
+
+            match iec62056::parse_identification(&response) {
+                Ok(mut ident) => {
+                    ident.set_mode(io::resolve_protocol_mode(self.options.protocol_mode.as_deref(), &ident));
+                    self.log("success", &format!("Sayaç tanımlandı: {} — {} ({}) [Mod {}]",
+                        ident.manufacturer, ident.edas_id, ident.model, ident.mode.as_char()));
+                    self.port = Some(port);
+                    self.decoder = decoder;
+                    self.handshake_baud = try_baud;
+                    self.baud_rate = try_baud;
+                    self.ident = Some(ident.clone());
+                    self.state = SessionState::Identified;
+                    return Ok(ident);
+                }
+                Err(e) => {
+                    self.log("warn", &format!("Sayaç tanımlama yanıtı ayrıştırılamadı: {}", e));
+                    parse_error = Some(AppError::from(e));
+                }
+            }
+        }
+
+        self.check_cancelled()?;
+        self.log("error", "Hiçbir baud hızında yanıt alınamadı");
+        Err(parse_error.unwrap_or(AppError::NoIdentification { port: port_name }))
+    }
+
+    /// Select the readout (Mode 0) or a MASS packet (5-9); the meter starts sending
+    ///
+    /// Modes A, B and D have no option select: only the readout is available
+    /// and the meter starts it on its own after the identification.
+    pub fn enter_readout(&mut self, mode: ProtocolMode) -> Result<(), AppError> {
+        let ident = self.require_option_select(mode)?;
+
+        if ident.mode.has_option_select() {
+            self.select_option(mode, &ident)?;
+        } else {
+            self.log("info", &format!("Mod {}: seçenek seçimi yok, sayaç okumayı kendisi başlatıyor",
+                ident.mode.as_char()));
+            let port = self.port.as_mut().ok_or(AppError::NotConnected)?;
+            let readout_baud = io::enter_readout_without_ack(port, ident.mode, &ident, self.handshake_baud)
+                .inspect_err(|e| emit_log(self.window.as_ref(), "error", &e.to_string()))?;
+            if readout_baud != self.handshake_baud {
+                self.log("success", &format!("Baud hızı {} olarak ayarlandı", readout_baud));
+            }
+            self.baud_rate = readout_baud;
+        }
+
+        self.state = SessionState::Readout;
+        Ok(())
+    }
+
+    /// Enter programming mode (Mode 1); returns the meter's first message,
+    /// normally the P0 password prompt. A meter that stays silent is a timeout.
+    pub fn enter_programming(&mut self) -> Result<Frame, AppError> {
+        let ident = self.require_option_select(ProtocolMode::Programming)?;
+        self.select_option(ProtocolMode::Programming, &ident)?;
+
+        // Wait for the meter to be ready
+        std::thread::sleep(Duration::from_millis(PROGRAMMING_DELAY_MS));
+
+        let port = self.port.as_mut().ok_or(AppError::NotConnected)?;
+        let config = ReadConfig::response(self.options.timeout_ms).with_cancel(self.cancel.clone());
+        let Some(prompt) = io::read_reply(port, &mut self.decoder, self.window.as_ref(), &config)? else {
+            self.log("error", "Programlama modunda yanıt alınamadı");
+            return Err(AppError::Timeout { received: self.decoder.pending().len() });
+        };
+        self.log("rx", &iec62056::format_bytes_for_display(self.decoder.last_frame_bytes()));
+
+        self.state = SessionState::Programming;
+        self.log("success", "Programlama moduna geçildi");
+        Ok(prompt)
+    }
+
+    /// Send the password of `level` (P1-P3); `Ok(false)` if the meter rejected it
+    pub fn authenticate(&mut self, level: u8, password: &str) -> Result<bool, AppError> {
+        self.log("tx", &format!("P{} (********)", level));
+        match self.command(&iec62056::build_password_command(level, password))? {
+            Some(Frame::Ack) => {
+                self.log("success", &format!("P{} şifresi kabul edildi - Programlama modu aktif", level));
+                Ok(true)
+            }
+            Some(Frame::Nak) => {
+                self.log("error", "Şifre reddedildi!");
+                Ok(false)
+            }
+            Some(other) => {
+                self.log("error", "Geçersiz yanıt");
+                Err(AppError::unexpected_frame(&other))
+            }
+            None => {
+                self.log("error", "Yanıt alınamadı");
+                Err(AppError::Timeout { received: self.decoder.pending().len() })
+            }
+        }
+    }
+
+    /// Send a programming command (R2, W2, P1) and read the reply; a data
+    /// block with a BCC error is requested again up to `bcc_retries` times
+    pub fn command(&mut self, msg: &[u8]) -> Result<Option<Frame>, AppError> {
+        if self.state != SessionState::Programming {
+            return Err(AppError::NotInProgrammingMode);
+        }
+        self.check_cancelled()?;
+        self.send(msg)?;
+        std::thread::sleep(Duration::from_millis(COMMAND_DELAY_MS));
+
+        let port = self.port.as_mut().ok_or(AppError::NotConnected)?;
+        let config = ReadConfig::response(self.options.timeout_ms)
+            .with_bcc_retries(self.options.bcc_retries)
+            .with_cancel(self.cancel.clone());
+        io::read_checked_reply(port, &mut self.decoder, self.window.as_ref(), &config)
+    }
+
+    /// Write a message to the link
+    pub fn send(&mut self, msg: &[u8]) -> Result<(), AppError> {
+        let port = self.port.as_mut().ok_or(AppError::NotConnected)?;
+        write_message(port, self.window.as_ref(), msg).map_err(AppError::io)
+    }
+
+    /// Receive the (possibly multi-block) data the meter is sending
+    ///
+    /// Waits `config.initial_delay_ms` for the data to start; `config.bcc_retries`
+    /// and `config.cancel` are replaced by the session's settings. See
+    /// `io::read_data_blocks`.
+    pub fn read_blocks(
+        &mut self,
+        config: &ReadConfig,
+        on_rx: impl FnMut(&[u8], &[u8]),
+        on_block: impl FnMut(&BlockTransfer),
+    ) -> Result<BlockTransfer, AppError> {
+        if !matches!(self.state, SessionState::Readout | SessionState::Programming) {
+            return Err(AppError::NotConnected);
+        }
+        let config = config.clone()
+            .with_bcc_retries(self.options.bcc_retries)
+            .with_cancel(self.cancel.clone());
+        std::thread::sleep(Duration::from_millis(config.initial_delay_ms));
+
+        let port = self.port.as_mut().ok_or(AppError::NotConnected)?;
+        io::read_data_blocks(port, &mut self.decoder, self.window.as_ref(), &config, on_rx, on_block)
+    }
+
+    /// Send the break (B0) and close the link
+    pub fn close(&mut self) {
+        if let Some(mut port) = self.port.take() {
+            self.log("info", "Oturum sonlandırılıyor...");
+            let _ = io::send_break_command(&mut port);
+            drop(port);
+            self.log("info", "Port kapatıldı");
+        }
+        self.state = SessionState::Closed;
+    }
+
+    fn check_cancelled(&self) -> Result<(), AppError> {
+        if self.is_cancelled() {
+            self.log("warn", "İşlem iptal edildi");
+            return Err(AppError::Cancelled);
+        }
+        Ok(())
+    }
+
+    fn expect_state(&self, expected: SessionState, step: &str) -> Result<(), AppError> {
+        if self.state == expected {
+            Ok(())
+        } else {
+            Err(AppError::Internal { reason: format!("{}: {:?} != {:?}", step, self.state, expected) })
+        }
+    }
+
+    /// Identification of an identified session, after checking that the
+    /// meter's mode allows `mode`
+    fn require_option_select(&self, mode: ProtocolMode) -> Result<MeterIdent, AppError> {
+        self.expect_state(SessionState::Identified, "option select")?;
+        let ident = self.ident.clone().ok_or(AppError::NotConnected)?;
+        if mode != ProtocolMode::Readout && !ident.mode.has_option_select() {
+            let (operation, description) = operation_name(mode);
+            self.log("error", &format!("Mod {} sayaçlarda {} desteklenmiyor (seçenek seçimi yok)",
+                ident.mode.as_char(), description));
+            return Err(AppError::UnsupportedMode { mode: ident.mode.as_char(), operation: operation.to_string() });
+        }
+        Ok(ident)
+    }
+
+    /// Send the option select ACK and follow the meter to the session baud rate
+    fn select_option(&mut self, mode: ProtocolMode, ident: &MeterIdent) -> Result<(), AppError> {
+        let port = self.port.as_mut().ok_or(AppError::NotConnected)?;
+        let (target_baud, baud_char) = io::resolve_session_baud(
+            port, &self.options.connection_type, self.options.baud_rate, self.handshake_baud, ident
+        );
+
+        let ack = iec62056::build_ack_message(mode, baud_char);
+        emit_log(self.window.as_ref(), "tx", &iec62056::format_bytes_for_display(&ack));
+        write_message(port, self.window.as_ref(), &ack).map_err(AppError::io)?;
+
+        emit_log(self.window.as_ref(), "info", &format!("Baud hızı değiştiriliyor: {} -> {}", self.handshake_baud, target_baud));
+        std::thread::sleep(Duration::from_millis(BAUD_SWITCH_DELAY_MS));
+
+        if target_baud != self.handshake_baud {
+            port.set_baud_rate(target_baud).map_err(|e| {
+                emit_log(self.window.as_ref(), "error", &format!("Baud hızı değiştirilemedi: {}", e));
+                AppError::BaudSwitch { baud: target_baud, reason: e.to_string() }
+            })?;
+            emit_log(self.window.as_ref(), "success", &format!("Baud hızı {} olarak ayarlandı", target_baud));
+        }
+        self.baud_rate = target_baud;
+        Ok(())
+    }
+}
+
+impl From<&MeterIdent> for MeterIdentity {
+    /// Identity for the frontend; the serial number is read later from the data
+    fn from(ident: &MeterIdent) -> Self {
+        MeterIdentity {
+            manufacturer: ident.manufacturer.clone(),
+            edas_id: ident.edas_id.clone(),
+            model: ident.model.clone(),
+            baud_rate_char: ident.baud_char.to_string(),
+            generation: ident.generation.clone(),
+            serial_number: None,
+            protocol_mode: ident.mode.as_char().to_string(),
+            raw_ident: ident.raw.clone(),
+        }
+    }
+}
+
+impl Drop for MeterSession {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Operation i18n key (for `AppError::UnsupportedMode`) and Turkish description of a mode
+fn operation_name(mode: ProtocolMode) -> (&'static str, &'static str) {
+    match mode {
+        ProtocolMode::Readout => ("operation_readout", "tam okuma"),
+        ProtocolMode::Programming => ("operation_programming", "programlama modu"),
+        ProtocolMode::TechQuality => ("operation_tech_quality", "teknik kalite okuma (Paket 5)"),
+        ProtocolMode::ShortRead => ("operation_short_read", "kısa okuma (Paket 6)"),
+        ProtocolMode::Historical => ("operation_historical", "geçmiş veri okuma (Paket 7)"),
+        ProtocolMode::Warnings => ("operation_warnings", "uyarı okuma (Paket 8)"),
+        ProtocolMode::Outages => ("operation_outages", "kesinti okuma (Paket 9)"),
+    }
+}
+
+/// Write and flush a message, signalling TX activity
+fn write_message<T: MeterTransport + ?Sized>(port: &mut T, window: Option<&Window>, msg: &[u8]) -> std::io::Result<()> {
+    port.write_all(msg)?;
+    port.flush()?;
+    if let Some(w) = window {
+        let _ = w.emit("comm-activity", serde_json::json!({"type": "tx"}));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::test_support::start_meter;
+    use crate::simulator::MeterConfig;
+
+    #[test]
+    fn test_session_readout() {
+        let mut session = MeterSession::new(start_meter(MeterConfig::default()), None);
+        assert!(session.enter_readout(ProtocolMode::Readout).is_err());
+
+        let ident = session.identify().unwrap();
+        assert_eq!(ident.edas_id, "ADM");
+        assert_eq!(session.state(), SessionState::Identified);
+
+        session.enter_readout(ProtocolMode::ShortRead).unwrap();
+        assert_eq!(session.state(), SessionState::Readout);
+        assert_eq!(session.command(&iec62056::build_read_command("0.9.1")), Err(AppError::NotInProgrammingMode));
+
+        let transfer = session.read_blocks(&ReadConfig::short_read(), |_, _| {}, |_: &BlockTransfer| {}).unwrap();
+        assert!(transfer.complete);
+        assert!(!transfer.data.is_empty());
+
+        session.close();
+        assert_eq!(session.state(), SessionState::Closed);
+    }
+
+    #[test]
+    fn test_session_programming() {
+        let mut session = MeterSession::new(start_meter(MeterConfig::default()), None);
+        session.identify().unwrap();
+        assert!(matches!(session.enter_programming(), Ok(Frame::PasswordPrompt { .. })));
+        assert_eq!(session.state(), SessionState::Programming);
+
+        assert!(!session.authenticate(1, "00000000").unwrap());
+        assert!(session.authenticate(1, "12345678").unwrap());
+        assert_eq!(
+            session.command(&iec62056::build_read_command("0.9.1")).unwrap(),
+            Some(Frame::DataBlock { data: b"0.9.1(12:30:45)".to_vec(), bcc_ok: true })
+        );
+    }
+
+    #[test]
+    fn test_session_cancel() {
+        let config = MeterConfig { block_size: 32, ..Default::default() };
+        let cancel = CancelToken::new();
+        let mut session = MeterSession::new(start_meter(config), None);
+        session.set_cancel(Some(cancel.clone()));
+        session.identify().unwrap();
+        session.enter_readout(ProtocolMode::Readout).unwrap();
+
+        // Cancelled after the first block: the rest of the readout is not read
+        let transfer = session.read_blocks(&ReadConfig::full_read(), |_, _| {}, |_: &BlockTransfer| cancel.cancel()).unwrap();
+        assert!(transfer.cancelled);
+        assert!(!transfer.complete);
+        assert_eq!(transfer.blocks, 1);
+        session.close();
+
+        let mut session = MeterSession::new(start_meter(MeterConfig::default()), None);
+        session.set_cancel(Some(cancel));
+        assert_eq!(session.identify().unwrap_err(), AppError::Cancelled);
+    }
+}
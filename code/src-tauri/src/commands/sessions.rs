@@ -3,31 +3,40 @@
 //! Handles saving, loading, listing, and deleting meter session files.
 
 use super::types::SessionData;
+use crate::error::AppError;
+use std::path::Path;
+
+/// File error for `path`
+fn file_error(path: &Path, reason: String) -> AppError {
+    AppError::File { path: path.display().to_string(), reason }
+}
 
 /// Get the sessions folder path (next to executable)
-fn get_sessions_folder() -> Result<std::path::PathBuf, String> {
-    let exe_path = std::env::current_exe().map_err(|e| format!("Failed to get exe path: {}", e))?;
-    let exe_dir = exe_path.parent().ok_or("Failed to get exe directory")?;
+fn get_sessions_folder() -> Result<std::path::PathBuf, AppError> {
+    let exe_path = std::env::current_exe()
+        .map_err(|e| file_error(Path::new(""), format!("Failed to get exe path: {}", e)))?;
+    let exe_dir = exe_path.parent()
+        .ok_or_else(|| file_error(&exe_path, "Failed to get exe directory".to_string()))?;
     let sessions_dir = exe_dir.join("omnicore-meter-sessions");
 
     // Create directory if it doesn't exist
     if !sessions_dir.exists() {
         std::fs::create_dir_all(&sessions_dir)
-            .map_err(|e| format!("Failed to create sessions directory: {}", e))?;
+            .map_err(|e| file_error(&sessions_dir, format!("Failed to create sessions directory: {}", e)))?;
     }
 
     Ok(sessions_dir)
 }
 
 /// Delete existing sessions for a meter (helper function)
-fn delete_meter_sessions(sessions_dir: &std::path::Path, flag: &str, serial_number: &str) -> Result<(), String> {
+fn delete_meter_sessions(sessions_dir: &std::path::Path, flag: &str, serial_number: &str) -> Result<(), AppError> {
     let prefix = format!("{}-{}-",
         flag.replace(|c: char| !c.is_alphanumeric(), "_"),
         serial_number.replace(|c: char| !c.is_alphanumeric(), "_")
     );
 
     let entries = std::fs::read_dir(sessions_dir)
-        .map_err(|e| format!("Failed to read sessions directory: {}", e))?;
+        .map_err(|e| file_error(sessions_dir, format!("Failed to read sessions directory: {}", e)))?;
 
     for entry in entries.flatten() {
         let path = entry.path();
@@ -35,7 +44,7 @@ fn delete_meter_sessions(sessions_dir: &std::path::Path, flag: &str, serial_numb
             if filename.starts_with(&prefix) && filename.ends_with(".json") {
                 log::info!("Deleting existing session: {:?}", path);
                 std::fs::remove_file(&path)
-                    .map_err(|e| format!("Failed to delete session file: {}", e))?;
+                    .map_err(|e| file_error(&path, format!("Failed to delete session file: {}", e)))?;
             }
         }
     }
@@ -53,7 +62,7 @@ pub async fn save_session_file(
     meter_data: serde_json::Value,
     connection_info: serde_json::Value,
     overwrite_existing: bool,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     log::info!("Saving session for {}-{}", flag, serial_number);
 
     let sessions_dir = get_sessions_folder()?;
@@ -86,11 +95,11 @@ pub async fn save_session_file(
 
     // Serialize to JSON
     let json = serde_json::to_string_pretty(&session)
-        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+        .map_err(|e| file_error(&file_path, format!("Failed to serialize session: {}", e)))?;
 
     // Write to file
     std::fs::write(&file_path, json)
-        .map_err(|e| format!("Failed to write session file: {}", e))?;
+        .map_err(|e| file_error(&file_path, format!("Failed to write session file: {}", e)))?;
 
     log::info!("Session saved to: {:?}", file_path);
     Ok(filename)
@@ -98,12 +107,12 @@ pub async fn save_session_file(
 
 /// List saved session files
 #[tauri::command]
-pub async fn list_session_files() -> Result<Vec<serde_json::Value>, String> {
+pub async fn list_session_files() -> Result<Vec<serde_json::Value>, AppError> {
     let sessions_dir = get_sessions_folder()?;
 
     let mut sessions = Vec::new();
     let entries = std::fs::read_dir(&sessions_dir)
-        .map_err(|e| format!("Failed to read sessions directory: {}", e))?;
+        .map_err(|e| file_error(&sessions_dir, format!("Failed to read sessions directory: {}", e)))?;
 
     for entry in entries.flatten() {
         let path = entry.path();
@@ -139,33 +148,33 @@ pub async fn list_session_files() -> Result<Vec<serde_json::Value>, String> {
 
 /// Load a specific session file
 #[tauri::command]
-pub async fn load_session_file(filename: String) -> Result<serde_json::Value, String> {
+pub async fn load_session_file(filename: String) -> Result<serde_json::Value, AppError> {
     let sessions_dir = get_sessions_folder()?;
     let file_path = sessions_dir.join(&filename);
 
     if !file_path.exists() {
-        return Err(format!("Session file not found: {}", filename));
+        return Err(file_error(&file_path, "Session file not found".to_string()));
     }
 
     let content = std::fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read session file: {}", e))?;
+        .map_err(|e| file_error(&file_path, format!("Failed to read session file: {}", e)))?;
 
     serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse session file: {}", e))
+        .map_err(|e| file_error(&file_path, format!("Failed to parse session file: {}", e)))
 }
 
 /// Delete a specific session file
 #[tauri::command]
-pub async fn delete_session_file(filename: String) -> Result<(), String> {
+pub async fn delete_session_file(filename: String) -> Result<(), AppError> {
     let sessions_dir = get_sessions_folder()?;
     let file_path = sessions_dir.join(&filename);
 
     if !file_path.exists() {
-        return Err(format!("Session file not found: {}", filename));
+        return Err(file_error(&file_path, "Session file not found".to_string()));
     }
 
     std::fs::remove_file(&file_path)
-        .map_err(|e| format!("Failed to delete session file: {}", e))?;
+        .map_err(|e| file_error(&file_path, format!("Failed to delete session file: {}", e)))?;
 
     log::info!("Session file deleted: {}", filename);
     Ok(())
@@ -1,10 +1,16 @@
 //! Connection state management
 //!
-//! Manages the global serial connection state for interactive sessions.
+//! Manages the global connection state for interactive sessions. The sessions
+//! themselves live on the per-port I/O workers, so this state is only locked
+//! briefly and never across serial I/O.
 
+use super::session::{SessionOptions, SessionState};
+use super::worker::MeterWorker;
+use crate::error::AppError;
+use crate::serial::Link;
 use crate::{ConnectionParams, MeterIdentity};
 use once_cell::sync::Lazy;
-use serialport::SerialPort;
+use std::collections::HashMap;
 use std::sync::Mutex;
 
 /// Global connection state
@@ -12,39 +18,66 @@ pub static CONNECTION_STATE: Lazy<Mutex<ConnectionManager>> = Lazy::new(|| {
     Mutex::new(ConnectionManager::new())
 });
 
-/// Manages the serial connection and meter state
+/// Manages the configured connection and the I/O workers
 pub struct ConnectionManager {
-    pub port: Option<Box<dyn SerialPort>>,
     pub params: Option<ConnectionParams>,
     pub identity: Option<MeterIdentity>,
-    pub connected: bool,
-    pub in_programming_mode: bool,
-    pub negotiated_baud: u32,
+    /// I/O worker of every port used so far, by port name
+    workers: HashMap<String, MeterWorker>,
 }
 
 impl ConnectionManager {
     pub fn new() -> Self {
         Self {
-            port: None,
             params: None,
             identity: None,
-            connected: false,
-            in_programming_mode: false,
-            negotiated_baud: 300,
+            workers: HashMap::new(),
         }
     }
 
+    /// Worker of `options`' port, started on first use
+    pub fn worker_for(&mut self, options: &SessionOptions) -> Result<MeterWorker, AppError> {
+        let port = options.link.name();
+        if let Some(worker) = self.workers.get(&port) {
+            return Ok(worker.clone());
+        }
+        let worker = MeterWorker::spawn(&port)?;
+        self.workers.insert(port, worker.clone());
+        Ok(worker)
+    }
+
+    /// State of the session on the configured port
+    pub fn session_state(&self) -> SessionState {
+        let worker = self.params.as_ref()
+            .and_then(|params| Link::from_params(params).ok())
+            .and_then(|link| self.workers.get(&link.name()));
+        worker.map_or(SessionState::Idle, |w| w.state())
+    }
+
     pub fn is_connected(&self) -> bool {
-        self.connected && self.port.is_some()
+        self.session_state().is_open()
     }
 
-    pub fn disconnect(&mut self) {
-        self.port = None;
-        // Keep params and identity — they represent the configured connection,
-        // not the active session. Atomic operations need params to reconnect.
-        self.connected = false;
-        self.in_programming_mode = false;
-        self.negotiated_baud = 300;
+    /// Close the session on `options`' port, whose operation the caller holds,
+    /// and retire the idle workers of every other port
+    pub fn disconnect(&mut self, options: &SessionOptions) {
+        // The worker sends the break and closes its port after the requests
+        // already queued. Keep params and identity — they represent the
+        // configured connection, not the active session. Atomic operations
+        // need params to reconnect.
+        let port = options.link.name();
+        if let Some(worker) = self.workers.get(&port) {
+            worker.close_later();
+        }
+
+        // A worker whose command still runs is retired by a later disconnect
+        self.workers.retain(|name, worker| {
+            let keep = *name == port || !worker.is_idle();
+            if !keep {
+                worker.close_later();
+            }
+            keep
+        });
     }
 }
 
@@ -53,8 +86,3 @@ impl Default for ConnectionManager {
         Self::new()
     }
 }
-
-// Make ConnectionManager Send + Sync safe
-// SAFETY: We access ConnectionManager through a Mutex, which provides synchronization
-unsafe impl Send for ConnectionManager {}
-unsafe impl Sync for ConnectionManager {}
@@ -2,8 +2,31 @@
 //!
 //! Contains data structures used in meter communication commands.
 
+use crate::mass::{MassData, OutageRecords, WarningRecords};
+use crate::serial::iec62056::ObisDataItem;
 use serde::{Deserialize, Serialize};
 
+/// Value of a single OBIS register read with R2
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObisReading {
+    /// Value with its unit as the meter sent it, e.g. `000123.456*kWh`
+    pub value: String,
+    /// The reply failed BCC even after retransmission
+    pub untrusted: bool,
+}
+
+impl ObisReading {
+    /// Batch entry for a code that could not be read
+    pub fn error(reason: impl std::fmt::Display) -> Self {
+        Self { value: format!("ERROR: {}", reason), untrusted: false }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.value.starts_with("ERROR:")
+    }
+}
+
 /// Short read result data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -53,6 +76,61 @@ pub struct ShortReadResult {
 
     // Timestamp (epoch ms) captured when 0.9.1 and 0.9.2 were received from meter
     pub time_of_09x_read: Option<u64>,
+
+    // OBIS codes whose values failed BCC even after retransmission
+    pub untrusted_codes: Vec<String>,
+
+    // All OBIS lines of the readout with typed values
+    pub obis_items: Vec<ObisDataItem>,
+
+    // Event logs, DST and tariff tables and demand resets found in the readout
+    #[serde(default)]
+    pub mass: MassData,
+
+    // The read was cancelled; values come from the data received until then
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Full read result (Mode 0): the short read values plus every other MASS
+/// readout register
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullReadResult {
+    // Values shared with the short read, including the parsed OBIS list
+    #[serde(flatten)]
+    pub common: ShortReadResult,
+
+    // Active Energy Export (bidirectional meters)
+    pub active_energy_export_total: Option<f64>,
+    pub active_energy_export_t1: Option<f64>,
+    pub active_energy_export_t2: Option<f64>,
+    pub active_energy_export_t3: Option<f64>,
+    pub active_energy_export_t4: Option<f64>,
+
+    // Reactive Energy (Kombi meters): totals and quadrants Q1 (5.8.0) to Q4 (8.8.0)
+    pub reactive_energy_import_total: Option<f64>,
+    pub reactive_energy_export_total: Option<f64>,
+    pub reactive_energy_inductive_import: Option<f64>,
+    pub reactive_energy_capacitive_export: Option<f64>,
+    pub reactive_energy_inductive_export: Option<f64>,
+    pub reactive_energy_capacitive_import: Option<f64>,
+
+    // Maximum Demand Export
+    pub max_demand_export: Option<f64>,
+    pub max_demand_export_timestamp: Option<String>,
+
+    // Values of the previous billing periods, most recent first
+    pub billing_periods: Vec<BillingPeriodValues>,
+
+    // Event counters
+    pub demand_reset_count: Option<u32>,
+    pub outage_count: Option<u32>,
+    pub voltage_warning_count: Option<u32>,
+    pub current_warning_count: Option<u32>,
+    pub magnetic_warning_count: Option<u32>,
+    pub top_cover_openings: Option<u32>,
+    pub terminal_cover_openings: Option<u32>,
 }
 
 /// Progress event for reading operations
@@ -81,6 +159,9 @@ pub struct LoadProfileEntry {
     pub timestamp: String,
     pub values: Vec<f64>,
     pub status: Option<String>,
+    /// Entry came from a block that failed BCC even after retransmission
+    #[serde(default)]
+    pub untrusted: bool,
 }
 
 /// Load profile read result
@@ -90,6 +171,268 @@ pub struct LoadProfileResult {
     pub profile_number: u8,
     pub entries: Vec<LoadProfileEntry>,
     pub raw_data: String,
+    /// The read was cancelled; entries cover the data received until then
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Register values of one billing period (Packet 7)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BillingPeriodValues {
+    /// Billing period, 1 is the most recent
+    pub period: u8,
+    pub active_energy_import_total: f64,
+    pub active_energy_import_t1: f64,
+    pub active_energy_import_t2: f64,
+    pub active_energy_import_t3: f64,
+    pub active_energy_import_t4: f64,
+    #[serde(default)]
+    pub active_energy_export_total: Option<f64>,
+    pub max_demand_import: f64,
+    pub max_demand_import_timestamp: String,
+}
+
+/// Historical data (Packet 7)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalData {
+    pub periods: Vec<BillingPeriodValues>,
+}
+
+/// Level of one harmonic order, in the unit the meter reports (usually %)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Harmonic {
+    pub order: u8,
+    pub value: f64,
+}
+
+/// Power-quality values of one phase; `None` where the meter does not provide them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseQuality {
+    pub voltage: Option<f64>,
+    pub current: Option<f64>,
+    pub power_factor: Option<f64>,
+    /// Import minus export
+    pub active_power: Option<f64>,
+    /// Import minus export
+    pub reactive_power: Option<f64>,
+    pub apparent_power: Option<f64>,
+    /// Angle of the phase voltage from the L1 voltage, degrees
+    pub voltage_angle: Option<f64>,
+    /// Angle of the phase current from the phase voltage, degrees
+    pub current_angle: Option<f64>,
+    pub voltage_thd: Option<f64>,
+    pub current_thd: Option<f64>,
+    pub voltage_harmonics: Vec<Harmonic>,
+    pub current_harmonics: Vec<Harmonic>,
+}
+
+/// Technical quality (Packet 5)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TechQualityData {
+    pub l1: PhaseQuality,
+    pub l2: PhaseQuality,
+    pub l3: PhaseQuality,
+    pub neutral_current: Option<f64>,
+    pub frequency: Option<f64>,
+    pub power_factor: Option<f64>,
+    /// Totals over all phases, import minus export
+    pub active_power: Option<f64>,
+    pub reactive_power: Option<f64>,
+    pub apparent_power: Option<f64>,
+}
+
+/// Typed content of a MASS packet, tagged with the packet it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "packet", rename_all = "camelCase")]
+pub enum PacketData {
+    TechQuality(Box<TechQualityData>),
+    Historical(HistoricalData),
+    Warnings(WarningRecords),
+    Outages(OutageRecords),
+}
+
+/// Packet read result (Packets 5, 7, 8 and 9)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PacketReadResult {
+    /// Packet number, the option select mode character
+    pub mode: u8,
+    pub serial_number: String,
+    pub data: PacketData,
+    pub raw_data: String,
+    /// OBIS codes whose values failed BCC even after retransmission
+    pub untrusted_codes: Vec<String>,
+    pub obis_items: Vec<ObisDataItem>,
+    /// The read was cancelled; values come from the data received until then
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Read-back check of one register written by a programming command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteVerification {
+    pub obis_code: String,
+    pub written: String,
+    /// Value read back with R2, `None` if the read failed
+    pub read_back: Option<String>,
+    /// The read-back value is the written one, compared parsed
+    pub verified: bool,
+}
+
+/// Result of a programming command that writes and reads back several registers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramResult {
+    pub writes: Vec<WriteVerification>,
+    /// Every register was read back with the written value
+    pub verified: bool,
+}
+
+/// One register write requested from `program_meter`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterWrite {
+    pub obis_code: String,
+    pub value: String,
+}
+
+/// Meter reply to a W2 command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WriteReply {
+    Ack,
+    Nak,
+    /// No reply, an invalid reply or a port error
+    Failed,
+    /// Not sent because an earlier step failed
+    NotSent,
+}
+
+/// What `program_meter` did to one register
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterReport {
+    pub obis_code: String,
+    /// Value read before writing, `None` if it could not be read reliably
+    pub old_value: Option<String>,
+    pub new_value: String,
+    pub reply: WriteReply,
+    /// Value read back after all writes, `None` if not read
+    pub read_back: Option<String>,
+    /// The read-back value is the new one, compared parsed
+    pub verified: bool,
+    /// The old value was written back after a failed step
+    pub restored: bool,
+}
+
+impl RegisterReport {
+    /// A write was sent and may have been applied; a write without a reply may
+    /// have been applied too
+    pub fn may_be_written(&self) -> bool {
+        matches!(self.reply, WriteReply::Ack | WriteReply::Failed)
+    }
+}
+
+/// Result of `program_meter`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramMeterResult {
+    pub registers: Vec<RegisterReport>,
+    /// Every write was acknowledged and verified
+    pub success: bool,
+    /// A step failed and every register that may have been written got its
+    /// old value back
+    pub rolled_back: bool,
+}
+
+impl ProgramMeterResult {
+    /// Result of `program_meter` for `registers`, after a failed step when `failed`
+    pub fn new(registers: Vec<RegisterReport>, failed: bool) -> Self {
+        let rolled_back = failed && registers.iter().filter(|r| r.may_be_written()).all(|r| r.restored);
+        Self { registers, success: !failed, rolled_back }
+    }
+}
+
+/// Relay output state (`96.3.10`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RelayState {
+    /// `1`, energy on
+    Connected,
+    /// `0`, energy off
+    Disconnected,
+}
+
+impl RelayState {
+    /// `None` for anything but a `0` or `1` reading
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().parse::<u8>().ok()? {
+            1 => Some(RelayState::Connected),
+            0 => Some(RelayState::Disconnected),
+            _ => None,
+        }
+    }
+
+    /// Value written to `96.3.10`
+    pub fn value(self) -> &'static str {
+        match self {
+            RelayState::Connected => "1",
+            RelayState::Disconnected => "0",
+        }
+    }
+
+    /// `relay_status` of the readout results
+    pub fn status(self) -> &'static str {
+        match self {
+            RelayState::Connected => "active",
+            RelayState::Disconnected => "passive",
+        }
+    }
+
+    /// Token the caller passes to confirm switching the relay of `serial_number`
+    /// to this state, e.g. `DISCONNECT:123456789`
+    pub fn confirmation(self, serial_number: &str) -> String {
+        match self {
+            RelayState::Connected => format!("CONNECT:{}", serial_number),
+            RelayState::Disconnected => format!("DISCONNECT:{}", serial_number),
+        }
+    }
+}
+
+/// Result of `relay_connect` and `relay_disconnect`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayResult {
+    /// Serial number read from the meter before switching
+    pub serial_number: String,
+    /// State before switching, `None` if it could not be read
+    pub previous_state: Option<RelayState>,
+    pub requested_state: RelayState,
+    /// State read back after switching, `None` if it could not be read
+    pub state: Option<RelayState>,
+    /// The read-back state is the requested one
+    pub confirmed: bool,
+}
+
+/// Result of `change_password`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordChangeResult {
+    /// Password level, e.g. 1 for P1 (96.96)
+    pub level: u8,
+    /// The meter acknowledged the write of the new password
+    pub written: bool,
+    /// A new login with the new password was accepted
+    pub new_password_accepted: bool,
+    /// The meter refused the write and still uses the old password; the old
+    /// password is never tried after a refused new login
+    pub old_password_kept: bool,
 }
 
 /// Session data structure for saving
@@ -104,3 +447,50 @@ pub struct SessionData {
     pub meter_data: serde_json::Value,
     pub connection_info: serde_json::Value,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relay_state() {
+        assert_eq!(RelayState::parse("1"), Some(RelayState::Connected));
+        assert_eq!(RelayState::parse(" 0 "), Some(RelayState::Disconnected));
+        assert_eq!(RelayState::parse("10"), None);
+        assert_eq!(RelayState::parse(""), None);
+        assert_eq!(RelayState::Disconnected.confirmation("123456789"), "DISCONNECT:123456789");
+    }
+
+    fn report(obis_code: &str, reply: WriteReply, restored: bool) -> RegisterReport {
+        RegisterReport {
+            obis_code: obis_code.to_string(),
+            old_value: Some("0".to_string()),
+            new_value: "1".to_string(),
+            reply,
+            read_back: None,
+            verified: false,
+            restored,
+        }
+    }
+
+    #[test]
+    fn test_program_meter_rolled_back() {
+        let restored = vec![
+            report("96.50", WriteReply::Ack, true),
+            report("96.51", WriteReply::Failed, true),
+            report("96.52", WriteReply::NotSent, false),
+        ];
+        let result = ProgramMeterResult::new(restored.clone(), true);
+        assert!(!result.success);
+        assert!(result.rolled_back);
+        assert!(!ProgramMeterResult::new(restored, false).rolled_back);
+
+        // The meter NAKed the restore of 96.50, which keeps the new value
+        let restore_naked = vec![report("96.50", WriteReply::Ack, false), report("96.51", WriteReply::Nak, false)];
+        assert!(!ProgramMeterResult::new(restore_naked, true).rolled_back);
+
+        // 96.50 had no known old value, so it was never restored
+        let unknown = RegisterReport { old_value: None, ..report("96.50", WriteReply::Ack, false) };
+        assert!(!ProgramMeterResult::new(vec![unknown], true).rolled_back);
+    }
+}
@@ -0,0 +1,462 @@
+//! Per-port I/O worker
+//!
+//! A thread per port owns the `MeterSession`, and with it the transport, and
+//! runs the typed `Request`s queued by the commands one after another. The
+//! commands await the replies, so port reads and protocol delays never block
+//! the async runtime, and `CONNECTION_STATE` is never held across I/O.
+//!
+//! The queue orders single requests only. A command holds the worker's
+//! `OperationGuard` from its first request to its last, so no other command
+//! can close or re-identify its session in between.
+
+use super::events::{emit_log, emit_progress};
+use super::io::{BlockTransfer, ReadConfig};
+use super::operations::CancelToken;
+use super::session::{MeterSession, SessionOptions, SessionState};
+use crate::error::AppError;
+use crate::serial::iec62056::{self, Frame, MeterIdent, ObisDataItem, ProtocolMode};
+use std::sync::{Arc, Mutex};
+use tauri::Window;
+use tokio::sync::{mpsc, oneshot, OwnedMutexGuard};
+
+/// Channel the result of a request is sent back on
+pub type Reply<T> = oneshot::Sender<Result<T, AppError>>;
+
+/// Held by a command for its whole run on a worker, see `MeterWorker::begin`
+pub type OperationGuard = OwnedMutexGuard<()>;
+
+/// Called on the I/O thread with every chunk received during a block read
+pub type RxCallback = Box<dyn FnMut(&[u8], &[u8]) + Send>;
+
+/// Called on the I/O thread after every accepted block
+pub type BlockCallback = Box<dyn FnMut(&BlockTransfer) + Send>;
+
+/// Reply of the meter to a programming command
+#[derive(Debug)]
+pub struct CommandReply {
+    pub frame: Option<Frame>,
+    /// Raw bytes of the frame, for the log
+    pub raw: Vec<u8>,
+    /// Number of bytes received that did not form a complete frame
+    pub pending: usize,
+}
+
+/// Result of a block read
+#[derive(Debug)]
+pub struct BlockRead {
+    pub transfer: BlockTransfer,
+    /// Bytes of the unfinished block when the transfer is incomplete
+    pub pending: Vec<u8>,
+}
+
+/// Parsed data of a readout or MASS packet, see `MeterWorker::read_readout`
+#[derive(Debug)]
+pub struct Readout {
+    /// Data of all blocks, with the unfinished block when the transfer is incomplete
+    pub data: Vec<u8>,
+    pub items: Vec<ObisDataItem>,
+    /// Codes of the items from blocks that never passed the BCC check
+    pub untrusted_codes: Vec<String>,
+    pub cancelled: bool,
+    /// System time (ms since the epoch) once both 0.9.1 and 0.9.2 had arrived
+    pub time_of_09x_read: Option<u64>,
+}
+
+/// Work for the I/O thread; see the `MeterSession` method of the same name
+///
+/// A request that fails after using the link ends the session: the break is
+/// sent and the port closed, as when a command drops its session on error.
+pub enum Request {
+    /// Close the current session, open a new one and read the identification
+    Identify {
+        options: SessionOptions,
+        window: Option<Window>,
+        cancel: Option<CancelToken>,
+        reply: Reply<MeterIdent>,
+    },
+    /// Send events of the open session to the window of the current command,
+    /// under its operation; replies with the session state
+    Attach {
+        window: Option<Window>,
+        cancel: Option<CancelToken>,
+        reply: Reply<SessionState>,
+    },
+    EnterReadout { mode: ProtocolMode, reply: Reply<()> },
+    EnterProgramming { reply: Reply<Frame> },
+    Authenticate { level: u8, password: String, reply: Reply<bool> },
+    Command { msg: Vec<u8>, reply: Reply<CommandReply> },
+    Send { msg: Vec<u8>, reply: Reply<()> },
+    ReadBlocks {
+        config: ReadConfig,
+        on_rx: RxCallback,
+        on_block: BlockCallback,
+        reply: Reply<BlockRead>,
+    },
+    /// Send the break and close the session, if one is open
+    Close { reply: Option<Reply<()>> },
+}
+
+/// Handle to the I/O thread of one port; cheap to clone
+#[derive(Clone)]
+pub struct MeterWorker {
+    requests: mpsc::UnboundedSender<Request>,
+    state: Arc<Mutex<SessionState>>,
+    operation: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl MeterWorker {
+    /// Start the I/O thread for `port`; it ends when the last handle is dropped
+    pub fn spawn(port: &str) -> Result<Self, AppError> {
+        let (requests, receiver) = mpsc::unbounded_channel();
+        let state = Arc::new(Mutex::new(SessionState::Idle));
+        let worker = Worker { session: None, state: state.clone() };
+        std::thread::Builder::new()
+            .name(format!("meter-io {}", port))
+            .spawn(move || worker.run(receiver))
+            .map_err(|e| AppError::Internal { reason: e.to_string() })?;
+        Ok(Self { requests, state, operation: Arc::default() })
+    }
+
+    /// Wait until no other command runs on this worker and hold it until the
+    /// guard is dropped
+    pub async fn begin(&self) -> OperationGuard {
+        self.operation.clone().lock_owned().await
+    }
+
+    /// Whether no command holds this worker
+    pub fn is_idle(&self) -> bool {
+        self.operation.try_lock().is_ok()
+    }
+
+    /// State of the session after the last finished request; never waits for I/O
+    pub fn state(&self) -> SessionState {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub async fn identify(
+        &self,
+        options: SessionOptions,
+        window: Option<&Window>,
+        cancel: Option<CancelToken>,
+    ) -> Result<MeterIdent, AppError> {
+        let window = window.cloned();
+        self.call(|reply| Request::Identify { options, window, cancel, reply }).await
+    }
+
+    pub async fn attach(&self, window: Option<&Window>, cancel: Option<CancelToken>) -> Result<SessionState, AppError> {
+        let window = window.cloned();
+        self.call(|reply| Request::Attach { window, cancel, reply }).await
+    }
+
+    pub async fn enter_readout(&self, mode: ProtocolMode) -> Result<(), AppError> {
+        self.call(|reply| Request::EnterReadout { mode, reply }).await
+    }
+
+    pub async fn enter_programming(&self) -> Result<Frame, AppError> {
+        self.call(|reply| Request::EnterProgramming { reply }).await
+    }
+
+    pub async fn authenticate(&self, level: u8, password: &str) -> Result<bool, AppError> {
+        let password = password.to_string();
+        self.call(|reply| Request::Authenticate { level, password, reply }).await
+    }
+
+    pub async fn command(&self, msg: Vec<u8>) -> Result<CommandReply, AppError> {
+        self.call(|reply| Request::Command { msg, reply }).await
+    }
+
+    pub async fn send(&self, msg: Vec<u8>) -> Result<(), AppError> {
+        self.call(|reply| Request::Send { msg, reply }).await
+    }
+
+    pub async fn read_blocks(
+        &self,
+        config: ReadConfig,
+        on_rx: impl FnMut(&[u8], &[u8]) + Send + 'static,
+        on_block: impl FnMut(&BlockTransfer) + Send + 'static,
+    ) -> Result<BlockRead, AppError> {
+        let (on_rx, on_block) = (Box::new(on_rx), Box::new(on_block));
+        self.call(|reply| Request::ReadBlocks { config, on_rx, on_block, reply }).await
+    }
+
+    pub async fn close(&self) -> Result<(), AppError> {
+        self.call(|reply| Request::Close { reply: Some(reply) }).await
+    }
+
+    /// Receive the readout or packet the meter is sending, close the session
+    /// and parse the data, reporting progress as `step` and `step + 1` of `total_steps`
+    ///
+    /// Data from blocks that never passed the BCC check is kept and its items
+    /// flagged untrusted. An incomplete (idle timeout or cancelled) transfer
+    /// keeps what arrived; no data at all is an error.
+    pub async fn read_readout(
+        &self,
+        config: ReadConfig,
+        window: &Window,
+        step: u32,
+        total_steps: u32,
+    ) -> Result<Readout, AppError> {
+        let idle_timeout_s = config.idle_timeout_ms / 1000;
+        let read_start = std::time::Instant::now();
+        let time_of_09x_read: Arc<Mutex<Option<u64>>> = Arc::default();
+
+        // Capture system time when both 0.9.1 and 0.9.2 have arrived in the buffer
+        let on_rx = {
+            let time_of_09x_read = time_of_09x_read.clone();
+            move |_: &[u8], pending: &[u8]| {
+                let mut time = time_of_09x_read.lock().unwrap_or_else(|e| e.into_inner());
+                if time.is_none() {
+                    let has_091 = pending.windows(6).any(|w| w == b"0.9.1(");
+                    let has_092 = pending.windows(6).any(|w| w == b"0.9.2(");
+                    if has_091 && has_092 {
+                        *time = Some(
+                            std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .unwrap_or_default()
+                                .as_millis() as u64
+                        );
+                    }
+                }
+            }
+        };
+
+        // Large readouts may come as several partial blocks, each acknowledged
+        let on_block = {
+            let window = window.clone();
+            move |transfer: &BlockTransfer| {
+                emit_progress(&window, step, total_steps,
+                    &format!("{} blok alındı ({} byte)", transfer.blocks, transfer.data.len()));
+            }
+        };
+
+        // On error the worker sends the break and closes the port
+        let read = self.read_blocks(config, on_rx, on_block).await
+            .inspect_err(|e| emit_log(window, "error", &e.to_string()))?;
+        let transfer = read.transfer;
+        let time_of_09x_read = *time_of_09x_read.lock()?;
+
+        if transfer.naks > 0 {
+            emit_log(window, "warn", &format!("{} blok BCC hatası nedeniyle tekrar istendi (NAK)", transfer.naks));
+        }
+
+        let found_etx = transfer.complete;
+        let cancelled = transfer.cancelled;
+        let untrusted = transfer.untrusted;
+        let data = if transfer.complete {
+            emit_log(window, "info", &format!("Veri alımı tamamlandı: {} byte, {} blok, süre: {:.1}s",
+                transfer.data.len(), transfer.blocks, read_start.elapsed().as_secs_f32()));
+            transfer.data
+        } else {
+            // Idle timeout or cancelled: keep whatever part of the data arrived
+            let mut partial = transfer.data;
+            partial.extend_from_slice(&read.pending);
+            if cancelled {
+                emit_log(window, "warn", &format!("İşlem iptal edildi: {} byte alındı", partial.len()));
+            } else if partial.is_empty() {
+                emit_log(window, "error", &format!("Zaman aşımı: Hiç veri alınamadı ({}s boşta)", idle_timeout_s));
+            } else {
+                emit_log(window, "warn", &format!("Boşta kalma zaman aşımı: {} byte alındı ama ETX yok", partial.len()));
+            }
+            partial
+        };
+
+        // Send Break command and close port
+        self.close().await?;
+
+        if data.is_empty() {
+            emit_log(window, "error", "Veri alınamadı");
+            return Err(if cancelled { AppError::Cancelled } else { AppError::Timeout { received: 0 } });
+        }
+
+        if !found_etx {
+            emit_log(window, "warn", &format!("Veri tam alınamadı: ETX bulunamadı ({} byte alındı)", data.len()));
+        }
+
+        emit_progress(window, step + 1, total_steps, "Veriler doğrulanıyor...");
+
+        if !untrusted.is_empty() {
+            emit_log(window, "warn", &format!("BCC uyuşmazlığı: {} blok tekrar sonrasında da bozuk, bu bloklardaki değerler güvenilmez",
+                untrusted.len()));
+        } else if found_etx {
+            emit_log(window, "success", &format!("BCC doğrulaması başarılı ({} byte)", data.len()));
+        }
+
+        emit_log(window, "rx", &iec62056::format_bytes_for_display(&data));
+
+        // Parse the OBIS data, flagging values from blocks that never passed BCC
+        let items = iec62056::parse_data_block_checked(&data, &untrusted);
+        emit_log(window, "info", &format!("{} OBIS kodu ayrıştırıldı", items.len()));
+
+        let untrusted_codes: Vec<String> = items.iter()
+            .filter(|item| item.untrusted)
+            .map(|item| item.code.clone())
+            .collect();
+        if !untrusted_codes.is_empty() {
+            emit_log(window, "warn", &format!("Güvenilmez değerler (BCC hatalı): {}", untrusted_codes.join(", ")));
+        }
+
+        Ok(Readout { data, items, untrusted_codes, cancelled, time_of_09x_read })
+    }
+
+    /// Queue closing the session without waiting for it
+    pub fn close_later(&self) {
+        let _ = self.requests.send(Request::Close { reply: None });
+    }
+
+    async fn call<T>(&self, request: impl FnOnce(Reply<T>) -> Request) -> Result<T, AppError> {
+        let (reply, result) = oneshot::channel();
+        self.requests.send(request(reply)).map_err(|_| stopped())?;
+        result.await.map_err(|_| stopped())?
+    }
+}
+
+fn stopped() -> AppError {
+    AppError::Internal { reason: "reason_worker_stopped".to_string() }
+}
+
+/// The I/O thread
+struct Worker {
+    session: Option<MeterSession>,
+    state: Arc<Mutex<SessionState>>,
+}
+
+impl Worker {
+    /// Run requests until every handle is dropped; an open session is closed on exit
+    fn run(mut self, mut requests: mpsc::UnboundedReceiver<Request>) {
+        while let Some(request) = requests.blocking_recv() {
+            self.handle(request);
+        }
+        self.close();
+    }
+
+    fn handle(&mut self, request: Request) {
+        match request {
+            Request::Identify { options, window, cancel, reply } => {
+                self.close();
+                let mut session = MeterSession::new(options, window.as_ref());
+                session.set_cancel(cancel);
+                let result = session.identify();
+                if result.is_ok() {
+                    self.session = Some(session);
+                }
+                self.reply(reply, result);
+            }
+            Request::Attach { window, cancel, reply } => {
+                let result = self.session().map(|session| {
+                    session.set_window(window.as_ref());
+                    session.set_cancel(cancel);
+                    session.state()
+                });
+                self.reply(reply, result);
+            }
+            Request::EnterReadout { mode, reply } => {
+                let result = self.session().and_then(|session| session.enter_readout(mode));
+                self.reply(reply, result);
+            }
+            Request::EnterProgramming { reply } => {
+                let result = self.session().and_then(|session| session.enter_programming());
+                self.reply(reply, result);
+            }
+            Request::Authenticate { level, password, reply } => {
+                let result = self.session().and_then(|session| session.authenticate(level, &password));
+                self.reply(reply, result);
+            }
+            Request::Command { msg, reply } => {
+                let result = self.session().and_then(|session| {
+                    let frame = session.command(&msg)?;
+                    let decoder = session.decoder();
+                    Ok(CommandReply { frame, raw: decoder.last_frame_bytes().to_vec(), pending: decoder.pending().len() })
+                });
+                self.reply(reply, result);
+            }
+            Request::Send { msg, reply } => {
+                let result = self.session().and_then(|session| session.send(&msg));
+                self.reply(reply, result);
+            }
+            Request::ReadBlocks { config, on_rx, on_block, reply } => {
+                let result = self.session().and_then(|session| {
+                    let transfer = session.read_blocks(&config, on_rx, on_block)?;
+                    let pending = if transfer.complete { Vec::new() } else { session.decoder().take_pending() };
+                    Ok(BlockRead { transfer, pending })
+                });
+                self.reply(reply, result);
+            }
+            Request::Close { reply } => {
+                self.close();
+                match reply {
+                    Some(reply) => self.reply(reply, Ok(())),
+                    None => self.publish_state(),
+                }
+            }
+        }
+    }
+
+    fn session(&mut self) -> Result<&mut MeterSession, AppError> {
+        self.session.as_mut().ok_or(AppError::NotConnected)
+    }
+
+    /// Send the result back, closing the session if the request failed on the link
+    fn reply<T>(&mut self, reply: Reply<T>, result: Result<T, AppError>) {
+        if let Err(e) = &result {
+            if !matches!(e, AppError::NotConnected | AppError::NotInProgrammingMode) {
+                self.close();
+            }
+        }
+        // Published first, so the command sees the new state once it has the reply
+        self.publish_state();
+        let _ = reply.send(result);
+    }
+
+    fn publish_state(&self) {
+        let state = self.session.as_ref().map_or(SessionState::Idle, |session| session.state());
+        *self.state.lock().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+
+    fn close(&mut self) {
+        if let Some(mut session) = self.session.take() {
+            session.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::iec62056;
+    use crate::simulator::test_support::start_meter;
+    use crate::simulator::MeterConfig;
+
+    #[tokio::test]
+    async fn test_worker_session() {
+        let options = start_meter(MeterConfig::default());
+        let worker = MeterWorker::spawn(&options.link.name()).unwrap();
+        assert_eq!(worker.enter_readout(ProtocolMode::Readout).await.unwrap_err(), AppError::NotConnected);
+
+        worker.identify(options, None, None).await.unwrap();
+        assert_eq!(worker.state(), SessionState::Identified);
+        worker.enter_programming().await.unwrap();
+        assert!(worker.authenticate(1, "12345678").await.unwrap());
+        assert_eq!(worker.state(), SessionState::Programming);
+
+        let reply = worker.command(iec62056::build_read_command("0.9.1")).await.unwrap();
+        assert_eq!(reply.frame, Some(Frame::DataBlock { data: b"0.9.1(12:30:45)".to_vec(), bcc_ok: true }));
+
+        worker.close().await.unwrap();
+        assert_eq!(worker.state(), SessionState::Idle);
+        assert_eq!(worker.command(iec62056::build_read_command("0.9.1")).await.unwrap_err(), AppError::NotConnected);
+    }
+
+    #[tokio::test]
+    async fn test_worker_operation_guard() {
+        let worker = MeterWorker::spawn("guard-test").unwrap();
+        let operation = worker.begin().await;
+
+        // A second command waits until the first has finished
+        let wait = std::time::Duration::from_millis(50);
+        assert!(tokio::time::timeout(wait, worker.begin()).await.is_err());
+        assert!(!worker.is_idle());
+        drop(operation);
+        assert!(worker.is_idle());
+        assert!(tokio::time::timeout(wait, worker.begin()).await.is_ok());
+    }
+}
@@ -0,0 +1,263 @@
+//! Structured error type for protocol, I/O and Tauri commands
+//!
+//! Every error carries a machine-readable code and its context fields. It is
+//! serialized as `{ code, message, context }`, with the message localized
+//! through `i18n::I18n` in the language selected by the frontend.
+
+use crate::i18n::{self, I18n};
+use crate::serial::iec62056::{Frame, IdentError};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::json;
+
+/// Error returned by every `#[tauri::command]`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// No connection parameters or no open port; connect first
+    NotConnected,
+    /// The serial port could not be opened
+    PortOpen { port: String, reason: String },
+    /// Reading from or writing to the port failed
+    Io { reason: String },
+    /// The port could not be switched to the negotiated baud rate
+    BaudSwitch { baud: u32, reason: String },
+    /// The meter did not answer in time
+    Timeout { received: usize },
+    /// The operation was cancelled by the user before any data arrived
+    Cancelled,
+    /// No identification message at any baud rate
+    NoIdentification { port: String },
+    /// The identification message was malformed: an i18n key and the offending part
+    InvalidIdentification { reason: String, detail: Option<String> },
+    /// The protocol mode of the meter does not support the operation (an i18n key)
+    UnsupportedMode { mode: char, operation: String },
+    /// Data blocks failed BCC even after retransmission
+    BccMismatch { blocks: u32 },
+    /// The meter rejected a command with NAK
+    Nak { command: String },
+    /// The meter rejected the password
+    PasswordRejected,
+    /// The password is not 8 digits
+    InvalidPassword,
+    /// The command needs programming mode (authenticate first)
+    NotInProgrammingMode,
+    /// The connected meter is not the one the operation was meant for
+    MeterMismatch { expected: String, actual: String },
+    /// Invalid arguments from the caller: an i18n key and the offending value
+    InvalidInput { reason: String, detail: Option<String> },
+    /// The meter sent something other than the expected reply: an i18n key and
+    /// what was received
+    InvalidResponse { reason: String, detail: Option<String> },
+    /// SQLite storage failed
+    Database { reason: String },
+    /// A session file could not be read or written
+    File { path: String, reason: String },
+    /// Shared state is unusable (poisoned lock)
+    Internal { reason: String },
+}
+
+impl AppError {
+    /// Port read/write failure
+    pub fn io(e: impl std::fmt::Display) -> Self {
+        AppError::Io { reason: e.to_string() }
+    }
+
+    /// Rejected argument; `reason` is an i18n key
+    pub fn invalid_input(reason: &str) -> Self {
+        AppError::InvalidInput { reason: reason.to_string(), detail: None }
+    }
+
+    /// Rejected argument with the offending value, e.g. `"96.50: 25:00"`
+    pub fn invalid_value(reason: &str, detail: impl std::fmt::Display) -> Self {
+        AppError::InvalidInput { reason: reason.to_string(), detail: Some(detail.to_string()) }
+    }
+
+    /// The meter answered with `frame` where another reply was expected; the
+    /// frame is named in the error and dumped to the log
+    pub fn unexpected_frame(frame: &Frame) -> Self {
+        log::warn!("Unexpected frame: {:?}", frame);
+        AppError::InvalidResponse { reason: "reason_unexpected_frame".to_string(), detail: Some(frame.kind().to_string()) }
+    }
+
+    /// Machine-readable code, stable across languages
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotConnected => "NotConnected",
+            AppError::PortOpen { .. } => "PortOpen",
+            AppError::Io { .. } => "Io",
+            AppError::BaudSwitch { .. } => "BaudSwitch",
+            AppError::Timeout { .. } => "Timeout",
+            AppError::Cancelled => "Cancelled",
+            AppError::NoIdentification { .. } => "NoIdentification",
+            AppError::InvalidIdentification { .. } => "InvalidIdentification",
+            AppError::UnsupportedMode { .. } => "UnsupportedMode",
+            AppError::BccMismatch { .. } => "BccMismatch",
+            AppError::Nak { .. } => "Nak",
+            AppError::PasswordRejected => "PasswordRejected",
+            AppError::InvalidPassword => "InvalidPassword",
+            AppError::NotInProgrammingMode => "NotInProgrammingMode",
+            AppError::MeterMismatch { .. } => "MeterMismatch",
+            AppError::InvalidInput { .. } => "InvalidInput",
+            AppError::InvalidResponse { .. } => "InvalidResponse",
+            AppError::Database { .. } => "Database",
+            AppError::File { .. } => "File",
+            AppError::Internal { .. } => "Internal",
+        }
+    }
+
+    /// Context fields of the error as a JSON object
+    pub fn context(&self) -> serde_json::Value {
+        match self {
+            AppError::PortOpen { port, reason } => json!({ "port": port, "reason": reason }),
+            AppError::Io { reason }
+            | AppError::Database { reason }
+            | AppError::Internal { reason } => json!({ "reason": reason }),
+            AppError::InvalidInput { reason, detail }
+            | AppError::InvalidIdentification { reason, detail }
+            | AppError::InvalidResponse { reason, detail } => json!({ "reason": reason, "detail": detail }),
+            AppError::BaudSwitch { baud, reason } => json!({ "baud": baud, "reason": reason }),
+            AppError::Timeout { received } => json!({ "received": received }),
+            AppError::NoIdentification { port } => json!({ "port": port }),
+            AppError::UnsupportedMode { mode, operation } => json!({ "mode": mode, "operation": operation }),
+            AppError::BccMismatch { blocks } => json!({ "blocks": blocks }),
+            AppError::Nak { command } => json!({ "command": command }),
+            AppError::File { path, reason } => json!({ "path": path, "reason": reason }),
+            AppError::MeterMismatch { expected, actual } => json!({ "expected": expected, "actual": actual }),
+            AppError::NotConnected
+            | AppError::Cancelled
+            | AppError::PasswordRejected
+            | AppError::InvalidPassword
+            | AppError::NotInProgrammingMode => json!({}),
+        }
+    }
+
+    /// Human-readable message in the language of `i18n`
+    ///
+    /// Reasons are i18n keys where the app produces them; system error texts
+    /// have no key and are shown as they are.
+    pub fn message(&self, i18n: &I18n) -> String {
+        match self {
+            AppError::NotConnected => i18n.t("error_not_connected"),
+            AppError::PortOpen { port, reason } => format!("{} ({}): {}", i18n.t("error_port_open"), port, i18n.t(reason)),
+            AppError::Io { reason } => format!("{}: {}", i18n.t("error_io"), i18n.t(reason)),
+            AppError::BaudSwitch { baud, reason } => format!("{} ({}): {}", i18n.t("error_baud_switch"), baud, i18n.t(reason)),
+            AppError::Timeout { received } => format!("{} ({} byte)", i18n.t("error_timeout"), received),
+            AppError::Cancelled => i18n.t("error_cancelled"),
+            AppError::NoIdentification { port } => format!("{} ({})", i18n.t("error_no_identification"), port),
+            AppError::InvalidIdentification { reason, detail: None } => {
+                format!("{}: {}", i18n.t("error_invalid_identification"), i18n.t(reason))
+            }
+            AppError::InvalidIdentification { reason, detail: Some(detail) } => {
+                format!("{}: {} ({})", i18n.t("error_invalid_identification"), i18n.t(reason), detail)
+            }
+            AppError::UnsupportedMode { mode, operation } => {
+                format!("{} ({} {}): {}", i18n.t("error_unsupported_mode"), i18n.t("label_protocol_mode"), mode, i18n.t(operation))
+            }
+            AppError::BccMismatch { blocks } => format!("{} ({})", i18n.t("error_bcc_mismatch"), blocks),
+            AppError::Nak { command } => format!("{} ({})", i18n.t("error_nak"), command),
+            AppError::PasswordRejected => i18n.t("error_password_rejected"),
+            AppError::InvalidPassword => i18n.t("error_invalid_password"),
+            AppError::NotInProgrammingMode => i18n.t("error_not_in_programming_mode"),
+            AppError::MeterMismatch { expected, actual } => {
+                format!("{} ({} ≠ {})", i18n.t("error_meter_mismatch"), actual, expected)
+            }
+            AppError::InvalidInput { reason, detail: None } => format!("{}: {}", i18n.t("error_invalid_input"), i18n.t(reason)),
+            AppError::InvalidInput { reason, detail: Some(detail) } => {
+                format!("{}: {} ({})", i18n.t("error_invalid_input"), i18n.t(reason), detail)
+            }
+            AppError::InvalidResponse { reason, detail: None } => {
+                format!("{}: {}", i18n.t("error_invalid_response"), i18n.t(reason))
+            }
+            AppError::InvalidResponse { reason, detail: Some(detail) } => {
+                format!("{}: {} ({})", i18n.t("error_invalid_response"), i18n.t(reason), detail)
+            }
+            AppError::Database { reason } => format!("{}: {}", i18n.t("error_database"), i18n.t(reason)),
+            AppError::File { path, reason } => format!("{} ({}): {}", i18n.t("error_file"), path, i18n.t(reason)),
+            AppError::Internal { reason } => format!("{}: {}", i18n.t("error_internal"), i18n.t(reason)),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message(&i18n::current()))
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("context", &self.context())?;
+        state.end()
+    }
+}
+
+impl From<IdentError> for AppError {
+    fn from(e: IdentError) -> Self {
+        let (reason, detail) = match e {
+            IdentError::MissingStart => ("reason_ident_missing_start", None),
+            IdentError::TooShort(len) => ("reason_ident_too_short", Some(len.to_string())),
+            IdentError::InvalidManufacturer(code) => ("reason_ident_invalid_manufacturer", Some(code)),
+            IdentError::ReservedBaudChar(c) => ("reason_ident_reserved_baud_char", Some(c.to_string())),
+            IdentError::IncompleteEscape => ("reason_ident_incomplete_escape", None),
+            IdentError::UnterminatedGeneration => ("reason_ident_unterminated_generation", None),
+            IdentError::UnterminatedModel => ("reason_ident_unterminated_model", None),
+        };
+        AppError::InvalidIdentification { reason: reason.to_string(), detail }
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        AppError::Database { reason: e.to_string() }
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        AppError::Internal { reason: e.to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Lang;
+
+    #[test]
+    fn test_error_serialization() {
+        let err = AppError::Nak { command: "W2".to_string() };
+        let value = serde_json::to_value(&err).unwrap();
+        assert_eq!(value["code"], "Nak");
+        assert_eq!(value["context"]["command"], "W2");
+        assert!(value["message"].as_str().unwrap().contains("W2"));
+
+        let en = I18n::new(Lang::English);
+        assert_eq!(AppError::NotInProgrammingMode.message(&en), "Meter is not in programming mode");
+
+        // Reasons are localized with the rest of the message, values are kept
+        let err = AppError::invalid_value("reason_tariff_time_order", "96.50: 06:00");
+        assert_eq!(err.message(&en), "Invalid argument: switching times must be in ascending order (96.50: 06:00)");
+        let tr = I18n::new(Lang::Turkish);
+        assert_eq!(err.message(&tr), "Geçersiz parametre: değişim saatleri artan sırada olmalı (96.50: 06:00)");
+        assert_eq!(serde_json::to_value(&err).unwrap()["context"]["reason"], "reason_tariff_time_order");
+        assert_eq!(AppError::io("Broken pipe").message(&en), "Port read/write error: Broken pipe");
+
+        // Identification errors name the offending part outside the key
+        let err = AppError::from(IdentError::ReservedBaudChar('G'));
+        assert_eq!(err.message(&en), "Failed to parse meter identification: the baud rate character is reserved (G)");
+        assert_eq!(err.message(&tr), "Sayaç tanımlama yanıtı ayrıştırılamadı: baud hızı karakteri ayrılmış (G)");
+        assert_eq!(serde_json::to_value(&err).unwrap()["context"]["reason"], "reason_ident_reserved_baud_char");
+
+        let err = AppError::UnsupportedMode { mode: 'A', operation: "operation_short_read".to_string() };
+        assert_eq!(err.message(&en), "The meter's protocol mode does not support this operation (Mode A): short read (packet 6)");
+        assert_eq!(err.message(&tr), "Sayacın protokol modu bu işlemi desteklemiyor (Mod A): kısa okuma (Paket 6)");
+
+        let err = AppError::unexpected_frame(&Frame::DataBlock { data: b"0.9.1(12:30:45)".to_vec(), bcc_ok: true });
+        assert_eq!(err.message(&en), "Invalid response from meter: unexpected frame type (DataBlock)");
+        assert_eq!(serde_json::to_value(&err).unwrap()["context"]["detail"], "DataBlock");
+    }
+}
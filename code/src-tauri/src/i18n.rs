@@ -1,5 +1,25 @@
 //! Internationalization support for backend messages
 
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+/// Language selected by the frontend, used for error messages
+static CURRENT_LANG: Lazy<Mutex<Lang>> = Lazy::new(|| Mutex::new(Lang::Turkish));
+
+/// Switch the language of backend messages
+pub fn set_language(lang: Lang) {
+    if let Ok(mut current) = CURRENT_LANG.lock() {
+        *current = lang;
+    }
+}
+
+/// Translator for the currently selected language
+pub fn current() -> I18n {
+    let lang = CURRENT_LANG.lock().map(|l| *l).unwrap_or(Lang::Turkish);
+    I18n::new(lang)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Lang {
     Turkish,
     English,
@@ -31,6 +51,7 @@ impl I18n {
         }
     }
 
+    #[allow(clippy::useless_format)]
     fn tr(&self, key: &str) -> String {
         match key {
             // Connection messages
@@ -80,10 +101,81 @@ impl I18n {
             "receiving_data" => format!("Veriler alınıyor..."),
             "complete" => format!("Tamamlandı!"),
 
+            // Error codes
+            "error_not_connected" => format!("Bağlantı yok. Önce 'Bağlan' butonuna tıklayın."),
+            "error_port_open" => format!("Port açılamadı"),
+            "error_io" => format!("Port okuma/yazma hatası"),
+            "error_baud_switch" => format!("Baud hızı değiştirilemedi"),
+            "error_timeout" => format!("Zaman aşımı: sayaç yanıt vermedi"),
+            "error_cancelled" => format!("İşlem iptal edildi"),
+            "error_no_identification" => format!("Hiçbir baud hızında yanıt alınamadı"),
+            "error_invalid_identification" => format!("Sayaç tanımlama yanıtı ayrıştırılamadı"),
+            "error_unsupported_mode" => format!("Sayacın protokol modu bu işlemi desteklemiyor"),
+            "label_protocol_mode" => format!("Mod"),
+            "operation_readout" => format!("tam okuma"),
+            "operation_programming" => format!("programlama modu"),
+            "operation_tech_quality" => format!("teknik kalite okuma (Paket 5)"),
+            "operation_short_read" => format!("kısa okuma (Paket 6)"),
+            "operation_historical" => format!("geçmiş veri okuma (Paket 7)"),
+            "operation_warnings" => format!("uyarı okuma (Paket 8)"),
+            "operation_outages" => format!("kesinti okuma (Paket 9)"),
+            "error_bcc_mismatch" => format!("BCC hatası: bloklar tekrar sonrasında da bozuk"),
+            "error_nak" => format!("Sayaç komutu reddetti (NAK)"),
+            "error_password_rejected" => format!("Şifre reddedildi"),
+            "error_invalid_password" => format!("Şifre 8 haneli olmalıdır"),
+            "error_not_in_programming_mode" => format!("Sayaç programlama modunda değil"),
+            "error_meter_mismatch" => format!("Bağlı sayaç beklenen sayaç değil"),
+            "error_invalid_input" => format!("Geçersiz parametre"),
+            "error_invalid_response" => format!("Geçersiz yanıt"),
+            "error_database" => format!("Veritabanı hatası"),
+            "error_file" => format!("Dosya hatası"),
+            "error_internal" => format!("İç hata"),
+
+            // Error reasons
+            "reason_empty_obis_list" => format!("OBIS kodu listesi boş"),
+            "reason_empty_obis_code" => format!("boş OBIS kodu"),
+            "reason_empty_write_list" => format!("yazılacak kayıt listesi boş"),
+            "reason_expected_serial_missing" => format!("beklenen seri numarası boş"),
+            "reason_relay_confirmation" => format!("röle onay kodu geçersiz"),
+            "reason_password_unchanged" => format!("yeni şifre mevcut şifreyle aynı"),
+            "reason_password_level_unsupported" => format!("bu şifre seviyesinin kaydı belgelenmemiş"),
+            "reason_unsupported_packet" => format!("desteklenmeyen paket (5, 7, 8 veya 9 olmalı)"),
+            "reason_not_a_packet" => format!("paket modu değil"),
+            "reason_operation_id_in_use" => format!("işlem kimliği zaten kullanımda"),
+            "reason_tcp_host_missing" => format!("TCP bağlantısı için sunucu adresi gerekli"),
+            "reason_tcp_port_missing" => format!("TCP bağlantısı için port numarası gerekli"),
+            "reason_address_unresolved" => format!("adres çözümlenemedi"),
+            "reason_connection_closed" => format!("bağlantı karşı taraf tarafından kapatıldı"),
+            "reason_unexpected_frame" => format!("beklenmeyen çerçeve türü"),
+            "reason_worker_stopped" => format!("G/Ç iş parçacığı durdu"),
+            "reason_ident_missing_start" => format!("yanıt '/' ile başlamıyor"),
+            "reason_ident_too_short" => format!("yanıtta çok az karakter var"),
+            "reason_ident_invalid_manufacturer" => format!("üretici kodu geçersiz"),
+            "reason_ident_reserved_baud_char" => format!("baud hızı karakteri ayrılmış"),
+            "reason_ident_incomplete_escape" => format!("'\\' kaçış dizisinde yetenek karakteri yok"),
+            "reason_ident_unterminated_generation" => format!("'<' nesil işareti '>' ile kapanmıyor"),
+            "reason_ident_unterminated_model" => format!("'(' model alanı ')' ile kapanmıyor"),
+            "reason_database_not_initialized" => format!("veritabanı başlatılmadı"),
+            "reason_capture_version" => format!("desteklenmeyen kayıt sürümü"),
+            "reason_replay_no_session" => format!("kayıtta başka port oturumu yok"),
+            "reason_tariff_no_slots" => format!("tarife tablosunda en az bir dilim olmalı"),
+            "reason_tariff_too_many_slots" => format!("tarife tablosunda en fazla 8 dilim olabilir"),
+            "reason_tariff_invalid_time" => format!("geçersiz değişim saati"),
+            "reason_tariff_time_order" => format!("değişim saatleri artan sırada olmalı"),
+            "reason_tariff_number" => format!("tarife 1-4 arasında olmalı"),
+            "reason_dst_rule" => format!("geçersiz DST kuralı"),
+            "reason_dst_period_ids" => format!("DST dönem numaraları 1-12 arasında ve tekil olmalı"),
+            "reason_dst_no_periods" => format!("DST etkin, ancak tanımlı dönem yok"),
+            "reason_dst_offset" => format!("DST döneminin saat farkı geçersiz"),
+            "reason_dst_time_format" => format!("DST döneminin ileri ve geri alma zamanı YYYY-AA-GG SS:DD olmalı"),
+            "reason_dst_order" => format!("DST döneminde ileri alma, geri almadan önce olmalı"),
+            "reason_dst_overlap" => format!("DST dönemi önceki dönem bitmeden başlıyor"),
+
             _ => key.to_string(),
         }
     }
 
+    #[allow(clippy::useless_format)]
     fn en(&self, key: &str) -> String {
         match key {
             // Connection messages
@@ -133,6 +225,76 @@ impl I18n {
             "receiving_data" => format!("Receiving data..."),
             "complete" => format!("Completed!"),
 
+            // Error codes
+            "error_not_connected" => format!("Not connected. Click 'Connect' first."),
+            "error_port_open" => format!("Failed to open port"),
+            "error_io" => format!("Port read/write error"),
+            "error_baud_switch" => format!("Failed to switch baud rate"),
+            "error_timeout" => format!("Timeout: the meter did not respond"),
+            "error_cancelled" => format!("Operation cancelled"),
+            "error_no_identification" => format!("No response at any baud rate"),
+            "error_invalid_identification" => format!("Failed to parse meter identification"),
+            "error_unsupported_mode" => format!("The meter's protocol mode does not support this operation"),
+            "label_protocol_mode" => format!("Mode"),
+            "operation_readout" => format!("full readout"),
+            "operation_programming" => format!("programming mode"),
+            "operation_tech_quality" => format!("technical quality read (packet 5)"),
+            "operation_short_read" => format!("short read (packet 6)"),
+            "operation_historical" => format!("historical data read (packet 7)"),
+            "operation_warnings" => format!("warnings read (packet 8)"),
+            "operation_outages" => format!("outages read (packet 9)"),
+            "error_bcc_mismatch" => format!("BCC error: blocks still corrupt after retransmission"),
+            "error_nak" => format!("Command rejected by meter (NAK)"),
+            "error_password_rejected" => format!("Password rejected"),
+            "error_invalid_password" => format!("Password must be exactly 8 digits"),
+            "error_not_in_programming_mode" => format!("Meter is not in programming mode"),
+            "error_meter_mismatch" => format!("The connected meter is not the expected meter"),
+            "error_invalid_input" => format!("Invalid argument"),
+            "error_invalid_response" => format!("Invalid response from meter"),
+            "error_database" => format!("Database error"),
+            "error_file" => format!("File error"),
+            "error_internal" => format!("Internal error"),
+
+            // Error reasons
+            "reason_empty_obis_list" => format!("the OBIS code list is empty"),
+            "reason_empty_obis_code" => format!("empty OBIS code"),
+            "reason_empty_write_list" => format!("there are no registers to write"),
+            "reason_expected_serial_missing" => format!("the expected serial number is empty"),
+            "reason_relay_confirmation" => format!("the relay confirmation code is invalid"),
+            "reason_password_unchanged" => format!("the new password is the same as the current one"),
+            "reason_password_level_unsupported" => format!("the register of this password level is not documented"),
+            "reason_unsupported_packet" => format!("unsupported packet (must be 5, 7, 8 or 9)"),
+            "reason_not_a_packet" => format!("not a packet mode"),
+            "reason_operation_id_in_use" => format!("the operation ID is already in use"),
+            "reason_tcp_host_missing" => format!("a TCP connection needs a host address"),
+            "reason_tcp_port_missing" => format!("a TCP connection needs a port number"),
+            "reason_address_unresolved" => format!("the address could not be resolved"),
+            "reason_connection_closed" => format!("the connection was closed by the other side"),
+            "reason_unexpected_frame" => format!("unexpected frame type"),
+            "reason_worker_stopped" => format!("the I/O thread stopped"),
+            "reason_ident_missing_start" => format!("the reply does not start with '/'"),
+            "reason_ident_too_short" => format!("the reply has too few characters"),
+            "reason_ident_invalid_manufacturer" => format!("invalid manufacturer code"),
+            "reason_ident_reserved_baud_char" => format!("the baud rate character is reserved"),
+            "reason_ident_incomplete_escape" => format!("escape sequence '\\' without a capability character"),
+            "reason_ident_unterminated_generation" => format!("generation marker '<' is not closed with '>'"),
+            "reason_ident_unterminated_model" => format!("model '(' is not closed with ')'"),
+            "reason_database_not_initialized" => format!("the database is not initialized"),
+            "reason_capture_version" => format!("unsupported capture version"),
+            "reason_replay_no_session" => format!("the capture has no further port session"),
+            "reason_tariff_no_slots" => format!("a tariff table needs at least one slot"),
+            "reason_tariff_too_many_slots" => format!("a tariff table can have at most 8 slots"),
+            "reason_tariff_invalid_time" => format!("invalid switching time"),
+            "reason_tariff_time_order" => format!("switching times must be in ascending order"),
+            "reason_tariff_number" => format!("the tariff must be 1-4"),
+            "reason_dst_rule" => format!("invalid DST rule"),
+            "reason_dst_period_ids" => format!("DST period numbers must be unique and 1-12"),
+            "reason_dst_no_periods" => format!("DST is enabled but no period is defined"),
+            "reason_dst_offset" => format!("invalid time offset of a DST period"),
+            "reason_dst_time_format" => format!("the forward and backward times of a DST period must be YYYY-MM-DD HH:MM"),
+            "reason_dst_order" => format!("a DST period must move forward before it moves back"),
+            "reason_dst_overlap" => format!("a DST period starts before the previous one ends"),
+
             _ => key.to_string(),
         }
     }
@@ -5,9 +5,14 @@ mod serial;
 mod commands;
 mod storage;
 mod i18n;
+mod error;
+pub mod mass;
+pub mod simulator;
 
 pub use commands::*;
-pub use storage::{Session, Report, AppSettings};
+pub use error::AppError;
+pub use serial::iec62056;
+pub use storage::{Session, Report, AppSettings, AuditEntry};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortInfo {
@@ -25,6 +30,12 @@ pub struct MeterIdentity {
     pub baud_rate_char: String,
     pub generation: String,
     pub serial_number: Option<String>,
+    /// IEC 62056-21 protocol mode of the session ("A"-"D")
+    #[serde(default)]
+    pub protocol_mode: String,
+    /// Identification message as received, e.g. "/MKS5<2>ADM(M550.2251)"
+    #[serde(default)]
+    pub raw_ident: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +47,21 @@ pub struct ConnectionParams {
     pub timeout_ms: u32,
     pub meter_address: Option<String>,
     pub password: Option<String>,
+    /// NAK retransmission requests for a block with a BCC error (default 3)
+    #[serde(default)]
+    pub bcc_retries: Option<u32>,
+    /// IEC 62056-21 protocol mode ("A"-"D"); auto-detected from the identification if unset
+    #[serde(default)]
+    pub protocol_mode: Option<String>,
+    /// Converter or modem address for the "tcp" connection type
+    #[serde(default)]
+    pub host: Option<String>,
+    /// TCP port for the "tcp" connection type
+    #[serde(default)]
+    pub tcp_port: Option<u16>,
+    /// Negotiate a baud switch over TCP, for converters that follow it
+    #[serde(default)]
+    pub tcp_baud_switch: bool,
 }
 
 // Database commands
@@ -48,76 +74,83 @@ mod db_commands {
     pub fn save_session(
         session: Session,
         overwrite: bool,
-    ) -> Result<i64, String> {
+    ) -> Result<i64, AppError> {
         let guard = storage::get_database()?;
-        let db = guard.as_ref().ok_or("Database not initialized")?;
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
 
         if overwrite {
             // Check if session exists for this meter
-            if let Some(existing) = db.find_session_by_meter(&session.meter_serial, &session.meter_flag)
-                .map_err(|e| e.to_string())? {
-                db.update_session(existing.id, &session).map_err(|e| e.to_string())?;
+            if let Some(existing) = db.find_session_by_meter(&session.meter_serial, &session.meter_flag)? {
+                db.update_session(existing.id, &session)?;
                 return Ok(existing.id);
             }
         }
 
-        db.save_session(&session).map_err(|e| e.to_string())
+        Ok(db.save_session(&session)?)
     }
 
     /// Get a session by ID
     #[tauri::command]
-    pub fn get_session(id: i64) -> Result<Option<Session>, String> {
+    pub fn get_session(id: i64) -> Result<Option<Session>, AppError> {
         let guard = storage::get_database()?;
-        let db = guard.as_ref().ok_or("Database not initialized")?;
-        db.get_session(id).map_err(|e| e.to_string())
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+        Ok(db.get_session(id)?)
     }
 
     /// Get recent sessions
     #[tauri::command]
-    pub fn get_recent_sessions(limit: u32) -> Result<Vec<Session>, String> {
+    pub fn get_recent_sessions(limit: u32) -> Result<Vec<Session>, AppError> {
         let guard = storage::get_database()?;
-        let db = guard.as_ref().ok_or("Database not initialized")?;
-        db.get_recent_sessions(limit).map_err(|e| e.to_string())
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+        Ok(db.get_recent_sessions(limit)?)
     }
 
     /// Delete a session
     #[tauri::command]
-    pub fn delete_session(id: i64) -> Result<(), String> {
+    pub fn delete_session(id: i64) -> Result<(), AppError> {
         let guard = storage::get_database()?;
-        let db = guard.as_ref().ok_or("Database not initialized")?;
-        db.delete_session(id).map_err(|e| e.to_string())
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+        Ok(db.delete_session(id)?)
     }
 
     /// Save a report
     #[tauri::command]
-    pub fn save_report(report: Report) -> Result<i64, String> {
+    pub fn save_report(report: Report) -> Result<i64, AppError> {
         let guard = storage::get_database()?;
-        let db = guard.as_ref().ok_or("Database not initialized")?;
-        db.save_report(&report).map_err(|e| e.to_string())
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+        Ok(db.save_report(&report)?)
     }
 
     /// Get recent reports
     #[tauri::command]
-    pub fn get_recent_reports(limit: u32) -> Result<Vec<Report>, String> {
+    pub fn get_recent_reports(limit: u32) -> Result<Vec<Report>, AppError> {
         let guard = storage::get_database()?;
-        let db = guard.as_ref().ok_or("Database not initialized")?;
-        db.get_recent_reports(limit).map_err(|e| e.to_string())
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+        Ok(db.get_recent_reports(limit)?)
     }
 
     /// Get a setting value
     #[tauri::command]
-    pub fn get_setting(key: String) -> Result<Option<String>, String> {
+    pub fn get_setting(key: String) -> Result<Option<String>, AppError> {
         let guard = storage::get_database()?;
-        let db = guard.as_ref().ok_or("Database not initialized")?;
-        db.get_setting(&key).map_err(|e| e.to_string())
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+        Ok(db.get_setting(&key)?)
     }
 
     /// Set a setting value
     #[tauri::command]
-    pub fn set_setting(key: String, value: String) -> Result<(), String> {
+    pub fn set_setting(key: String, value: String) -> Result<(), AppError> {
         let guard = storage::get_database()?;
-        let db = guard.as_ref().ok_or("Database not initialized")?;
-        db.set_setting(&key, &value).map_err(|e| e.to_string())
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+        Ok(db.set_setting(&key, &value)?)
+    }
+
+    /// Get recent audit log entries, optionally of one meter only
+    #[tauri::command]
+    pub fn get_audit_log(meter_serial: Option<String>, limit: u32) -> Result<Vec<AuditEntry>, AppError> {
+        let guard = storage::get_database()?;
+        let db = guard.as_ref().ok_or_else(storage::not_initialized)?;
+        Ok(db.get_audit_log(meter_serial.as_deref(), limit)?)
     }
 }
 
@@ -144,8 +177,10 @@ pub fn run() {
             commands::disconnect,
             commands::get_connection_status,
             commands::get_meter_identity,
+            commands::set_language,
             // Reading commands
             commands::read_short,
+            commands::read_packet,
             commands::read_full,
             commands::read_obis,
             commands::read_obis_batch,
@@ -155,11 +190,24 @@ pub fn run() {
             commands::write_obis,
             commands::sync_time,
             commands::end_session,
+            commands::program_tariffs,
+            commands::program_dst,
+            commands::generate_dst_schedule,
+            commands::program_meter,
+            commands::relay_connect,
+            commands::relay_disconnect,
+            commands::change_password,
             // Session file commands
             commands::sessions::save_session_file,
             commands::sessions::list_session_files,
             commands::sessions::load_session_file,
             commands::sessions::delete_session_file,
+            // Capture commands
+            commands::captures::start_capture,
+            commands::captures::stop_capture,
+            commands::captures::get_capture_status,
+            // Operation commands
+            commands::operations::cancel_operation,
             // Database commands
             db_commands::save_session,
             db_commands::get_session,
@@ -169,6 +217,8 @@ pub fn run() {
             db_commands::get_recent_reports,
             db_commands::get_setting,
             db_commands::set_setting,
+            db_commands::get_audit_log,
+            commands::audit::export_audit_log,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,80 @@
+//! Demand reset history
+//!
+//! Each demand reset closes a billing period: `0.1.2*N` is the time of the
+//! reset and `1.6.0*N` the maximum demand of the period it closed.
+
+use super::{count, format_time, split_period};
+use crate::serial::iec62056::ObisDataItem;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Maximum demand of one closed billing period
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandPeriod {
+    /// Billing period, 1 is the most recent
+    pub period: u8,
+    /// Time of the reset that closed the period, empty if unknown
+    pub reset_time: String,
+    /// kW
+    pub max_demand: Option<f64>,
+    pub max_demand_time: String,
+}
+
+/// Demand resets kept by the meter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandResetHistory {
+    /// Total number of resets (`0.1.0`)
+    pub count: Option<u32>,
+    /// Most recent period first
+    pub periods: Vec<DemandPeriod>,
+}
+
+impl DemandResetHistory {
+    pub fn parse(items: &[ObisDataItem]) -> Self {
+        let mut periods: BTreeMap<u8, DemandPeriod> = BTreeMap::new();
+        for item in items {
+            let Some((code, period)) = split_period(item) else { continue };
+            let code = code.to_string();
+            if code != "0.1.2" && code != "1.6.0" {
+                continue;
+            }
+            let entry = periods.entry(period).or_insert_with(|| DemandPeriod { period, ..Default::default() });
+            if code == "0.1.2" {
+                entry.reset_time = format_time(&item.parsed);
+            } else {
+                entry.max_demand = item.parsed.as_f64();
+                entry.max_demand_time = format_time(&item.parsed);
+            }
+        }
+        Self { count: count(items, "0.1.0"), periods: periods.into_values().collect() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::iec62056::parse_data_block;
+
+    #[test]
+    fn test_demand_reset_history() {
+        let items = parse_data_block(
+            "0.1.0(2)\r\n\
+             0.1.2*2(24-05-01,00:00)\r\n\
+             0.1.2*1(24-06-01,00:00)\r\n\
+             1.6.0*1(000.850*kW)(24-05-14,18:30)\r\n\
+             1.6.0*3(001.200*kW)(24-03-02,09:15)\r\n",
+        );
+        let history = DemandResetHistory::parse(&items);
+
+        assert_eq!(history.count, Some(2));
+        let periods: Vec<u8> = history.periods.iter().map(|p| p.period).collect();
+        assert_eq!(periods, vec![1, 2, 3]);
+        assert_eq!(history.periods[0].reset_time, "2024-06-01 00:00");
+        assert_eq!(history.periods[0].max_demand, Some(0.85));
+        assert_eq!(history.periods[0].max_demand_time, "2024-05-14 18:30");
+        assert_eq!(history.periods[1].max_demand, None);
+        assert_eq!(history.periods[2].reset_time, "");
+    }
+}
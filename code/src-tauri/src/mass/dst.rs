@@ -0,0 +1,344 @@
+//! Daylight saving time settings
+//!
+//! `96.90.0` enables automatic DST and `96.90.1` to `96.90.12` hold one
+//! period each as `offset,yy-mm-dd,hh:mm;yy-mm-dd,hh:mm`: the clock offset,
+//! when it is applied and when it is taken back.
+
+use super::{find, TIME_FORMAT};
+use crate::error::AppError;
+use crate::serial::iec62056::ObisDataItem;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+/// Number of DST periods a MASS meter keeps
+pub const DST_PERIODS: u8 = 12;
+
+/// Value of a period slot that is not used
+const UNUSED_PERIOD: &str = "+00:00,00-00-00,00:00;00-00-00,00:00";
+
+/// Largest clock offset accepted for a period, in minutes
+const MAX_OFFSET_MINUTES: u32 = 12 * 60;
+
+/// One DST period (`96.90.N`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DstPeriod {
+    /// Period number, 1 to 12
+    pub id: u8,
+    /// Clock offset as the meter sends it, e.g. `+01:00`
+    pub offset: String,
+    /// Time the offset is applied, empty if unset
+    pub forward: String,
+    /// Time the offset is taken back, empty if unset
+    pub backward: String,
+}
+
+/// DST settings of the meter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DstSchedule {
+    pub enabled: bool,
+    pub periods: Vec<DstPeriod>,
+}
+
+impl DstPeriod {
+    /// A period with neither switch time set leaves its slot unused
+    pub fn is_used(&self) -> bool {
+        !self.forward.is_empty() || !self.backward.is_empty()
+    }
+
+    /// `offset,yy-mm-dd,hh:mm;yy-mm-dd,hh:mm` value of the period
+    pub fn encode(&self) -> String {
+        if !self.is_used() {
+            return UNUSED_PERIOD.to_string();
+        }
+        format!("{},{};{}", self.offset, encode_switch(&self.forward), encode_switch(&self.backward))
+    }
+}
+
+/// How the periods of a schedule are generated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum DstRule {
+    /// No DST; Turkey has stayed on UTC+3 all year since 2016
+    Disabled,
+    /// Forward on the last Sunday of `forward_month` at `forward_hour`, back on
+    /// the last Sunday of `backward_month` at `backward_hour` (local time)
+    LastSunday {
+        offset: String,
+        forward_month: u32,
+        forward_hour: u32,
+        backward_month: u32,
+        backward_hour: u32,
+    },
+}
+
+impl DstRule {
+    /// Last Sunday of March 03:00 to last Sunday of October 04:00, one hour
+    /// forward: the EU rule in local time, used in Turkey until 2016
+    pub fn march_october() -> Self {
+        DstRule::LastSunday {
+            offset: "+01:00".to_string(),
+            forward_month: 3,
+            forward_hour: 3,
+            backward_month: 10,
+            backward_hour: 4,
+        }
+    }
+}
+
+impl DstSchedule {
+    /// DST settings, `None` if the readout has no `96.90.0`
+    pub fn parse(items: &[ObisDataItem]) -> Option<Self> {
+        let enabled = find(items, "96.90.0")?.value.trim() != "0";
+        let periods = (1..=DST_PERIODS)
+            .filter_map(|id| parse_period(id, &find(items, &format!("96.90.{}", id))?.value))
+            .collect();
+        Some(Self { enabled, periods })
+    }
+
+    /// Schedule of `rule` with one period per year from `first_year`, filling all 12 slots
+    pub fn generate(rule: &DstRule, first_year: i32) -> Result<Self, AppError> {
+        let DstRule::LastSunday { offset, forward_month, forward_hour, backward_month, backward_hour } = rule else {
+            return Ok(Self { enabled: false, periods: Vec::new() });
+        };
+        let invalid = || AppError::invalid_value("reason_dst_rule", format!("{:?}", rule));
+        if ![forward_month, backward_month].iter().all(|month| (1..=12).contains(*month)) {
+            return Err(invalid());
+        }
+
+        let mut periods = Vec::with_capacity(DST_PERIODS as usize);
+        for id in 1..=DST_PERIODS {
+            let year = first_year + i32::from(id) - 1;
+            // A period that starts late in the year ends in the next one (southern hemisphere)
+            let backward_year = if backward_month < forward_month { year + 1 } else { year };
+            let forward = last_sunday(year, *forward_month, *forward_hour).ok_or_else(invalid)?;
+            let backward = last_sunday(backward_year, *backward_month, *backward_hour).ok_or_else(invalid)?;
+            periods.push(DstPeriod {
+                id,
+                offset: offset.clone(),
+                forward: forward.format(TIME_FORMAT).to_string(),
+                backward: backward.format(TIME_FORMAT).to_string(),
+            });
+        }
+
+        let schedule = Self { enabled: true, periods };
+        schedule.validate()?;
+        Ok(schedule)
+    }
+
+    /// Check the periods: ids 1 to 12 without repeats, `+hh:mm` offsets and
+    /// for every used period a forward time before its backward time, each
+    /// period after the previous one
+    pub fn validate(&self) -> Result<(), AppError> {
+        let mut ids: Vec<u8> = self.periods.iter().map(|p| p.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.len() != self.periods.len() || ids.iter().any(|id| !(1..=DST_PERIODS).contains(id)) {
+            return Err(AppError::invalid_input("reason_dst_period_ids"));
+        }
+
+        let mut used: Vec<&DstPeriod> = self.periods.iter().filter(|p| p.is_used()).collect();
+        used.sort_by_key(|p| p.id);
+        if self.enabled && used.is_empty() {
+            return Err(AppError::invalid_input("reason_dst_no_periods"));
+        }
+
+        let mut previous_backward: Option<NaiveDateTime> = None;
+        for period in used {
+            if !valid_offset(&period.offset) {
+                return Err(AppError::invalid_value("reason_dst_offset", format!("{}: {}", period.id, period.offset)));
+            }
+            let parse = |value: &str| NaiveDateTime::parse_from_str(value.trim(), TIME_FORMAT).ok();
+            let (Some(forward), Some(backward)) = (parse(&period.forward), parse(&period.backward)) else {
+                return Err(AppError::invalid_value("reason_dst_time_format", period.id));
+            };
+            if forward >= backward {
+                return Err(AppError::invalid_value("reason_dst_order", period.id));
+            }
+            if previous_backward.is_some_and(|previous| forward < previous) {
+                return Err(AppError::invalid_value("reason_dst_overlap", period.id));
+            }
+            previous_backward = Some(backward);
+        }
+        Ok(())
+    }
+
+    /// OBIS code and MASS value of the enable flag and all 12 period slots, in write order
+    ///
+    /// Slots without a period are written as unused, so no old period stays active.
+    pub fn tables(&self) -> Vec<(String, String)> {
+        let enabled = if self.enabled { "1" } else { "0" };
+        std::iter::once(("96.90.0".to_string(), enabled.to_string()))
+            .chain((1..=DST_PERIODS).map(|id| {
+                let value = self.periods.iter()
+                    .find(|p| p.id == id)
+                    .map_or_else(|| UNUSED_PERIOD.to_string(), DstPeriod::encode);
+                (format!("96.90.{}", id), value)
+            }))
+            .collect()
+    }
+}
+
+/// Last Sunday of `month` at `hour`
+fn last_sunday(year: i32, month: u32, hour: u32) -> Option<NaiveDateTime> {
+    let next_month = if month == 12 { NaiveDate::from_ymd_opt(year + 1, 1, 1) } else { NaiveDate::from_ymd_opt(year, month + 1, 1) }?;
+    let last_day = next_month - Duration::days(1);
+    let sunday = last_day - Duration::days(i64::from(last_day.weekday().num_days_from_sunday()));
+    sunday.and_hms_opt(hour, 0, 0)
+}
+
+/// `+hh:mm` or `-hh:mm`, at most 12 hours
+fn valid_offset(offset: &str) -> bool {
+    let Some(rest) = offset.strip_prefix('+').or_else(|| offset.strip_prefix('-')) else { return false };
+    let Some((hours, minutes)) = rest.split_once(':') else { return false };
+    if hours.len() != 2 || minutes.len() != 2 {
+        return false;
+    }
+    match (hours.parse::<u32>(), minutes.parse::<u32>()) {
+        (Ok(hours), Ok(minutes)) => minutes < 60 && hours * 60 + minutes <= MAX_OFFSET_MINUTES,
+        _ => false,
+    }
+}
+
+/// `2025-03-30 03:00` → `25-03-30,03:00`, unset for an empty or invalid time
+fn encode_switch(value: &str) -> String {
+    NaiveDateTime::parse_from_str(value.trim(), TIME_FORMAT)
+        .map(|time| time.format("%y-%m-%d,%H:%M").to_string())
+        .unwrap_or_else(|_| "00-00-00,00:00".to_string())
+}
+
+fn parse_period(id: u8, value: &str) -> Option<DstPeriod> {
+    let (forward, backward) = value.split_once(';')?;
+    let (offset, forward) = forward.split_once(',')?;
+    Some(DstPeriod {
+        id,
+        offset: offset.trim().to_string(),
+        forward: format_switch(forward),
+        backward: format_switch(backward),
+    })
+}
+
+/// `yy-mm-dd,hh:mm` in `TIME_FORMAT`, empty for `00-00-00,00:00` and invalid dates
+fn format_switch(value: &str) -> String {
+    NaiveDateTime::parse_from_str(value.trim(), "%y-%m-%d,%H:%M")
+        .map(|time| time.format(TIME_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::iec62056::parse_data_block;
+
+    #[test]
+    fn test_dst_schedule() {
+        let items = parse_data_block(
+            "96.90.0(1)\r\n\
+             96.90.1(+01:00,25-03-30,03:00;25-10-26,04:00)\r\n\
+             96.90.2(+01:00,26-03-29,03:00;00-00-00,00:00)\r\n\
+             96.90.12(+00:00,00-00-00,00:00;00-00-00,00:00)\r\n",
+        );
+        let dst = DstSchedule::parse(&items).unwrap();
+
+        assert!(dst.enabled);
+        assert_eq!(dst.periods.len(), 3);
+        assert_eq!(dst.periods[0], DstPeriod {
+            id: 1,
+            offset: "+01:00".to_string(),
+            forward: "2025-03-30 03:00".to_string(),
+            backward: "2025-10-26 04:00".to_string(),
+        });
+        assert_eq!(dst.periods[1].backward, "");
+        assert_eq!(dst.periods[2].id, 12);
+        assert_eq!(dst.periods[2].forward, "");
+    }
+
+    #[test]
+    fn test_dst_encode() {
+        let period = DstPeriod {
+            id: 1,
+            offset: "+01:00".to_string(),
+            forward: "2025-03-30 03:00".to_string(),
+            backward: "2025-10-26 04:00".to_string(),
+        };
+        assert_eq!(period.encode(), "+01:00,25-03-30,03:00;25-10-26,04:00");
+        assert_eq!(parse_period(1, &period.encode()), Some(period.clone()));
+
+        let tables = DstSchedule { enabled: true, periods: vec![period] }.tables();
+        assert_eq!(tables.len(), 1 + DST_PERIODS as usize);
+        assert_eq!(tables[0], ("96.90.0".to_string(), "1".to_string()));
+        assert_eq!(tables[1].1, "+01:00,25-03-30,03:00;25-10-26,04:00");
+        assert_eq!(tables[12], ("96.90.12".to_string(), UNUSED_PERIOD.to_string()));
+    }
+
+    #[test]
+    fn test_dst_generate() {
+        let dst = DstSchedule::generate(&DstRule::march_october(), 2026).unwrap();
+        assert!(dst.enabled);
+        assert_eq!(dst.periods.len(), DST_PERIODS as usize);
+        assert_eq!(dst.periods[0].forward, "2026-03-29 03:00");
+        assert_eq!(dst.periods[0].backward, "2026-10-25 04:00");
+        assert_eq!(dst.periods[11].id, 12);
+        assert_eq!(dst.periods[11].forward, "2037-03-29 03:00");
+
+        let southern = DstRule::LastSunday {
+            offset: "+01:00".to_string(),
+            forward_month: 10,
+            forward_hour: 2,
+            backward_month: 3,
+            backward_hour: 3,
+        };
+        let dst = DstSchedule::generate(&southern, 2026).unwrap();
+        assert_eq!(dst.periods[0].backward, "2027-03-28 03:00");
+
+        let dst = DstSchedule::generate(&DstRule::Disabled, 2026).unwrap();
+        assert!(!dst.enabled);
+        assert!(dst.periods.is_empty());
+        assert!(DstSchedule::generate(&DstRule::LastSunday {
+            offset: "+01:00".to_string(),
+            forward_month: 13,
+            forward_hour: 3,
+            backward_month: 10,
+            backward_hour: 4,
+        }, 2026).is_err());
+    }
+
+    #[test]
+    fn test_dst_validate() {
+        let period = |id: u8, offset: &str, forward: &str, backward: &str| DstPeriod {
+            id,
+            offset: offset.to_string(),
+            forward: forward.to_string(),
+            backward: backward.to_string(),
+        };
+        let valid = period(1, "+01:00", "2026-03-29 03:00", "2026-10-25 04:00");
+        let schedule = |periods: Vec<DstPeriod>| DstSchedule { enabled: true, periods };
+
+        assert!(schedule(vec![valid.clone(), period(2, "", "", "")]).validate().is_ok());
+        assert!(DstSchedule { enabled: false, periods: vec![] }.validate().is_ok());
+        for invalid in [
+            vec![],
+            vec![valid.clone(), valid.clone()],
+            vec![period(13, "+01:00", "2026-03-29 03:00", "2026-10-25 04:00")],
+            vec![period(1, "01:00", "2026-03-29 03:00", "2026-10-25 04:00")],
+            vec![period(1, "+13:00", "2026-03-29 03:00", "2026-10-25 04:00")],
+            vec![period(1, "+01:00", "2026-03-29 03:00", "")],
+            vec![period(1, "+01:00", "2026-10-25 04:00", "2026-03-29 03:00")],
+            vec![valid.clone(), period(2, "+01:00", "2026-10-01 03:00", "2027-03-01 03:00")],
+        ] {
+            assert!(matches!(schedule(invalid).validate(), Err(AppError::InvalidInput { .. })));
+        }
+    }
+
+    #[test]
+    fn test_dst_schedule_missing() {
+        let items = parse_data_block("0.0.0(12345678)\r\n96.90.1(+01:00,25-03-30,03:00;25-10-26,04:00)\r\n");
+        assert!(DstSchedule::parse(&items).is_none());
+
+        let items = parse_data_block("96.90.0(0)\r\n");
+        let dst = DstSchedule::parse(&items).unwrap();
+        assert!(!dst.enabled);
+        assert!(dst.periods.is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+//! Outage and warning logs
+//!
+//! Each log is a counter (`96.7.0`, `96.7.4`, ...) and the records of the
+//! last events in the billing periods of another code (`96.7.10*N`,
+//! `96.77.4*N`, ...), each a `start;end` interval.
+
+use super::{count, format_time, split_period, value, TIME_FORMAT};
+use crate::serial::iec62056::ObisDataItem;
+use serde::{Deserialize, Serialize};
+
+/// Outage or warning record (`96.7.10*N(start;end)`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventRecord {
+    /// Record number, the billing period group of the OBIS code
+    pub index: u8,
+    pub start: String,
+    /// Empty while the event is still ongoing
+    pub end: String,
+    pub duration_minutes: Option<i64>,
+    /// Record came from a block that failed BCC even after retransmission
+    #[serde(default)]
+    pub untrusted: bool,
+}
+
+/// Event counter with the records the meter keeps for it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventLog {
+    /// Total number of events counted by the meter, may exceed `records`
+    pub count: u32,
+    pub records: Vec<EventRecord>,
+}
+
+impl EventLog {
+    /// Counter `count_code` with the records of `record_code*N`
+    ///
+    /// Unused record slots (`00-00-00,00:00` start) are skipped.
+    pub fn parse(items: &[ObisDataItem], count_code: &str, record_code: &str) -> Self {
+        let records = items.iter()
+            .filter_map(|item| {
+                let (code, index) = split_period(item)?;
+                if code.to_string() != record_code {
+                    return None;
+                }
+                let (start, end) = item.parsed.as_interval()?;
+                let start_time = start.as_datetime()?;
+                let end_time = end.as_datetime();
+                Some(EventRecord {
+                    index,
+                    start: start_time.format(TIME_FORMAT).to_string(),
+                    end: format_time(end),
+                    duration_minutes: end_time.map(|end| (end - start_time).num_minutes()),
+                    untrusted: item.untrusted,
+                })
+            })
+            .collect();
+        Self { count: count(items, count_code).unwrap_or(0), records }
+    }
+}
+
+/// Outage log: three-phase outages and outages of each phase
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutageRecords {
+    pub three_phase: EventLog,
+    pub phase_l1: EventLog,
+    pub phase_l2: EventLog,
+    pub phase_l3: EventLog,
+}
+
+impl OutageRecords {
+    pub fn parse(items: &[ObisDataItem]) -> Self {
+        Self {
+            three_phase: EventLog::parse(items, "96.7.0", "96.7.10"),
+            phase_l1: EventLog::parse(items, "96.77.1", "96.77.10"),
+            phase_l2: EventLog::parse(items, "96.77.2", "96.77.20"),
+            phase_l3: EventLog::parse(items, "96.77.3", "96.77.30"),
+        }
+    }
+}
+
+/// Terminal cover openings counted in one month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyCount {
+    pub month: u8,
+    pub count: u32,
+}
+
+/// Warning log: supply warnings, cover openings and tariff changes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarningRecords {
+    pub voltage: EventLog,
+    pub current: EventLog,
+    pub magnetic: EventLog,
+    pub top_cover_count: u32,
+    pub terminal_cover: Vec<MonthlyCount>,
+    /// Times of the last tariff table changes
+    pub tariff_changes: Vec<String>,
+    pub ff_code: String,
+    pub gf_code: String,
+}
+
+impl WarningRecords {
+    pub fn parse(items: &[ObisDataItem]) -> Self {
+        let terminal_cover = (1..=12)
+            .filter_map(|month| {
+                let count = count(items, &format!("96.71.0*{}", month))?;
+                Some(MonthlyCount { month, count })
+            })
+            .collect();
+
+        let tariff_changes = items.iter()
+            .filter(|item| split_period(item).is_some_and(|(code, _)| code.to_string() == "96.2.2"))
+            .filter_map(|item| item.parsed.as_datetime())
+            .map(|time| time.format(TIME_FORMAT).to_string())
+            .collect();
+
+        Self {
+            voltage: EventLog::parse(items, "96.7.4", "96.77.4"),
+            current: EventLog::parse(items, "96.7.5", "96.77.5"),
+            magnetic: EventLog::parse(items, "96.7.6", "96.77.6"),
+            top_cover_count: count(items, "96.70.0").unwrap_or(0),
+            terminal_cover,
+            tariff_changes,
+            ff_code: value(items, "F.F.0"),
+            gf_code: value(items, "F.F.1"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::iec62056::parse_data_block;
+
+    #[test]
+    fn test_outage_records() {
+        let items = parse_data_block(
+            "96.7.0(3)\r\n\
+             96.7.10*1(24-06-02,03:10;24-06-02,03:42)\r\n\
+             96.7.10*2(24-05-20,22:00;24-05-21,01:15)\r\n\
+             96.7.10*3(00-00-00,00:00;00-00-00,00:00)\r\n\
+             96.77.2(1)\r\n\
+             96.77.20*1(24-06-10,08:00;00-00-00,00:00)\r\n",
+        );
+        let outages = OutageRecords::parse(&items);
+
+        assert_eq!(outages.three_phase.count, 3);
+        assert_eq!(outages.three_phase.records.len(), 2);
+        let record = &outages.three_phase.records[1];
+        assert_eq!(record.index, 2);
+        assert_eq!(record.start, "2024-05-20 22:00");
+        assert_eq!(record.end, "2024-05-21 01:15");
+        assert_eq!(record.duration_minutes, Some(195));
+
+        assert_eq!(outages.phase_l1.count, 0);
+        assert!(outages.phase_l1.records.is_empty());
+        let ongoing = &outages.phase_l2.records[0];
+        assert_eq!(ongoing.end, "");
+        assert_eq!(ongoing.duration_minutes, None);
+    }
+
+    #[test]
+    fn test_warning_records() {
+        let items = parse_data_block(
+            "F.F(00000000)\r\n\
+             96.7.4(1)\r\n\
+             96.77.4*1(24-06-01,10:00;24-06-01,10:05)\r\n\
+             96.7.6(0)\r\n\
+             96.70(4)\r\n\
+             96.71*1(2)\r\n\
+             96.71*3(1)\r\n\
+             96.2.2*1(24-01-01,00:00)\r\n\
+             96.2.2*2(00-00-00,00:00)\r\n",
+        );
+        let warnings = WarningRecords::parse(&items);
+
+        assert_eq!(warnings.ff_code, "00000000");
+        assert_eq!(warnings.voltage.count, 1);
+        assert_eq!(warnings.voltage.records[0].duration_minutes, Some(5));
+        assert_eq!(warnings.magnetic.count, 0);
+        assert_eq!(warnings.top_cover_count, 4);
+        let months: Vec<(u8, u32)> = warnings.terminal_cover.iter().map(|m| (m.month, m.count)).collect();
+        assert_eq!(months, vec![(1, 2), (3, 1)]);
+        assert_eq!(warnings.tariff_changes, vec!["2024-01-01 00:00"]);
+    }
+}
@@ -0,0 +1,80 @@
+//! MASS readout content
+//!
+//! Turkish MASS meters keep their event logs, DST and tariff tables and
+//! demand reset history in `96.x` codes and billing periods (`C.D.E*N`).
+//! This module turns the parsed OBIS items of a readout or packet into
+//! typed structures, so the pages, exports and the `test_read` CLI share
+//! one parser instead of matching raw text each.
+
+mod demand;
+mod dst;
+mod events;
+mod tariff;
+
+pub use demand::*;
+pub use dst::*;
+pub use events::*;
+pub use tariff::*;
+
+use crate::serial::iec62056::ObisDataItem;
+use crate::serial::{ObisCode, ObisValue};
+use serde::{Deserialize, Serialize};
+
+/// Display format of record times
+pub(crate) const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Everything this module knows how to read from a readout
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MassData {
+    pub outages: OutageRecords,
+    pub warnings: WarningRecords,
+    /// `None` when the readout has no DST settings
+    pub dst: Option<DstSchedule>,
+    /// `None` when the readout has no tariff table
+    pub tariffs: Option<TariffSchedule>,
+    pub demand_resets: DemandResetHistory,
+}
+
+impl MassData {
+    pub fn parse(items: &[ObisDataItem]) -> Self {
+        Self {
+            outages: OutageRecords::parse(items),
+            warnings: WarningRecords::parse(items),
+            dst: DstSchedule::parse(items),
+            tariffs: TariffSchedule::parse(items),
+            demand_resets: DemandResetHistory::parse(items),
+        }
+    }
+}
+
+/// Reduced code without its billing period, and the period, of a `C.D.E*N` item
+pub(crate) fn split_period(item: &ObisDataItem) -> Option<(ObisCode, u8)> {
+    let code: ObisCode = item.code.parse().ok()?;
+    let period = code.billing_period()?;
+    Some((ObisCode { f: None, ..code.reduced() }, period))
+}
+
+/// Item of `code`, in any notation
+pub(crate) fn find<'a>(items: &'a [ObisDataItem], code: &str) -> Option<&'a ObisDataItem> {
+    let code: ObisCode = code.parse().ok()?;
+    items.iter().find(|item| item.matches(&code))
+}
+
+/// Raw value of `code`, empty if missing
+pub(crate) fn value(items: &[ObisDataItem], code: &str) -> String {
+    find(items, code).map(|item| item.value.clone()).unwrap_or_default()
+}
+
+pub(crate) fn number(items: &[ObisDataItem], code: &str) -> Option<f64> {
+    find(items, code).and_then(|item| item.parsed.as_f64())
+}
+
+pub(crate) fn count(items: &[ObisDataItem], code: &str) -> Option<u32> {
+    find(items, code).and_then(|item| item.value.trim().parse().ok())
+}
+
+/// Date-time in `TIME_FORMAT`, empty for unset dates
+pub(crate) fn format_time(value: &ObisValue) -> String {
+    value.as_datetime().map(|time| time.format(TIME_FORMAT).to_string()).unwrap_or_default()
+}
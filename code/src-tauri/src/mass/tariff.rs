@@ -0,0 +1,224 @@
+//! Time-of-use tariff tables
+//!
+//! Each day type has a switching time table (`96.50` weekdays, `96.51`
+//! Saturday, `96.52` Sunday) of `HHMM` groups padded with `9999`, and a
+//! tariff table (`96.60` to `96.62`) with one digit per switching time,
+//! padded with `0`.
+
+use super::find;
+use crate::error::AppError;
+use crate::serial::iec62056::ObisDataItem;
+use serde::{Deserialize, Serialize};
+
+/// Switching times a day type can hold
+pub const MAX_TARIFF_SLOTS: usize = 8;
+
+/// Highest tariff number (T1 to T4)
+pub const MAX_TARIFF: u8 = 4;
+
+/// Start of one tariff period of a day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TariffSlot {
+    /// `HH:MM`
+    pub start: String,
+    /// Tariff number, 1 to 4
+    pub tariff: u8,
+}
+
+/// Tariff periods of each day type, in switching order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TariffSchedule {
+    pub weekdays: Vec<TariffSlot>,
+    pub saturday: Vec<TariffSlot>,
+    pub sunday: Vec<TariffSlot>,
+}
+
+impl TariffSchedule {
+    /// Tariff tables, `None` if the readout has no periods for any day type
+    pub fn parse(items: &[ObisDataItem]) -> Option<Self> {
+        let day_slots = |times_code: &str, tariffs_code: &str| match (find(items, times_code), find(items, tariffs_code)) {
+            (Some(times), Some(tariffs)) => decode_day(&times.value, &tariffs.value),
+            _ => Vec::new(),
+        };
+        let schedule = Self {
+            weekdays: day_slots("96.50", "96.60"),
+            saturday: day_slots("96.51", "96.61"),
+            sunday: day_slots("96.52", "96.62"),
+        };
+        let empty = schedule.weekdays.is_empty() && schedule.saturday.is_empty() && schedule.sunday.is_empty();
+        (!empty).then_some(schedule)
+    }
+
+    /// Check every day type: 1 to 8 slots, valid `HH:MM` starts in ascending
+    /// order and tariffs 1 to 4
+    pub fn validate(&self) -> Result<(), AppError> {
+        for (slots, times_code, _) in self.days() {
+            validate_day(times_code, slots)?;
+        }
+        Ok(())
+    }
+
+    /// OBIS code and MASS value of the six tables, in write order
+    pub fn tables(&self) -> Vec<(String, String)> {
+        self.days()
+            .into_iter()
+            .flat_map(|(slots, times_code, tariffs_code)| {
+                [
+                    (times_code.to_string(), encode_switching_times(slots)),
+                    (tariffs_code.to_string(), encode_tariff_numbers(slots)),
+                ]
+            })
+            .collect()
+    }
+
+    /// Slots, switching time code and tariff code of each day type:
+    /// weekdays, Saturday, Sunday
+    fn days(&self) -> [(&[TariffSlot], &'static str, &'static str); 3] {
+        [
+            (&self.weekdays, "96.50", "96.60"),
+            (&self.saturday, "96.51", "96.61"),
+            (&self.sunday, "96.52", "96.62"),
+        ]
+    }
+}
+
+/// Check the slots of the day type whose switching times go to `times_code`
+fn validate_day(times_code: &str, slots: &[TariffSlot]) -> Result<(), AppError> {
+    if slots.is_empty() {
+        return Err(AppError::invalid_value("reason_tariff_no_slots", times_code));
+    }
+    if slots.len() > MAX_TARIFF_SLOTS {
+        return Err(AppError::invalid_value("reason_tariff_too_many_slots", format!("{}: {}", times_code, slots.len())));
+    }
+    let mut previous: Option<u32> = None;
+    for slot in slots {
+        let minutes = slot_minutes(&slot.start)
+            .ok_or_else(|| AppError::invalid_value("reason_tariff_invalid_time", format!("{}: {}", times_code, slot.start)))?;
+        if previous.is_some_and(|previous| minutes <= previous) {
+            return Err(AppError::invalid_value("reason_tariff_time_order", format!("{}: {}", times_code, slot.start)));
+        }
+        if !(1..=MAX_TARIFF).contains(&slot.tariff) {
+            return Err(AppError::invalid_value("reason_tariff_number", format!("{}: T{}", times_code, slot.tariff)));
+        }
+        previous = Some(minutes);
+    }
+    Ok(())
+}
+
+/// Minutes after midnight of an `HH:MM` start
+fn slot_minutes(start: &str) -> Option<u32> {
+    let (hours, minutes) = start.split_once(':')?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return None;
+    }
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Slots of one day type from its switching time and tariff tables
+pub fn decode_day(times: &str, tariffs: &str) -> Vec<TariffSlot> {
+    decode_switching_times(times)
+        .into_iter()
+        .zip(decode_tariff_numbers(tariffs))
+        .map(|(start, tariff)| TariffSlot { start, tariff })
+        .collect()
+}
+
+/// `["00:00", "06:00"]` → `0000060099999999...`, padded to 8 groups
+pub fn encode_switching_times(slots: &[TariffSlot]) -> String {
+    (0..MAX_TARIFF_SLOTS)
+        .map(|i| slots.get(i).map_or_else(|| "9999".to_string(), |slot| slot.start.replace(':', "")))
+        .collect()
+}
+
+/// `[3, 1]` → `31000000`, padded to 8 digits
+pub fn encode_tariff_numbers(slots: &[TariffSlot]) -> String {
+    (0..MAX_TARIFF_SLOTS)
+        .map(|i| slots.get(i).map_or('0', |slot| char::from(b'0' + slot.tariff)))
+        .collect()
+}
+
+/// `00000600170022009999` → `["00:00", "06:00", "17:00", "22:00"]`
+pub fn decode_switching_times(value: &str) -> Vec<String> {
+    value.trim().as_bytes()
+        .chunks(4)
+        .map(|chunk| String::from_utf8_lossy(chunk))
+        .take_while(|chunk| chunk.len() == 4 && chunk != "9999")
+        .map(|chunk| format!("{}:{}", &chunk[..2], &chunk[2..]))
+        .collect()
+}
+
+/// `31230000` → `[3, 1, 2, 3]`
+pub fn decode_tariff_numbers(value: &str) -> Vec<u8> {
+    value.trim().chars()
+        .map_while(|c| c.to_digit(10).filter(|&digit| digit != 0))
+        .map(|digit| digit as u8)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::iec62056::parse_data_block;
+
+    #[test]
+    fn test_tariff_schedule() {
+        let items = parse_data_block(
+            "96.50(00000600170022009999999999999999)\r\n\
+             96.60(31230000)\r\n\
+             96.51(00009999999999999999999999999999)\r\n\
+             96.61(30000000)\r\n\
+             96.52(99999999999999999999999999999999)\r\n\
+             96.62(00000000)\r\n",
+        );
+        let tariffs = TariffSchedule::parse(&items).unwrap();
+
+        let weekdays: Vec<(&str, u8)> = tariffs.weekdays.iter().map(|s| (s.start.as_str(), s.tariff)).collect();
+        assert_eq!(weekdays, vec![("00:00", 3), ("06:00", 1), ("17:00", 2), ("22:00", 3)]);
+        assert_eq!(tariffs.saturday, vec![TariffSlot { start: "00:00".to_string(), tariff: 3 }]);
+        assert!(tariffs.sunday.is_empty());
+    }
+
+    #[test]
+    fn test_tariff_encode() {
+        let slots = decode_day("00000600170022009999999999999999", "31230000");
+        assert_eq!(encode_switching_times(&slots), "00000600170022009999999999999999");
+        assert_eq!(encode_tariff_numbers(&slots), "31230000");
+
+        let schedule = TariffSchedule { weekdays: slots.clone(), saturday: slots.clone(), sunday: slots[..1].to_vec() };
+        let tables = schedule.tables();
+        let codes: Vec<&str> = tables.iter().map(|(code, _)| code.as_str()).collect();
+        assert_eq!(codes, vec!["96.50", "96.60", "96.51", "96.61", "96.52", "96.62"]);
+        assert_eq!(tables[4].1, "00009999999999999999999999999999");
+        assert_eq!(tables[5].1, "30000000");
+    }
+
+    #[test]
+    fn test_tariff_validate() {
+        let slot = |start: &str, tariff: u8| TariffSlot { start: start.to_string(), tariff };
+        let valid = vec![slot("00:00", 3), slot("06:00", 1), slot("17:00", 2)];
+        let schedule = |weekdays: Vec<TariffSlot>| TariffSchedule { weekdays, saturday: valid.clone(), sunday: valid.clone() };
+
+        assert!(schedule(valid.clone()).validate().is_ok());
+        for invalid in [
+            vec![],
+            vec![slot("00:00", 1); MAX_TARIFF_SLOTS + 1],
+            vec![slot("06:00", 1), slot("06:00", 2)],
+            vec![slot("17:00", 1), slot("06:00", 2)],
+            vec![slot("24:00", 1)],
+            vec![slot("6:00", 1)],
+            vec![slot("00:00", 0)],
+            vec![slot("00:00", 5)],
+        ] {
+            assert!(matches!(schedule(invalid).validate(), Err(AppError::InvalidInput { .. })));
+        }
+    }
+
+    #[test]
+    fn test_tariff_schedule_missing() {
+        let items = parse_data_block("96.50(00000600999999999999999999999999)\r\n");
+        assert!(TariffSchedule::parse(&items).is_none());
+    }
+}
@@ -1,15 +1,16 @@
-//! IEC 62056-21 Mode C Protocol Implementation
+//! IEC 62056-21 Protocol Implementation
 //!
-//! This module implements the IEC 62056-21 Mode C protocol for communication
-//! with MASS-compliant electricity meters.
+//! This module implements the IEC 62056-21 protocol for communication with
+//! MASS-compliant electricity meters. Mode C is used for all operations;
+//! older meters in Modes A, B and D are supported for the readout.
 
 #![allow(dead_code)]
 
 #[allow(unused_imports)]
 use std::io::{Read, Write};
-use std::time::Duration;
-use serialport::{DataBits, Parity, StopBits, SerialPort};
+use std::ops::Range;
 use serde::{Deserialize, Serialize};
+use super::obis::{ObisCode, ObisValue};
 
 /// Control characters used in IEC 62056-21 protocol
 pub mod control {
@@ -49,125 +50,143 @@ impl ProtocolMode {
     }
 }
 
+/// IEC 62056-21 protocol mode, told apart by the baud rate character of the
+/// identification message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IecMode {
+    A, // Readout at 300 baud, no baud switch, no option select
+    B, // Baud switch to the rate announced with 'A'-'I', no option select
+    C, // Option select with ACK, baud switch to the acknowledged rate
+    D, // Readout pushed at a fixed 2400 baud, no option select
+}
+
+impl IecMode {
+    /// Detect the mode from the identification baud character, `None` for the
+    /// reserved characters '7'-'9' and 'G'-'I'.
+    /// Mode D also announces '3', so it can only be told from Mode C by configuration.
+    pub fn from_baud_char(c: char) -> Option<Self> {
+        match c {
+            '0'..='6' => Some(IecMode::C),
+            'A'..='F' => Some(IecMode::B),
+            '7'..='9' | 'G'..='I' => None,
+            _ => Some(IecMode::A),
+        }
+    }
+
+    /// Parse a configured mode ("A"-"D"); anything else means auto-detect
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "A" => Some(IecMode::A),
+            "B" => Some(IecMode::B),
+            "C" => Some(IecMode::C),
+            "D" => Some(IecMode::D),
+            _ => None,
+        }
+    }
+
+    /// Only Mode C has the ACK option select (and with it programming mode and packets)
+    pub fn has_option_select(&self) -> bool {
+        *self == IecMode::C
+    }
+
+    pub fn as_char(&self) -> char {
+        match self {
+            IecMode::A => 'A',
+            IecMode::B => 'B',
+            IecMode::C => 'C',
+            IecMode::D => 'D',
+        }
+    }
+}
+
 /// Meter identification information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MeterIdent {
     pub manufacturer: String,
     pub baud_char: char,
+    pub mode: IecMode,
+    /// Characters of the `\W` escape sequences after the baud character
+    pub escapes: Vec<char>,
+    /// MASS generation from the `<n>` marker, empty if absent
     pub generation: String,
     pub edas_id: String,
+    /// Model from the `(...)` group, empty if absent
     pub model: String,
     pub max_baud_rate: u32,
+    /// Identification line as received, without CR LF
+    pub raw: String,
 }
 
-/// OBIS code representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ObisCode {
-    pub a: u8,  // Media
-    pub b: u8,  // Channel
-    pub c: u8,  // Physical quantity
-    pub d: u8,  // Processing type
-    pub e: u8,  // Tariff
-    pub f: u8,  // Billing period
-}
-
-impl ObisCode {
-    pub fn new(a: u8, b: u8, c: u8, d: u8, e: u8, f: u8) -> Self {
-        Self { a, b, c, d, e, f }
+impl MeterIdent {
+    /// The meter announced Mode E (HDLC) with the `\2` escape sequence
+    pub fn supports_mode_e(&self) -> bool {
+        self.escapes.contains(&'2')
     }
 
-    pub fn from_str(s: &str) -> Option<Self> {
-        let parts: Vec<&str> = s.split('.').collect();
-        if parts.len() < 3 {
-            return None;
+    /// Use `mode` for the session instead of the detected one; the maximum
+    /// baud rate follows the mode (2400 in Mode D) where the baud character allows
+    pub fn set_mode(&mut self, mode: IecMode) {
+        self.mode = mode;
+        if let Some(baud) = baud_rate_for_mode(mode, self.baud_char) {
+            self.max_baud_rate = baud;
         }
-
-        Some(Self {
-            a: parts.get(0).and_then(|p| p.parse().ok()).unwrap_or(0),
-            b: parts.get(1).and_then(|p| p.parse().ok()).unwrap_or(0),
-            c: parts.get(2).and_then(|p| p.parse().ok()).unwrap_or(0),
-            d: parts.get(3).and_then(|p| p.parse().ok()).unwrap_or(0),
-            e: parts.get(4).and_then(|p| p.parse().ok()).unwrap_or(0),
-            f: parts.get(5).and_then(|p| p.parse().ok()).unwrap_or(0),
-        })
     }
+}
+
+/// Why an identification message could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentError {
+    /// The message does not start with '/'
+    MissingStart,
+    /// Fewer than the 3 manufacturer characters and the baud character
+    TooShort(usize),
+    /// Manufacturer characters that are not letters or digits
+    InvalidManufacturer(String),
+    /// Baud character with no defined rate (reserved Mode B letters 'G'-'I')
+    ReservedBaudChar(char),
+    /// A '\' at the end of the message with no capability character
+    IncompleteEscape,
+    /// '<' without the closing '>' of the generation marker
+    UnterminatedGeneration,
+    /// '(' without the closing ')' of the model
+    UnterminatedModel,
+}
 
-    pub fn to_string(&self) -> String {
-        if self.f > 0 {
-            format!("{}.{}.{}*{}", self.c, self.d, self.e, self.f)
-        } else if self.e > 0 {
-            format!("{}.{}.{}", self.c, self.d, self.e)
-        } else {
-            format!("{}.{}", self.c, self.d)
+impl std::fmt::Display for IdentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentError::MissingStart => write!(f, "identification does not start with '/'"),
+            IdentError::TooShort(len) => write!(f, "identification too short ({} characters)", len),
+            IdentError::InvalidManufacturer(code) => write!(f, "invalid manufacturer code '{}'", code),
+            IdentError::ReservedBaudChar(c) => write!(f, "baud character '{}' is reserved", c),
+            IdentError::IncompleteEscape => write!(f, "escape sequence '\\' without capability character"),
+            IdentError::UnterminatedGeneration => write!(f, "generation marker '<' is not closed with '>'"),
+            IdentError::UnterminatedModel => write!(f, "model '(' is not closed with ')'"),
         }
     }
 }
 
+impl std::error::Error for IdentError {}
+
 /// Parsed OBIS data item
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObisDataItem {
     pub code: String,
+    /// Raw value of the first group, without unit
     pub value: String,
     pub unit: Option<String>,
+    /// Typed value covering all groups of the line
+    pub parsed: ObisValue,
+    /// The line came from a block that failed BCC even after retransmission
+    #[serde(default)]
+    pub untrusted: bool,
 }
 
-/// Short read data structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ShortReadData {
-    // Meter Identity
-    pub serial_number: String,
-    pub program_version: String,
-    pub production_date: String,
-    pub calibration_date: String,
-
-    // Date/Time
-    pub meter_date: String,
-    pub meter_time: String,
-    pub day_of_week: u8,
-
-    // Active Energy Import
-    pub active_energy_import_total: f64,
-    pub active_energy_import_t1: f64,
-    pub active_energy_import_t2: f64,
-    pub active_energy_import_t3: f64,
-    pub active_energy_import_t4: f64,
-
-    // Active Energy Export (for bidirectional meters)
-    pub active_energy_export_total: Option<f64>,
-    pub active_energy_export_t1: Option<f64>,
-    pub active_energy_export_t2: Option<f64>,
-    pub active_energy_export_t3: Option<f64>,
-    pub active_energy_export_t4: Option<f64>,
-
-    // Reactive Energy (for Kombi meters)
-    pub reactive_inductive_import: Option<f64>,
-    pub reactive_capacitive_import: Option<f64>,
-    pub reactive_inductive_export: Option<f64>,
-    pub reactive_capacitive_export: Option<f64>,
-
-    // Maximum Demand
-    pub max_demand_import: f64,
-    pub max_demand_import_timestamp: String,
-    pub max_demand_export: Option<f64>,
-    pub max_demand_export_timestamp: Option<String>,
-
-    // Instantaneous Values
-    pub voltage_l1: f64,
-    pub voltage_l2: Option<f64>,
-    pub voltage_l3: Option<f64>,
-    pub current_l1: f64,
-    pub current_l2: Option<f64>,
-    pub current_l3: Option<f64>,
-    pub frequency: f64,
-    pub power_factor_l1: f64,
-    pub power_factor_l2: Option<f64>,
-    pub power_factor_l3: Option<f64>,
-
-    // Status Codes
-    pub ff_code: String,
-    pub gf_code: String,
-    pub battery_status: String,
-    pub relay_status: String,
+impl ObisDataItem {
+    /// Whether the item's code denotes the same object as `code`, in any notation
+    pub fn matches(&self, code: &ObisCode) -> bool {
+        self.code.parse::<ObisCode>().is_ok_and(|c| c.canonical_eq(code))
+    }
 }
 
 /// Baud rate character mapping for IEC 62056-21
@@ -184,6 +203,31 @@ pub fn baud_rate_from_char(c: char) -> Option<u32> {
     }
 }
 
+/// Baud rate character mapping for Mode B ('G'-'I' are reserved)
+pub fn baud_rate_from_mode_b_char(c: char) -> Option<u32> {
+    match c {
+        'A' => Some(600),
+        'B' => Some(1200),
+        'C' => Some(2400),
+        'D' => Some(4800),
+        'E' => Some(9600),
+        'F' => Some(19200),
+        _ => None,
+    }
+}
+
+/// Baud rate announced by the identification baud character in the given mode:
+/// the maximum rate in Mode C, the rate switched to in Mode B, the fixed
+/// session rate in Modes A and D
+pub fn baud_rate_for_mode(mode: IecMode, c: char) -> Option<u32> {
+    match mode {
+        IecMode::A => Some(300),
+        IecMode::B => baud_rate_from_mode_b_char(c),
+        IecMode::C => baud_rate_from_char(c),
+        IecMode::D => Some(2400),
+    }
+}
+
 /// Baud rate character for IEC 62056-21
 pub fn char_from_baud_rate(baud: u32) -> Option<char> {
     match baud {
@@ -209,17 +253,6 @@ pub fn verify_bcc(data: &[u8], expected_bcc: u8) -> bool {
     calculate_bcc(data) == expected_bcc
 }
 
-/// Open a serial port with IEC 62056-21 Mode C settings (7E1)
-pub fn open_port(port_name: &str, baud_rate: u32, timeout_ms: u64) -> Result<Box<dyn SerialPort>, String> {
-    serialport::new(port_name, baud_rate)
-        .data_bits(DataBits::Seven)
-        .parity(Parity::Even)
-        .stop_bits(StopBits::One)
-        .timeout(Duration::from_millis(timeout_ms))
-        .open()
-        .map_err(|e| format!("Failed to open port {}: {}", port_name, e))
-}
-
 /// Build the request message for handshake
 /// Format: /?ADDRESS!\r\n or /?!\r\n (if no address)
 pub fn build_request_message(address: Option<&str>) -> Vec<u8> {
@@ -247,12 +280,12 @@ pub fn build_ack_message(mode: ProtocolMode, baud_char: char) -> Vec<u8> {
 }
 
 /// Build password command for programming mode
-/// Format: SOH P1 STX (PASSWORD) ETX BCC
-pub fn build_password_command(password: &str) -> Vec<u8> {
+/// Format: SOH Pn STX (PASSWORD) ETX BCC, `n` the password level (1-3)
+pub fn build_password_command(level: u8, password: &str) -> Vec<u8> {
     let mut msg = Vec::new();
     msg.push(control::SOH);
     msg.push(b'P');
-    msg.push(b'1');
+    msg.push(b'0' + level);
     msg.push(control::STX);
     msg.push(b'(');
     msg.extend_from_slice(password.as_bytes());
@@ -308,6 +341,18 @@ pub fn build_write_command(obis: &str, value: &str) -> Vec<u8> {
     msg
 }
 
+/// Build block acknowledgment, sent after each correctly received partial block
+/// Format: ACK
+pub fn build_block_ack() -> Vec<u8> {
+    vec![control::ACK]
+}
+
+/// Build negative acknowledgment, asks the meter to repeat the last block
+/// Format: NAK
+pub fn build_block_nak() -> Vec<u8> {
+    vec![control::NAK]
+}
+
 /// Build break/logout command
 /// Format: SOH B0 ETX BCC
 pub fn build_break_command() -> Vec<u8> {
@@ -366,73 +411,386 @@ pub fn build_load_profile_command(profile_number: u8, start_time: Option<&str>,
     msg
 }
 
-/// Parse the meter identification message
-/// Format: /XXXZ<generation>YYYYY(MODEL)\r\n
-/// Example: /MKS5<2>ADM(M550.2251)
-pub fn parse_identification(response: &str) -> Option<MeterIdent> {
-    if !response.starts_with('/') {
-        return None;
+/// Default number of NAK retransmission requests for a block with a BCC error
+pub const DEFAULT_BCC_RETRIES: u32 = 3;
+
+/// Longest identification line accepted before the leading '/' is treated as noise
+const MAX_IDENT_LEN: usize = 128;
+
+/// Frame received on the line, as produced by `FrameDecoder`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    /// Request message `/?ADDRESS!` (or its echo), without CR LF
+    Request { address: String },
+    /// Identification message `/XXXZ<ident>`, without CR LF
+    Identification(String),
+    /// Option select `ACK V Z Y CR LF`
+    OptionSelect { protocol: char, baud_char: char, mode: char },
+    Ack,
+    Nak,
+    /// Password request `SOH P0 STX (operand) ETX BCC`
+    PasswordPrompt { data: String, bcc_ok: bool },
+    /// Last (or only) data block `STX data ETX BCC`
+    DataBlock { data: Vec<u8>, bcc_ok: bool },
+    /// Partial data block `STX data EOT BCC`, more blocks follow after ACK
+    PartialBlock { data: Vec<u8>, bcc_ok: bool },
+    /// Break `SOH B0 ETX BCC`
+    Break,
+    /// Any other command message `SOH Cd [STX data] ETX BCC`
+    Command { command: String, data: Vec<u8>, bcc_ok: bool },
+}
+
+impl Frame {
+    /// Name of the kind of frame, for messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Frame::Request { .. } => "Request",
+            Frame::Identification(_) => "Identification",
+            Frame::OptionSelect { .. } => "OptionSelect",
+            Frame::Ack => "ACK",
+            Frame::Nak => "NAK",
+            Frame::PasswordPrompt { .. } => "PasswordPrompt",
+            Frame::DataBlock { .. } => "DataBlock",
+            Frame::PartialBlock { .. } => "PartialBlock",
+            Frame::Break => "Break",
+            Frame::Command { .. } => "Command",
+        }
+    }
+}
+
+/// Outcome of decoding the start of the buffer
+enum Decoded {
+    Frame(Frame, usize),
+    Incomplete,
+    Invalid,
+}
+
+/// Incremental IEC 62056-21 frame decoder
+///
+/// Bytes are pushed as they arrive from the port, in chunks of any size;
+/// complete frames are taken with `next_frame`. Bytes that cannot start a
+/// frame (stray CR LF, line noise) are skipped.
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+    last: Vec<u8>,
+    skipped: usize,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append received bytes
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Append received bytes and take all frames completed by them
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Frame> {
+        self.push(bytes);
+        std::iter::from_fn(|| self.next_frame()).collect()
+    }
+
+    /// Take the next complete frame, if any
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        loop {
+            match self.buf.iter().position(|&b| is_frame_start(b)) {
+                Some(start) => self.skip(start),
+                None => {
+                    self.skip(self.buf.len());
+                    return None;
+                }
+            }
+
+            match decode_frame(&self.buf) {
+                Decoded::Frame(frame, len) => {
+                    self.last = self.buf.drain(..len).collect();
+                    return Some(frame);
+                }
+                Decoded::Incomplete => return None,
+                // Drop the start byte and resynchronize on the next one
+                Decoded::Invalid => self.skip(1),
+            }
+        }
     }
 
-    let content = response.trim_start_matches('/').trim();
+    /// Raw bytes of the last frame returned by `next_frame`, for logging
+    pub fn last_frame_bytes(&self) -> &[u8] {
+        &self.last
+    }
 
-    // Extract manufacturer code (3 chars)
-    if content.len() < 4 {
-        return None;
+    /// Bytes received but not yet decoded into a frame
+    pub fn pending(&self) -> &[u8] {
+        &self.buf
     }
 
-    let manufacturer = content[..3].to_string();
-    let baud_char = content.chars().nth(3)?;
-    let max_baud = baud_rate_from_char(baud_char)?;
+    /// Remove and return the undecoded bytes (e.g. a block cut off by a timeout)
+    pub fn take_pending(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buf)
+    }
 
-    // Find generation marker
-    let gen_start = content.find('<')?;
-    let gen_end = content.find('>')?;
-    let generation = content[gen_start + 1..gen_end].to_string();
+    /// Number of bytes discarded as noise so far
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
 
-    // EDAS ID is after the generation marker
-    let after_gen = &content[gen_end + 1..];
-    let model_start = after_gen.find('(')?;
-    let edas_id = after_gen[..model_start].to_string();
+    pub fn clear(&mut self) {
+        self.buf.clear();
+    }
 
-    // Model is inside parentheses
-    let model_end = after_gen.find(')')?;
-    let model = after_gen[model_start + 1..model_end].to_string();
+    fn skip(&mut self, n: usize) {
+        self.skipped += n;
+        self.buf.drain(..n);
+    }
+}
 
-    Some(MeterIdent {
+fn is_frame_start(b: u8) -> bool {
+    matches!(b, b'/' | control::ACK | control::NAK | control::SOH | control::STX)
+}
+
+fn decode_frame(buf: &[u8]) -> Decoded {
+    match buf[0] {
+        b'/' => decode_line(buf),
+        control::ACK => decode_ack(buf),
+        control::NAK => Decoded::Frame(Frame::Nak, 1),
+        control::STX => decode_block(buf),
+        control::SOH => decode_command(buf),
+        _ => Decoded::Invalid,
+    }
+}
+
+/// `/...CR LF` - request or identification message
+fn decode_line(buf: &[u8]) -> Decoded {
+    for i in 1..buf.len() {
+        if i > MAX_IDENT_LEN {
+            return Decoded::Invalid;
+        }
+        match buf[i] {
+            control::CR => {
+                return match buf.get(i + 1) {
+                    None => Decoded::Incomplete,
+                    Some(&control::LF) => {
+                        let text = String::from_utf8_lossy(&buf[..i]).to_string();
+                        let frame = match text.strip_prefix("/?").and_then(|s| s.strip_suffix('!')) {
+                            Some(address) => Frame::Request { address: address.to_string() },
+                            None => Frame::Identification(text),
+                        };
+                        Decoded::Frame(frame, i + 2)
+                    }
+                    Some(_) => Decoded::Invalid,
+                };
+            }
+            b if b < 0x20 => return Decoded::Invalid,
+            _ => {}
+        }
+    }
+    Decoded::Incomplete
+}
+
+/// Single ACK, or option select `ACK V Z Y CR LF`
+///
+/// A lone ACK is reported as soon as it arrives, so an option select
+/// split right after its first byte decodes as `Ack` plus skipped noise.
+fn decode_ack(buf: &[u8]) -> Decoded {
+    match buf.get(1) {
+        Some(b) if b.is_ascii_digit() => {
+            if buf.len() < 6 {
+                return Decoded::Incomplete;
+            }
+            if buf[4] == control::CR && buf[5] == control::LF {
+                Decoded::Frame(
+                    Frame::OptionSelect {
+                        protocol: buf[1] as char,
+                        baud_char: buf[2] as char,
+                        mode: buf[3] as char,
+                    },
+                    6,
+                )
+            } else {
+                Decoded::Frame(Frame::Ack, 1)
+            }
+        }
+        _ => Decoded::Frame(Frame::Ack, 1),
+    }
+}
+
+/// `STX data ETX|EOT BCC`
+fn decode_block(buf: &[u8]) -> Decoded {
+    for i in 1..buf.len() {
+        match buf[i] {
+            control::ETX | control::EOT => {
+                let Some(&bcc) = buf.get(i + 1) else {
+                    return Decoded::Incomplete;
+                };
+                let data = buf[1..i].to_vec();
+                let bcc_ok = calculate_bcc(&buf[1..=i]) == bcc;
+                let frame = if buf[i] == control::ETX {
+                    Frame::DataBlock { data, bcc_ok }
+                } else {
+                    Frame::PartialBlock { data, bcc_ok }
+                };
+                return Decoded::Frame(frame, i + 2);
+            }
+            // A new frame started before this one ended: the block was cut off
+            control::SOH | control::STX => return Decoded::Invalid,
+            _ => {}
+        }
+    }
+    Decoded::Incomplete
+}
+
+/// `SOH Cd ETX BCC` or `SOH Cd STX data ETX BCC`
+fn decode_command(buf: &[u8]) -> Decoded {
+    if buf.len() < 4 {
+        return Decoded::Incomplete;
+    }
+    if !buf[1].is_ascii_alphanumeric() || !buf[2].is_ascii_alphanumeric() {
+        return Decoded::Invalid;
+    }
+    let command = String::from_utf8_lossy(&buf[1..3]).to_string();
+
+    let (data, etx) = match buf[3] {
+        control::ETX => (Vec::new(), 3),
+        control::STX => {
+            let Some(offset) = buf[4..].iter().position(|&b| matches!(b, control::ETX | control::SOH | control::STX)) else {
+                return Decoded::Incomplete;
+            };
+            let etx = 4 + offset;
+            if buf[etx] != control::ETX {
+                return Decoded::Invalid;
+            }
+            (buf[4..etx].to_vec(), etx)
+        }
+        _ => return Decoded::Invalid,
+    };
+
+    let Some(&bcc) = buf.get(etx + 1) else {
+        return Decoded::Incomplete;
+    };
+    let bcc_ok = calculate_bcc(&buf[1..=etx]) == bcc;
+
+    let frame = match command.as_str() {
+        "B0" => Frame::Break,
+        "P0" => Frame::PasswordPrompt {
+            data: String::from_utf8_lossy(&data).to_string(),
+            bcc_ok,
+        },
+        _ => Frame::Command { command, data, bcc_ok },
+    };
+    Decoded::Frame(frame, etx + 2)
+}
+
+/// Parse the meter identification message
+/// Format: /XXXZ[\W...][<generation>]EDASID[(MODEL)]\r\n
+/// Example: /MKS5<2>ADM(M550.2251)
+/// The protocol mode is detected from the baud character Z. Generation and
+/// model are optional, as are the `\W` enhanced capability sequences
+/// (`\2` announces Mode E).
+pub fn parse_identification(response: &str) -> Result<MeterIdent, IdentError> {
+    let raw = response.trim_end_matches(['\r', '\n']);
+    let content = raw.strip_prefix('/').ok_or(IdentError::MissingStart)?;
+
+    // Manufacturer code (3 chars) and baud character
+    let mut chars = content.chars();
+    let manufacturer: String = chars.by_ref().take(3).collect();
+    let baud_char = chars.next()
+        .ok_or_else(|| IdentError::TooShort(content.chars().count()))?;
+    if !manufacturer.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(IdentError::InvalidManufacturer(manufacturer));
+    }
+
+    let mode = IecMode::from_baud_char(baud_char)
+        .ok_or(IdentError::ReservedBaudChar(baud_char))?;
+    let max_baud = baud_rate_for_mode(mode, baud_char)
+        .ok_or(IdentError::ReservedBaudChar(baud_char))?;
+
+    // Enhanced capability sequences: \W, possibly repeated
+    let mut rest = chars.as_str();
+    let mut escapes = Vec::new();
+    while let Some(after) = rest.strip_prefix('\\') {
+        let mut esc = after.chars();
+        escapes.push(esc.next().ok_or(IdentError::IncompleteEscape)?);
+        rest = esc.as_str();
+    }
+    let rest = rest.trim();
+
+    // Optional generation marker
+    let (generation, rest) = match rest.strip_prefix('<') {
+        Some(after) => {
+            let end = after.find('>').ok_or(IdentError::UnterminatedGeneration)?;
+            (after[..end].to_string(), &after[end + 1..])
+        }
+        None => (String::new(), rest),
+    };
+
+    // EDAS ID, then the optional model inside parentheses
+    let (edas_id, model) = match rest.find('(') {
+        Some(start) => {
+            let len = rest[start + 1..].find(')').ok_or(IdentError::UnterminatedModel)?;
+            (rest[..start].to_string(), rest[start + 1..start + 1 + len].to_string())
+        }
+        None => (rest.to_string(), String::new()),
+    };
+
+    Ok(MeterIdent {
         manufacturer,
         baud_char,
+        mode,
+        escapes,
         generation,
-        edas_id,
+        edas_id: edas_id.trim().to_string(),
         model,
         max_baud_rate: max_baud,
+        raw: raw.to_string(),
     })
 }
 
 /// Parse OBIS data from response
 /// Format: OBIS(value*unit)\r\n or OBIS(value)\r\n
+/// Some lines carry several groups, e.g. max demand: 1.6.0(value*kW)(yy-mm-dd,hh:mm)
 pub fn parse_obis_response(line: &str) -> Option<ObisDataItem> {
     let open_paren = line.find('(')?;
-    let close_paren = line.rfind(')')?;
+    let code = line[..open_paren].trim().to_string();
 
-    if open_paren >= close_paren {
-        return None;
+    // Collect the content of every (...) group after the code
+    let mut groups: Vec<&str> = Vec::new();
+    let mut rest = &line[open_paren..];
+    while let Some(start) = rest.find('(') {
+        let Some(len) = rest[start..].find(')') else {
+            break;
+        };
+        groups.push(&rest[start + 1..start + len]);
+        rest = &rest[start + len + 1..];
     }
 
-    let code = line[..open_paren].trim().to_string();
-    let value_part = &line[open_paren + 1..close_paren];
+    let first = groups.first()?;
 
     // Check for unit separator
-    let (value, unit) = if let Some(star_pos) = value_part.find('*') {
+    let (value, unit) = if let Some(star_pos) = first.find('*') {
         (
-            value_part[..star_pos].to_string(),
-            Some(value_part[star_pos + 1..].to_string()),
+            first[..star_pos].to_string(),
+            Some(first[star_pos + 1..].to_string()),
         )
     } else {
-        (value_part.to_string(), None)
+        (first.to_string(), None)
     };
 
-    Some(ObisDataItem { code, value, unit })
+    let is_status = code.parse::<ObisCode>().is_ok_and(|obis| obis.is_status_register());
+    let parsed = if is_status {
+        ObisValue::parse_status(first)
+    } else {
+        ObisValue::from_groups(&groups)
+    };
+
+    Some(ObisDataItem {
+        code,
+        value,
+        unit,
+        parsed,
+        untrusted: false,
+    })
 }
 
 /// Parse multiple OBIS lines from a data block
@@ -448,6 +806,34 @@ pub fn parse_data_block(data: &str) -> Vec<ObisDataItem> {
         .collect()
 }
 
+/// Parse a data block, flagging items whose line overlaps one of the
+/// `untrusted` byte ranges (blocks that never passed BCC)
+pub fn parse_data_block_checked(data: &[u8], untrusted: &[Range<usize>]) -> Vec<ObisDataItem> {
+    let mut items = Vec::new();
+    let mut line_start = 0;
+
+    for line in data.split_inclusive(|&b| b == control::LF) {
+        let line_range = line_start..line_start + line.len();
+        line_start = line_range.end;
+
+        let cleaned: String = String::from_utf8_lossy(line)
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect();
+        if let Some(mut item) = parse_obis_response(cleaned.trim()) {
+            item.untrusted = overlaps_any(&line_range, untrusted);
+            items.push(item);
+        }
+    }
+
+    items
+}
+
+/// Whether `range` shares at least one byte with any of `ranges`
+pub fn overlaps_any(range: &Range<usize>, ranges: &[Range<usize>]) -> bool {
+    ranges.iter().any(|r| r.start < range.end && range.start < r.end)
+}
+
 /// EDAŞ ID lookup table
 pub fn edas_name_from_id(id: u8) -> &'static str {
     match id {
@@ -574,7 +960,7 @@ mod tests {
     fn test_parse_identification() {
         let response = "/MKS5<2>ADM(M550.2251)\r\n";
         let result = parse_identification(response);
-        assert!(result.is_some());
+        assert!(result.is_ok());
         let ident = result.unwrap();
         assert_eq!(ident.manufacturer, "MKS");
         assert_eq!(ident.baud_char, '5');
@@ -582,6 +968,65 @@ mod tests {
         assert_eq!(ident.edas_id, "ADM");
         assert_eq!(ident.model, "M550.2251");
         assert_eq!(ident.max_baud_rate, 9600);
+        assert_eq!(ident.mode, IecMode::C);
+        assert_eq!(ident.raw, "/MKS5<2>ADM(M550.2251)");
+        assert!(!ident.supports_mode_e());
+    }
+
+    #[test]
+    fn test_parse_identification_variants() {
+        // No generation marker, no model
+        let ident = parse_identification("/ABC4XYZ123\r\n").unwrap();
+        assert_eq!(ident.generation, "");
+        assert_eq!(ident.edas_id, "XYZ123");
+        assert_eq!(ident.model, "");
+
+        // Enhanced capability sequences before the identification
+        let ident = parse_identification("/MKS5\\2\\@<2>ADM(M550.2251)\r\n").unwrap();
+        assert_eq!(ident.escapes, vec!['2', '@']);
+        assert!(ident.supports_mode_e());
+        assert_eq!(ident.generation, "2");
+        assert_eq!(ident.model, "M550.2251");
+
+        assert_eq!(parse_identification("MKS5<2>ADM").unwrap_err(), IdentError::MissingStart);
+        assert_eq!(parse_identification("/MK").unwrap_err(), IdentError::TooShort(2));
+        assert_eq!(parse_identification("/M-S5ADM").unwrap_err(), IdentError::InvalidManufacturer("M-S".to_string()));
+        assert_eq!(parse_identification("/MKS5\\").unwrap_err(), IdentError::IncompleteEscape);
+        assert_eq!(parse_identification("/MKS5<2ADM").unwrap_err(), IdentError::UnterminatedGeneration);
+        assert_eq!(parse_identification("/MKS5<2>ADM(M550").unwrap_err(), IdentError::UnterminatedModel);
+    }
+
+    #[test]
+    fn test_parse_identification_modes() {
+        let mode_b = parse_identification("/MKSE<2>ADM(M550.2251)\r\n").unwrap();
+        assert_eq!(mode_b.mode, IecMode::B);
+        assert_eq!(mode_b.max_baud_rate, 9600);
+
+        let mode_a = parse_identification("/MKSK<1>ADM(M300)\r\n").unwrap();
+        assert_eq!(mode_a.mode, IecMode::A);
+        assert_eq!(mode_a.max_baud_rate, 300);
+
+        // 'G'-'I' are reserved Mode B rates, '7'-'9' reserved Mode C rates
+        assert_eq!(
+            parse_identification("/MKSG<2>ADM(M550.2251)\r\n").unwrap_err(),
+            IdentError::ReservedBaudChar('G')
+        );
+        for c in ['7', '8', '9'] {
+            assert_eq!(
+                parse_identification(&format!("/MKS{}<2>ADM\r\n", c)).unwrap_err(),
+                IdentError::ReservedBaudChar(c)
+            );
+        }
+
+        // Mode D is configured on a meter that announces '5': it still runs at 2400
+        let mut mode_d = parse_identification("/MKS5<2>ADM(M550.2251)\r\n").unwrap();
+        mode_d.set_mode(IecMode::D);
+        assert_eq!(mode_d.mode, IecMode::D);
+        assert_eq!(mode_d.max_baud_rate, 2400);
+
+        assert_eq!(IecMode::from_config("d"), Some(IecMode::D));
+        assert_eq!(IecMode::from_config("auto"), None);
+        assert_eq!(baud_rate_for_mode(IecMode::D, '3'), Some(2400));
     }
 
     #[test]
@@ -593,6 +1038,37 @@ mod tests {
         assert_eq!(item.code, "1.8.0");
         assert_eq!(item.value, "00123.456");
         assert_eq!(item.unit, Some("kWh".to_string()));
+        assert_eq!(item.parsed.as_f64(), Some(123.456));
+    }
+
+    #[test]
+    fn test_parse_obis_response_multiple_groups() {
+        let item = parse_obis_response("1.6.0(000.123*kW)(24-12-01,13:30)").unwrap();
+        assert_eq!(item.code, "1.6.0");
+        assert_eq!(item.value, "000.123");
+        assert_eq!(item.unit, Some("kW".to_string()));
+        assert_eq!(item.parsed.as_f64(), Some(0.123));
+        assert_eq!(
+            item.parsed.as_datetime().map(|dt| dt.format("%Y-%m-%d %H:%M").to_string()),
+            Some("2024-12-01 13:30".to_string())
+        );
+
+        assert!(parse_obis_response("1.8.0(123").is_none());
+    }
+
+    #[test]
+    fn test_parse_data_block_checked() {
+        let data = b"0.0.0(123456)\r\n1.8.0(00123.456*kWh)\r\n1.8.1(00100.000*kWh)\r\n!\r\n";
+        // Corrupt block covering the middle of the 1.8.0 line
+        let corrupt = [Range { start: 20, end: 25 }];
+        let items = parse_data_block_checked(data, &corrupt);
+        assert_eq!(items.len(), 3);
+        assert!(!items[0].untrusted);
+        assert!(items[1].untrusted);
+        assert!(!items[2].untrusted);
+
+        let all_trusted = parse_data_block_checked(data, &[]);
+        assert!(all_trusted.iter().all(|item| !item.untrusted));
     }
 
     #[test]
@@ -604,12 +1080,119 @@ mod tests {
         assert_eq!(msg_no_addr, b"/?!\r\n");
     }
 
+    /// Wrap data in `STX data <end> BCC`
+    fn block(data: &[u8], end: u8) -> Vec<u8> {
+        let mut msg = vec![control::STX];
+        msg.extend_from_slice(data);
+        msg.push(end);
+        msg.push(calculate_bcc(&msg[1..]));
+        msg
+    }
+
     #[test]
-    fn test_obis_code() {
-        let obis = ObisCode::new(1, 0, 1, 8, 0, 0);
-        assert_eq!(obis.to_string(), "1.8.0");
+    fn test_decoder_identification() {
+        let mut decoder = FrameDecoder::new();
+        assert_eq!(decoder.feed(b"/?!\r\n/MKS5<2>ADM(M55"), vec![Frame::Request { address: String::new() }]);
+        assert_eq!(decoder.feed(b"0.2251)\r"), vec![]);
+        assert_eq!(decoder.feed(b"\n"), vec![Frame::Identification("/MKS5<2>ADM(M550.2251)".to_string())]);
+        assert_eq!(decoder.last_frame_bytes(), b"/MKS5<2>ADM(M550.2251)\r\n");
+        assert!(decoder.pending().is_empty());
+    }
+
+    #[test]
+    fn test_decoder_data_block_split() {
+        let msg = block(b"0.0.0(123456)\r\n1.8.0(00123.456*kWh)\r\n!\r\n", control::ETX);
+        let mut decoder = FrameDecoder::new();
+        let mut frames = Vec::new();
+        // Feed one byte at a time, as a slow optical head would deliver it
+        for b in &msg {
+            frames.extend(decoder.feed(&[*b]));
+        }
+        assert_eq!(frames.len(), 1);
+        match &frames[0] {
+            Frame::DataBlock { data, bcc_ok } => {
+                assert!(bcc_ok);
+                assert!(data.starts_with(b"0.0.0(123456)"));
+            }
+            other => panic!("unexpected frame {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decoder_bcc_error_and_partial_block() {
+        let mut bad = block(b"1.8.0(1)", control::ETX);
+        *bad.last_mut().unwrap() ^= 0x01;
+        let partial = block(b"P.01(24-12-01,00:00)", control::EOT);
+
+        let mut decoder = FrameDecoder::new();
+        let mut input = bad.clone();
+        input.extend_from_slice(&partial);
+        let frames = decoder.feed(&input);
+        assert_eq!(frames, vec![
+            Frame::DataBlock { data: b"1.8.0(1)".to_vec(), bcc_ok: false },
+            Frame::PartialBlock { data: b"P.01(24-12-01,00:00)".to_vec(), bcc_ok: true },
+        ]);
+    }
+
+    #[test]
+    fn test_decoder_bcc_byte_looks_like_control() {
+        // "AA" makes the BCC itself equal to ETX; it must not start another frame
+        let msg = block(b"AA", control::ETX);
+        assert_eq!(msg.last(), Some(&control::ETX));
+        let mut decoder = FrameDecoder::new();
+        let frames = decoder.feed(&msg);
+        assert_eq!(frames.len(), 1);
+        assert!(decoder.pending().is_empty());
+    }
+
+    #[test]
+    fn test_decoder_ack_nak_and_option_select() {
+        let mut decoder = FrameDecoder::new();
+        assert_eq!(decoder.feed(&[control::ACK]), vec![Frame::Ack]);
+        assert_eq!(decoder.feed(&[control::NAK]), vec![Frame::Nak]);
+        assert_eq!(
+            decoder.feed(&build_ack_message(ProtocolMode::Programming, '5')),
+            vec![Frame::OptionSelect { protocol: '1', baud_char: '5', mode: '1' }]
+        );
+    }
 
-        let obis_tariff = ObisCode::new(1, 0, 1, 8, 1, 0);
-        assert_eq!(obis_tariff.to_string(), "1.8.1");
+    #[test]
+    fn test_decoder_password_prompt_and_break() {
+        let mut prompt = vec![control::SOH, b'P', b'0', control::STX];
+        prompt.extend_from_slice(b"(12345678)");
+        prompt.push(control::ETX);
+        prompt.push(calculate_bcc(&prompt[1..]));
+
+        let mut brk = vec![control::SOH, b'B', b'0', control::ETX];
+        brk.push(calculate_bcc(&brk[1..]));
+
+        let mut decoder = FrameDecoder::new();
+        let (first, second) = prompt.split_at(5);
+        assert_eq!(decoder.feed(first), vec![]);
+        assert_eq!(
+            decoder.feed(second),
+            vec![Frame::PasswordPrompt { data: "(12345678)".to_string(), bcc_ok: true }]
+        );
+        assert_eq!(decoder.feed(&brk), vec![Frame::Break]);
+
+        match decoder.feed(&build_read_command("0.9.1")).as_slice() {
+            [Frame::Command { command, data, .. }] => {
+                assert_eq!(command, "R2");
+                assert_eq!(data, b"0.9.1()");
+            }
+            other => panic!("unexpected frames {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decoder_skips_noise_and_truncated_frames() {
+        let mut decoder = FrameDecoder::new();
+        let mut input = b"\r\n\x00".to_vec();
+        // Truncated block followed by a complete one
+        input.extend_from_slice(&[control::STX, b'1', b'.']);
+        input.extend_from_slice(&block(b"0.9.1(12:00:00)", control::ETX));
+        let frames = decoder.feed(&input);
+        assert_eq!(frames, vec![Frame::DataBlock { data: b"0.9.1(12:00:00)".to_vec(), bcc_ok: true }]);
+        assert_eq!(decoder.skipped(), 6);
     }
 }
@@ -1,5 +1,10 @@
 pub mod port;
 pub mod iec62056;
+pub mod obis;
+pub mod transport;
+pub mod capture;
 
 pub use port::*;
 pub use iec62056::*;
+pub use obis::*;
+pub use transport::*;
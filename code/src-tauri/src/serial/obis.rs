@@ -0,0 +1,768 @@
+//! OBIS codes and typed OBIS values
+//!
+//! `ObisCode` covers the IEC 62056-61 notations seen in readouts: full
+//! (`1-0:1.8.0*255`), reduced (`1.8.0`, `96.77.4*3`) and letter groups
+//! (`F.F.0`, `C.1.0`, `P.01`).
+//!
+//! Values inside OBIS data lines come in a handful of textual shapes:
+//! numbers with units (`000123.456*kWh`), dates (`24-12-15`), times (`14:30:05`),
+//! date-time pairs (`24-12-15,14:30`), `start;end` intervals used by outage and
+//! warning records, comma-separated lists and hexadecimal status words.
+//! This module turns that text into an `ObisValue` once, so callers don't
+//! re-parse raw strings by hand.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Maximum number of significant digits that fit in an f64 without loss
+const MAX_NUMERIC_DIGITS: usize = 15;
+
+/// Billing period value meaning "current / not used" in the F group
+const F_CURRENT: u8 = 255;
+
+/// A single value group of an OBIS code: a number (0-255) or a letter
+///
+/// IEC 62056-61 reserves the letters `C` (general service), `F` (error),
+/// `L` (list) and `P` (load profile) for the C group; MASS meters also use
+/// `F.F.0`/`F.F.1` for their status words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObisGroup {
+    Value(u8),
+    Letter(char),
+}
+
+impl ObisGroup {
+    fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+            return s
+                .parse::<u8>()
+                .map(ObisGroup::Value)
+                .map_err(|_| format!("OBIS group out of range: {}", s));
+        }
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_alphabetic() => Ok(ObisGroup::Letter(c.to_ascii_uppercase())),
+            _ => Err(format!("Invalid OBIS group: '{}'", s)),
+        }
+    }
+}
+
+impl From<u8> for ObisGroup {
+    fn from(value: u8) -> Self {
+        ObisGroup::Value(value)
+    }
+}
+
+impl fmt::Display for ObisGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObisGroup::Value(v) => write!(f, "{}", v),
+            ObisGroup::Letter(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+/// OBIS code in full (`A-B:C.D.E*F`) or reduced (`C.D.E*F`, `C.D`) notation
+///
+/// Groups that were not written are kept as `None`, so a parsed code formats
+/// back to the notation it came from. Use [`ObisCode::canonical_eq`] to
+/// compare codes written in different notations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObisCode {
+    pub a: Option<u8>,          // Media
+    pub b: Option<u8>,          // Channel
+    pub c: ObisGroup,           // Physical quantity
+    pub d: ObisGroup,           // Processing type
+    pub e: Option<ObisGroup>,   // Tariff
+    pub f: Option<u8>,          // Billing period
+}
+
+impl ObisCode {
+    /// Full numeric code `A-B:C.D.E*F`
+    pub fn new(a: u8, b: u8, c: u8, d: u8, e: u8, f: u8) -> Self {
+        Self {
+            a: Some(a),
+            b: Some(b),
+            c: c.into(),
+            d: d.into(),
+            e: Some(e.into()),
+            f: Some(f),
+        }
+    }
+
+    /// Reduced numeric code `C.D.E` as used in MASS readouts
+    pub fn short(c: u8, d: u8, e: u8) -> Self {
+        Self {
+            a: None,
+            b: None,
+            c: c.into(),
+            d: d.into(),
+            e: Some(e.into()),
+            f: None,
+        }
+    }
+
+    /// Same code with the billing period set (`C.D.E*F`)
+    pub fn with_billing_period(mut self, f: u8) -> Self {
+        self.f = Some(f);
+        self
+    }
+
+    /// Code without A and B groups, formatted the way meters print it
+    pub fn reduced(&self) -> Self {
+        let c = self.canonical();
+        Self { a: None, b: None, ..c }
+    }
+
+    /// Normalized form: a missing E group is 0 and `*255` means no billing period
+    pub fn canonical(&self) -> Self {
+        Self {
+            e: Some(self.e.unwrap_or(ObisGroup::Value(0))),
+            f: self.f.filter(|&f| f != F_CURRENT),
+            ..*self
+        }
+    }
+
+    /// Whether both codes denote the same object
+    ///
+    /// `1.8.0`, `1.8.0*255` and `1-0:1.8.0` are all equal. A and B are only
+    /// compared when both codes specify them, since reduced codes leave
+    /// them implicit.
+    pub fn canonical_eq(&self, other: &ObisCode) -> bool {
+        let (x, y) = (self.canonical(), other.canonical());
+        let group_eq = |p: Option<u8>, q: Option<u8>| match (p, q) {
+            (Some(p), Some(q)) => p == q,
+            _ => true,
+        };
+        group_eq(x.a, y.a) && group_eq(x.b, y.b) && x.c == y.c && x.d == y.d && x.e == y.e && x.f == y.f
+    }
+
+    /// Billing period (F group), `None` for current values
+    pub fn billing_period(&self) -> Option<u8> {
+        self.canonical().f
+    }
+
+    /// Whether this is one of the `F.F` status words, whose value is a hex bitfield
+    pub fn is_status_register(&self) -> bool {
+        self.c == ObisGroup::Letter('F') && self.d == ObisGroup::Letter('F')
+    }
+}
+
+impl FromStr for ObisCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("Empty OBIS code".to_string());
+        }
+
+        let (body, f) = match s.split_once('*') {
+            Some((body, f)) => {
+                let f = f
+                    .trim()
+                    .parse::<u8>()
+                    .map_err(|_| format!("Invalid OBIS billing period: '{}'", f))?;
+                (body, Some(f))
+            }
+            None => (s, None),
+        };
+
+        let (a, b, rest) = match body.split_once(':') {
+            Some((ab, rest)) => {
+                let (a, b) = ab
+                    .split_once('-')
+                    .ok_or_else(|| format!("Invalid OBIS media/channel: '{}'", ab))?;
+                let parse_u8 = |g: &str| {
+                    g.trim()
+                        .parse::<u8>()
+                        .map_err(|_| format!("Invalid OBIS group: '{}'", g))
+                };
+                (Some(parse_u8(a)?), Some(parse_u8(b)?), rest)
+            }
+            None => (None, None, body),
+        };
+
+        let groups = rest
+            .split('.')
+            .map(ObisGroup::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let numeric = |g: ObisGroup| match g {
+            ObisGroup::Value(v) => Ok(v),
+            ObisGroup::Letter(c) => Err(format!("OBIS group must be numeric: '{}'", c)),
+        };
+
+        match (a.is_some(), groups.as_slice()) {
+            (_, [c, d]) => Ok(Self { a, b, c: *c, d: *d, e: None, f }),
+            (_, [c, d, e]) => Ok(Self { a, b, c: *c, d: *d, e: Some(*e), f }),
+            // Dotted full form A.B.C.D.E(.F)
+            (false, [ga, gb, c, d, e]) if f.is_none() => Ok(Self {
+                a: Some(numeric(*ga)?),
+                b: Some(numeric(*gb)?),
+                c: *c,
+                d: *d,
+                e: Some(*e),
+                f: None,
+            }),
+            (false, [ga, gb, c, d, e, gf]) if f.is_none() => Ok(Self {
+                a: Some(numeric(*ga)?),
+                b: Some(numeric(*gb)?),
+                c: *c,
+                d: *d,
+                e: Some(*e),
+                f: Some(numeric(*gf)?),
+            }),
+            _ => Err(format!("Invalid OBIS code: '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for ObisCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let (Some(a), Some(b)) = (self.a, self.b) {
+            write!(f, "{}-{}:", a, b)?;
+        }
+        write!(f, "{}.", self.c)?;
+        // Load profile codes keep their two-digit form: P.01
+        match (self.c, self.d) {
+            (ObisGroup::Letter('P'), ObisGroup::Value(d)) => write!(f, "{:02}", d)?,
+            (_, d) => write!(f, "{}", d)?,
+        }
+        if let Some(e) = self.e {
+            write!(f, ".{}", e)?;
+        }
+        if let Some(period) = self.f {
+            write!(f, "*{}", period)?;
+        }
+        Ok(())
+    }
+}
+
+/// Typed value of an OBIS data item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ObisValue {
+    /// Numeric value with optional unit, e.g. `000123.456*kWh`
+    Number { value: f64, unit: Option<String> },
+    /// Date in `yy-mm-dd` format
+    Date { date: NaiveDate },
+    /// Time in `hh:mm` or `hh:mm:ss` format
+    Time { time: NaiveTime },
+    /// Date and time pair, e.g. `yy-mm-dd,hh:mm`
+    DateTime { datetime: NaiveDateTime },
+    /// `start;end` interval (outage and warning records)
+    Interval { start: Box<ObisValue>, end: Box<ObisValue> },
+    /// Comma-separated values, or several parenthesized groups on one line
+    Multi { values: Vec<ObisValue> },
+    /// Hexadecimal status word (FF/GF codes, load profile status), only
+    /// produced by `parse_status`
+    Hex { value: String },
+    /// Empty value `()` or an unset date such as `00-00-00,00:00`
+    Empty,
+    /// Anything that does not match one of the shapes above
+    Text { value: String },
+}
+
+impl ObisValue {
+    /// Parse the content of a single parenthesized group (without the parentheses)
+    pub fn parse(raw: &str) -> Self {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return ObisValue::Empty;
+        }
+
+        if let Some((start, end)) = raw.split_once(';') {
+            return ObisValue::Interval {
+                start: Box::new(Self::parse(start)),
+                end: Box::new(Self::parse(end)),
+            };
+        }
+
+        if raw.contains(',') {
+            let tokens: Vec<&str> = raw.split(',').map(str::trim).collect();
+            let mut values = Vec::new();
+            let mut i = 0;
+            while i < tokens.len() {
+                // A date directly followed by a time forms a single date-time value
+                if i + 1 < tokens.len() && is_date(tokens[i]) && is_time(tokens[i + 1]) {
+                    values.push(parse_date_time(tokens[i], tokens[i + 1], raw));
+                    i += 2;
+                } else {
+                    values.push(parse_scalar(tokens[i]));
+                    i += 1;
+                }
+            }
+            return if values.len() == 1 {
+                values.remove(0)
+            } else {
+                ObisValue::Multi { values }
+            };
+        }
+
+        parse_scalar(raw)
+    }
+
+    /// Parse the value of a status register: a fixed-width hex word such as
+    /// `00000000` is a bitfield, anything else is parsed like `parse`
+    ///
+    /// Outside this context `0090` is a number and `dead` is text.
+    pub fn parse_status(raw: &str) -> Self {
+        let raw = raw.trim();
+        if is_hex_word(raw) {
+            ObisValue::Hex { value: raw.to_string() }
+        } else {
+            Self::parse(raw)
+        }
+    }
+
+    /// Parse all parenthesized groups of a data line
+    ///
+    /// A single group yields its own value; several groups, like the
+    /// `1.6.0(000.123*kW)(24-12-01,13:30)` max demand line, yield `Multi`.
+    pub fn from_groups(groups: &[&str]) -> Self {
+        match groups {
+            [] => ObisValue::Empty,
+            [single] => Self::parse(single),
+            _ => ObisValue::Multi {
+                values: groups.iter().map(|g| Self::parse(g)).collect(),
+            },
+        }
+    }
+
+    /// Numeric value, taking the first element of a multi-value
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ObisValue::Number { value, .. } => Some(*value),
+            ObisValue::Multi { values } => values.first().and_then(|v| v.as_f64()),
+            _ => None,
+        }
+    }
+
+    /// Unit of a numeric value, taking the first element of a multi-value
+    pub fn unit(&self) -> Option<&str> {
+        match self {
+            ObisValue::Number { unit, .. } => unit.as_deref(),
+            ObisValue::Multi { values } => values.first().and_then(|v| v.unit()),
+            _ => None,
+        }
+    }
+
+    /// Date value, or the first date found in a multi-value
+    pub fn as_date(&self) -> Option<NaiveDate> {
+        match self {
+            ObisValue::Date { date } => Some(*date),
+            ObisValue::DateTime { datetime } => Some(datetime.date()),
+            ObisValue::Multi { values } => values.iter().find_map(|v| v.as_date()),
+            _ => None,
+        }
+    }
+
+    /// Time value, or the first time found in a multi-value
+    pub fn as_time(&self) -> Option<NaiveTime> {
+        match self {
+            ObisValue::Time { time } => Some(*time),
+            ObisValue::DateTime { datetime } => Some(datetime.time()),
+            ObisValue::Multi { values } => values.iter().find_map(|v| v.as_time()),
+            _ => None,
+        }
+    }
+
+    /// Date-time value, or the first date-time found in a multi-value
+    pub fn as_datetime(&self) -> Option<NaiveDateTime> {
+        match self {
+            ObisValue::DateTime { datetime } => Some(*datetime),
+            ObisValue::Multi { values } => values.iter().find_map(|v| v.as_datetime()),
+            _ => None,
+        }
+    }
+
+    /// Hexadecimal status word as an integer
+    pub fn as_hex(&self) -> Option<u64> {
+        match self {
+            ObisValue::Hex { value } => u64::from_str_radix(value, 16).ok(),
+            _ => None,
+        }
+    }
+
+    /// Start and end of an interval
+    pub fn as_interval(&self) -> Option<(&ObisValue, &ObisValue)> {
+        match self {
+            ObisValue::Interval { start, end } => Some((start, end)),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, ObisValue::Empty)
+    }
+
+    /// Whether this value, read back from the meter, is the `written` one
+    ///
+    /// The meter echoes numbers zero-padded and with their unit, so numbers
+    /// compare by value, and by unit only when the written value has one:
+    /// `0030*min` echoes `30` and `30*min` but not `30*s`.
+    pub fn echoes(&self, written: &ObisValue) -> bool {
+        match (self, written) {
+            (ObisValue::Number { value, unit }, ObisValue::Number { value: w_value, unit: w_unit }) => {
+                value == w_value && (w_unit.is_none() || unit == w_unit)
+            }
+            (ObisValue::Interval { start, end }, ObisValue::Interval { start: w_start, end: w_end }) => {
+                start.echoes(w_start) && end.echoes(w_end)
+            }
+            (ObisValue::Multi { values }, ObisValue::Multi { values: w_values }) => {
+                values.len() == w_values.len() && values.iter().zip(w_values).all(|(v, w)| v.echoes(w))
+            }
+            _ => self == written,
+        }
+    }
+}
+
+/// Parse a single token that contains no `;` or `,` separators
+fn parse_scalar(token: &str) -> ObisValue {
+    let token = token.trim();
+    if token.is_empty() {
+        return ObisValue::Empty;
+    }
+
+    // Value with unit: 000123.456*kWh
+    if let Some((number, unit)) = token.split_once('*') {
+        return match parse_number(number) {
+            Some(value) => ObisValue::Number {
+                value,
+                unit: if unit.is_empty() { None } else { Some(unit.to_string()) },
+            },
+            None => ObisValue::Text { value: token.to_string() },
+        };
+    }
+
+    if is_date(token) {
+        return parse_date(token)
+            .map(|date| ObisValue::Date { date })
+            .unwrap_or_else(|| unset_or_text(token));
+    }
+
+    if is_time(token) {
+        return parse_time(token)
+            .map(|time| ObisValue::Time { time })
+            .unwrap_or_else(|| ObisValue::Text { value: token.to_string() });
+    }
+
+    // Some meters separate date and time with a space: 24-12-15 14:30
+    if let Some((date, time)) = token.split_once(' ') {
+        if is_date(date) && is_time(time.trim()) {
+            return parse_date_time(date, time.trim(), token);
+        }
+    }
+
+    if let Some(value) = parse_number(token) {
+        return ObisValue::Number { value, unit: None };
+    }
+
+    ObisValue::Text { value: token.to_string() }
+}
+
+/// Parse a strictly formatted decimal number (sign, digits, optional point)
+fn parse_number(s: &str) -> Option<f64> {
+    let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+    if digits.is_empty() || digits == "." {
+        return None;
+    }
+    if digits.chars().filter(|&c| c == '.').count() > 1 {
+        return None;
+    }
+    if !digits.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    if digits.chars().filter(|c| c.is_ascii_digit()).count() > MAX_NUMERIC_DIGITS {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// `yy-mm-dd`
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 8
+        && b[2] == b'-'
+        && b[5] == b'-'
+        && [0, 1, 3, 4, 6, 7].iter().all(|&i| b[i].is_ascii_digit())
+}
+
+/// `hh:mm` or `hh:mm:ss`
+fn is_time(s: &str) -> bool {
+    let b = s.as_bytes();
+    let digits_at = |idx: &[usize]| idx.iter().all(|&i| b[i].is_ascii_digit());
+    match b.len() {
+        5 => b[2] == b':' && digits_at(&[0, 1, 3, 4]),
+        8 => b[2] == b':' && b[5] == b':' && digits_at(&[0, 1, 3, 4, 6, 7]),
+        _ => false,
+    }
+}
+
+/// Status words are fixed-width upper-case hex: 2, 4, 8 or 16 digits
+fn is_hex_word(s: &str) -> bool {
+    matches!(s.len(), 2 | 4 | 8 | 16)
+        && s.chars().all(|c| c.is_ascii_digit() || ('A'..='F').contains(&c))
+}
+
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    let yy: i32 = s[0..2].parse().ok()?;
+    let mm: u32 = s[3..5].parse().ok()?;
+    let dd: u32 = s[6..8].parse().ok()?;
+    NaiveDate::from_ymd_opt(2000 + yy, mm, dd)
+}
+
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    let hh: u32 = s[0..2].parse().ok()?;
+    let mm: u32 = s[3..5].parse().ok()?;
+    let ss: u32 = if s.len() == 8 { s[6..8].parse().ok()? } else { 0 };
+    NaiveTime::from_hms_opt(hh, mm, ss)
+}
+
+fn parse_date_time(date: &str, time: &str, raw: &str) -> ObisValue {
+    match (parse_date(date), parse_time(time)) {
+        (Some(d), Some(t)) => ObisValue::DateTime { datetime: d.and_time(t) },
+        _ => unset_or_text(raw),
+    }
+}
+
+/// Meters report "no record" as an all-zero date
+fn unset_or_text(raw: &str) -> ObisValue {
+    if raw.starts_with("00-00-00") {
+        ObisValue::Empty
+    } else {
+        ObisValue::Text { value: raw.to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn test_number_with_unit() {
+        let v = ObisValue::parse("000123.456*kWh");
+        assert_eq!(v, ObisValue::Number { value: 123.456, unit: Some("kWh".to_string()) });
+        assert_eq!(v.as_f64(), Some(123.456));
+        assert_eq!(v.unit(), Some("kWh"));
+    }
+
+    #[test]
+    fn test_plain_number() {
+        assert_eq!(ObisValue::parse("50.0").as_f64(), Some(50.0));
+        assert_eq!(ObisValue::parse("-0.98").as_f64(), Some(-0.98));
+        assert_eq!(ObisValue::parse("4").as_f64(), Some(4.0));
+        // Too many digits for an f64: keep the text
+        assert!(matches!(
+            ObisValue::parse("00000600170022009999999999999999"),
+            ObisValue::Text { .. }
+        ));
+    }
+
+    #[test]
+    fn test_date_and_time() {
+        assert_eq!(
+            ObisValue::parse("24-12-15").as_date(),
+            NaiveDate::from_ymd_opt(2024, 12, 15)
+        );
+        assert_eq!(
+            ObisValue::parse("14:30:05").as_time(),
+            NaiveTime::from_hms_opt(14, 30, 5)
+        );
+        assert_eq!(
+            ObisValue::parse("06:00").as_time(),
+            NaiveTime::from_hms_opt(6, 0, 0)
+        );
+        assert!(matches!(ObisValue::parse("24-13-40"), ObisValue::Text { .. }));
+    }
+
+    #[test]
+    fn test_date_time_pair() {
+        assert_eq!(
+            ObisValue::parse("24-12-01,13:30").as_datetime(),
+            Some(dt(2024, 12, 1, 13, 30))
+        );
+        assert_eq!(
+            ObisValue::parse("24-12-01 13:30").as_datetime(),
+            Some(dt(2024, 12, 1, 13, 30))
+        );
+        assert!(ObisValue::parse("00-00-00,00:00").is_empty());
+    }
+
+    #[test]
+    fn test_interval() {
+        let v = ObisValue::parse("24-01-05,10:15;24-01-05,11:40");
+        let (start, end) = v.as_interval().unwrap();
+        assert_eq!(start.as_datetime(), Some(dt(2024, 1, 5, 10, 15)));
+        assert_eq!(end.as_datetime(), Some(dt(2024, 1, 5, 11, 40)));
+
+        let unset = ObisValue::parse("00-00-00,00:00;00-00-00,00:00");
+        let (start, end) = unset.as_interval().unwrap();
+        assert!(start.is_empty() && end.is_empty());
+    }
+
+    #[test]
+    fn test_multi_value() {
+        let v = ObisValue::parse("220.61,000.52,003.02,000.028,0.00,50.0");
+        match &v {
+            ObisValue::Multi { values } => {
+                assert_eq!(values.len(), 6);
+                assert_eq!(values[5].as_f64(), Some(50.0));
+            }
+            other => panic!("expected multi, got {:?}", other),
+        }
+        assert_eq!(v.as_f64(), Some(220.61));
+
+        // DST period: offset, date, time
+        let dst = ObisValue::parse("+1,24-03-31,03:00");
+        assert_eq!(
+            dst,
+            ObisValue::Multi {
+                values: vec![
+                    ObisValue::Number { value: 1.0, unit: None },
+                    ObisValue::DateTime { datetime: dt(2024, 3, 31, 3, 0) },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_hex_status() {
+        let v = ObisValue::parse_status("0000000000000090");
+        assert_eq!(v, ObisValue::Hex { value: "0000000000000090".to_string() });
+        assert_eq!(v.as_hex(), Some(0x90));
+        assert_eq!(ObisValue::parse_status("1A").as_hex(), Some(0x1A));
+        assert_eq!(ObisValue::parse_status("0000C0DE").as_hex(), Some(0xC0DE));
+    }
+
+    #[test]
+    fn test_hex_status_negative() {
+        // Not status words: wrong case, odd width, or outside a status register
+        for s in ["Fe", "dead", "ABC", "00000000000000900", "ARIZA"] {
+            assert_eq!(ObisValue::parse_status(s).as_hex(), None, "{}", s);
+        }
+        assert_eq!(ObisValue::parse("Fe"), ObisValue::Text { value: "Fe".to_string() });
+        assert_eq!(ObisValue::parse("DEAD"), ObisValue::Text { value: "DEAD".to_string() });
+        assert_eq!(ObisValue::parse("00000090").as_f64(), Some(90.0));
+        assert!(!"1.8.0".parse::<ObisCode>().unwrap().is_status_register());
+        assert!("F.F.0".parse::<ObisCode>().unwrap().is_status_register());
+    }
+
+    #[test]
+    fn test_echoes() {
+        let echo = |read: &str, written: &str| ObisValue::parse(read).echoes(&ObisValue::parse(written));
+        assert!(echo("0030*min", "30"));
+        assert!(echo("0030*min", "30*min"));
+        assert!(!echo("0030*min", "30*s"));
+        assert!(!echo("0031*min", "30"));
+        assert!(echo("12:30:00", "12:30"));
+        assert!(echo("24-12-15", "24-12-15"));
+        assert!(!echo("24-12-16", "24-12-15"));
+        assert!(echo("00000600170022009999", "00000600170022009999"));
+        assert!(!echo("00000600170022009999", "0600170022009999"));
+    }
+
+    #[test]
+    fn test_groups() {
+        let v = ObisValue::from_groups(&["000.123*kW", "24-12-01,13:30"]);
+        assert_eq!(v.as_f64(), Some(0.123));
+        assert_eq!(v.unit(), Some("kW"));
+        assert_eq!(v.as_datetime(), Some(dt(2024, 12, 1, 13, 30)));
+        assert!(ObisValue::from_groups(&[""]).is_empty());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let json = serde_json::to_value(ObisValue::parse("1.5*V")).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "number", "value": 1.5, "unit": "V"}));
+        let json = serde_json::to_value(ObisValue::parse("24-12-01,13:30")).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "dateTime", "datetime": "2024-12-01T13:30:00"}));
+        let json = serde_json::to_value(ObisValue::Empty).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "empty"}));
+    }
+
+    #[test]
+    fn test_obis_code() {
+        let obis = ObisCode::new(1, 0, 1, 8, 0, 255);
+        assert_eq!(obis.reduced().to_string(), "1.8.0");
+
+        let obis_tariff = ObisCode::new(1, 0, 1, 8, 1, 255);
+        assert_eq!(obis_tariff.reduced().to_string(), "1.8.1");
+    }
+
+    #[test]
+    fn test_obis_code_reduced() {
+        let code: ObisCode = "1.8.0".parse().unwrap();
+        assert_eq!(code, ObisCode::short(1, 8, 0));
+        assert_eq!(code.to_string(), "1.8.0");
+
+        let code: ObisCode = "96.77.4*3".parse().unwrap();
+        assert_eq!(code, ObisCode::short(96, 77, 4).with_billing_period(3));
+        assert_eq!(code.billing_period(), Some(3));
+        assert_eq!(code.to_string(), "96.77.4*3");
+    }
+
+    #[test]
+    fn test_obis_code_full() {
+        let code: ObisCode = "1-0:1.8.0*255".parse().unwrap();
+        assert_eq!(code, ObisCode::new(1, 0, 1, 8, 0, 255));
+        assert_eq!(code.to_string(), "1-0:1.8.0*255");
+        assert_eq!(code.billing_period(), None);
+        assert_eq!(code.reduced().to_string(), "1.8.0");
+
+        let dotted: ObisCode = "1.0.1.8.0.255".parse().unwrap();
+        assert_eq!(dotted, code);
+    }
+
+    #[test]
+    fn test_obis_code_letters() {
+        for s in ["F.F.0", "C.1.0", "P.01", "L.2.1"] {
+            let code: ObisCode = s.parse().unwrap();
+            assert_eq!(code.to_string(), s);
+        }
+        let code: ObisCode = "f.f.1".parse().unwrap();
+        assert_eq!(code.c, ObisGroup::Letter('F'));
+        assert_eq!(code.e, Some(ObisGroup::Value(1)));
+    }
+
+    #[test]
+    fn test_obis_code_canonical_eq() {
+        let short: ObisCode = "1.8.0".parse().unwrap();
+        for s in ["1.8.0*255", "1-0:1.8.0", "1-0:1.8.0*255", "1.8"] {
+            assert!(short.canonical_eq(&s.parse().unwrap()), "{}", s);
+        }
+        assert!(!short.canonical_eq(&"1.8.0*1".parse().unwrap()));
+        assert!(!short.canonical_eq(&"1.8.1".parse().unwrap()));
+        let full: ObisCode = "1-0:1.8.0".parse().unwrap();
+        assert!(!full.canonical_eq(&"1-1:1.8.0".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_obis_code_vectors() {
+        // Shared with normalizeObis in the frontend
+        let vectors: Vec<serde_json::Value> =
+            serde_json::from_str(include_str!("../../../src/lib/utils/obis-vectors.json")).unwrap();
+        for v in vectors {
+            let code = v["code"].as_str().unwrap();
+            match v["reduced"].as_str() {
+                Some(reduced) => assert_eq!(code.parse::<ObisCode>().unwrap().reduced().to_string(), reduced, "{}", code),
+                None => assert!(code.parse::<ObisCode>().is_err(), "{}", code),
+            }
+        }
+    }
+
+    #[test]
+    fn test_obis_code_invalid() {
+        for s in ["", "1", "1.8.0.1", "1.8.0*x", "1.8.256", "1-0.1.8.0", "A-0:1.8.0", "1.FF.0"] {
+            assert!(s.parse::<ObisCode>().is_err(), "{}", s);
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use serialport::{available_ports, SerialPortType};
 use crate::PortInfo;
+use crate::error::AppError;
 
 /// List all available serial ports on the system
-pub fn list_ports() -> Result<Vec<PortInfo>, String> {
-    let ports = available_ports().map_err(|e| e.to_string())?;
+pub fn list_ports() -> Result<Vec<PortInfo>, AppError> {
+    let ports = available_ports().map_err(AppError::io)?;
 
     let port_infos: Vec<PortInfo> = ports
         .into_iter()
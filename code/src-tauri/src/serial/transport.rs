@@ -0,0 +1,350 @@
+//! Byte transports for IEC 62056-21 sessions
+//!
+//! The protocol and command layers talk to the meter through `MeterTransport`,
+//! so the physical link (COM port, network, recorded trace) can be swapped.
+
+use super::capture::{self, ReplayTransport};
+use crate::error::AppError;
+use crate::ConnectionParams;
+use serialport::{DataBits, Parity, SerialPort, StopBits};
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Byte link to a meter
+///
+/// Reads block for at most `timeout()` and then fail with
+/// `io::ErrorKind::TimedOut`, like a serial port does.
+pub trait MeterTransport: Read + Write + Send {
+    /// Name of the link for logs, e.g. "COM5"
+    fn name(&self) -> String;
+
+    /// Current line baud rate
+    fn baud_rate(&self) -> io::Result<u32>;
+
+    /// Switch the line to a new baud rate
+    fn set_baud_rate(&mut self, baud_rate: u32) -> io::Result<()>;
+
+    /// Read timeout
+    fn timeout(&self) -> Duration;
+
+    /// Change the read timeout
+    fn set_timeout(&mut self, timeout: Duration) -> io::Result<()>;
+
+    /// Discard received bytes that have not been read yet
+    fn clear_input(&mut self) -> io::Result<()>;
+
+    /// Whether the line can follow the baud switch after option select
+    fn can_switch_baud(&self) -> bool {
+        true
+    }
+}
+
+impl<T: MeterTransport + ?Sized> MeterTransport for Box<T> {
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    fn baud_rate(&self) -> io::Result<u32> {
+        (**self).baud_rate()
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> io::Result<()> {
+        (**self).set_baud_rate(baud_rate)
+    }
+
+    fn timeout(&self) -> Duration {
+        (**self).timeout()
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        (**self).set_timeout(timeout)
+    }
+
+    fn clear_input(&mut self) -> io::Result<()> {
+        (**self).clear_input()
+    }
+
+    fn can_switch_baud(&self) -> bool {
+        (**self).can_switch_baud()
+    }
+}
+
+/// Physical link of a connection, taken from `ConnectionParams`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Link {
+    /// Local COM port (optical, serial, auto)
+    Serial { port: String },
+    /// Transparent TCP socket of an RS-485/Ethernet converter or GPRS modem
+    Tcp { host: String, port: u16, baud_switch: bool },
+    /// Recorded conversation played back from a capture file
+    Replay { path: String },
+}
+
+impl Link {
+    /// Link for the connection type of `params`
+    pub fn from_params(params: &ConnectionParams) -> Result<Self, AppError> {
+        match params.connection_type.as_str() {
+            "tcp" => {}
+            "replay" => return Ok(Link::Replay { path: params.port.clone() }),
+            _ => return Ok(Link::Serial { port: params.port.clone() }),
+        }
+        let host = params.host.as_deref().map(str::trim).unwrap_or_default();
+        if host.is_empty() {
+            return Err(AppError::invalid_input("reason_tcp_host_missing"));
+        }
+        let port = params.tcp_port.filter(|&p| p > 0)
+            .ok_or_else(|| AppError::invalid_input("reason_tcp_port_missing"))?;
+        Ok(Link::Tcp { host: host.to_string(), port, baud_switch: params.tcp_baud_switch })
+    }
+
+    /// Name of the link for logs and errors
+    pub fn name(&self) -> String {
+        match self {
+            Link::Serial { port } => port.clone(),
+            Link::Tcp { host, port, .. } => format!("{}:{}", host, port),
+            Link::Replay { path } => path.clone(),
+        }
+    }
+
+    /// Open the link with the handshake baud rate, recording it if a capture is running
+    pub fn open(&self, baud_rate: u32, timeout_ms: u64) -> Result<Box<dyn MeterTransport>, AppError> {
+        let transport: Box<dyn MeterTransport> = match self {
+            Link::Serial { port } => Box::new(SerialTransport::open(port, baud_rate, timeout_ms)?),
+            Link::Tcp { host, port, baud_switch } => {
+                let mut transport = TcpTransport::connect(host, *port, baud_rate, timeout_ms)?;
+                transport.baud_switch = *baud_switch;
+                Box::new(transport)
+            }
+            Link::Replay { path } => Box::new(ReplayTransport::open(std::path::Path::new(path))?),
+        };
+        Ok(capture::record(transport, baud_rate))
+    }
+}
+
+/// Serial port link (optical probe, RS-232 or RS-485 adapter), 7E1
+pub struct SerialTransport {
+    port: Box<dyn SerialPort>,
+}
+
+impl SerialTransport {
+    /// Open a serial port with IEC 62056-21 settings (7E1)
+    pub fn open(port_name: &str, baud_rate: u32, timeout_ms: u64) -> Result<Self, AppError> {
+        let port = serialport::new(port_name, baud_rate)
+            .data_bits(DataBits::Seven)
+            .parity(Parity::Even)
+            .stop_bits(StopBits::One)
+            .timeout(Duration::from_millis(timeout_ms))
+            .open()
+            .map_err(|e| AppError::PortOpen { port: port_name.to_string(), reason: e.to_string() })?;
+        Ok(Self { port })
+    }
+}
+
+impl Read for SerialTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.port.read(buf)
+    }
+}
+
+impl Write for SerialTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.port.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl MeterTransport for SerialTransport {
+    fn name(&self) -> String {
+        self.port.name().unwrap_or_default()
+    }
+
+    fn baud_rate(&self) -> io::Result<u32> {
+        Ok(self.port.baud_rate()?)
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> io::Result<()> {
+        Ok(self.port.set_baud_rate(baud_rate)?)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.port.timeout()
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        Ok(self.port.set_timeout(timeout)?)
+    }
+
+    fn clear_input(&mut self) -> io::Result<()> {
+        Ok(self.port.clear(serialport::ClearBuffer::Input)?)
+    }
+}
+
+/// TCP link to a transparent serial converter or modem
+///
+/// The serial side of the converter runs at a fixed speed, so the baud rate
+/// is only recorded. With `baud_switch` set, the session still negotiates the
+/// meter's maximum rate, for converters that follow the switch themselves.
+pub struct TcpTransport {
+    stream: TcpStream,
+    peer: String,
+    baud_rate: u32,
+    timeout: Duration,
+    /// Negotiate a baud switch after option select (default: stay at the handshake rate)
+    pub baud_switch: bool,
+}
+
+impl TcpTransport {
+    /// Connect to `host:port` within the timeout
+    pub fn connect(host: &str, port: u16, baud_rate: u32, timeout_ms: u64) -> Result<Self, AppError> {
+        let peer = format!("{}:{}", host, port);
+        let open_error = |reason: String| AppError::PortOpen { port: peer.clone(), reason };
+        let timeout = Duration::from_millis(timeout_ms.max(1));
+
+        let addrs = (host, port).to_socket_addrs().map_err(|e| open_error(e.to_string()))?;
+        let mut last_error = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => {
+                    stream.set_nodelay(true).map_err(|e| open_error(e.to_string()))?;
+                    stream.set_read_timeout(Some(timeout)).map_err(|e| open_error(e.to_string()))?;
+                    return Ok(Self { stream, peer, baud_rate, timeout, baud_switch: false });
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(open_error(last_error.map_or_else(|| "reason_address_unresolved".to_string(), |e| e.to_string())))
+    }
+}
+
+impl Read for TcpTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.stream.read(buf) {
+            // A closed socket reads 0 bytes forever; report it instead of idling
+            Ok(0) if !buf.is_empty() => Err(io::Error::new(io::ErrorKind::ConnectionAborted, "reason_connection_closed")),
+            // Socket read timeouts surface as WouldBlock on Unix; match the serial port
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Err(io::Error::new(io::ErrorKind::TimedOut, e)),
+            other => other,
+        }
+    }
+}
+
+impl Write for TcpTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl MeterTransport for TcpTransport {
+    fn name(&self) -> String {
+        self.peer.clone()
+    }
+
+    fn baud_rate(&self) -> io::Result<u32> {
+        Ok(self.baud_rate)
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> io::Result<()> {
+        self.baud_rate = baud_rate;
+        Ok(())
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        let timeout = timeout.max(Duration::from_millis(1));
+        self.stream.set_read_timeout(Some(timeout))?;
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn clear_input(&mut self) -> io::Result<()> {
+        self.stream.set_nonblocking(true)?;
+        let mut buf = [0u8; 1024];
+        let result = loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => break Ok(()),
+                Ok(_) => continue,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        self.stream.set_nonblocking(false)?;
+        result
+    }
+
+    fn can_switch_baud(&self) -> bool {
+        self.baud_switch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_tcp_transport_roundtrip() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let meter = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut request = [0u8; 5];
+            socket.read_exact(&mut request).unwrap();
+            assert_eq!(&request, b"/?!\r\n");
+            socket.write_all(b"/MKS5<2>ADM(M550.2251)\r\n").unwrap();
+        });
+
+        let mut transport = TcpTransport::connect("127.0.0.1", port, 300, 500).unwrap();
+        assert!(!transport.can_switch_baud());
+        transport.write_all(b"/?!\r\n").unwrap();
+        let mut ident = Vec::new();
+        let mut buf = [0u8; 64];
+        while !ident.ends_with(b"\r\n") {
+            let n = transport.read(&mut buf).unwrap();
+            ident.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(ident, b"/MKS5<2>ADM(M550.2251)\r\n");
+        meter.join().unwrap();
+
+        // Peer closed: reads fail instead of returning 0 bytes
+        assert_eq!(transport.read(&mut buf).unwrap_err().kind(), io::ErrorKind::ConnectionAborted);
+        transport.set_baud_rate(9600).unwrap();
+        assert_eq!(transport.baud_rate().unwrap(), 9600);
+    }
+
+    #[test]
+    fn test_link_from_params() {
+        let mut params = ConnectionParams {
+            connection_type: "tcp".to_string(),
+            port: String::new(),
+            baud_rate: 0,
+            timeout_ms: 2000,
+            meter_address: None,
+            password: None,
+            bcc_retries: None,
+            protocol_mode: None,
+            host: Some("10.0.0.5".to_string()),
+            tcp_port: Some(4001),
+            tcp_baud_switch: false,
+        };
+        let link = Link::from_params(&params).unwrap();
+        assert_eq!(link.name(), "10.0.0.5:4001");
+
+        params.tcp_port = None;
+        assert!(matches!(Link::from_params(&params), Err(AppError::InvalidInput { .. })));
+
+        params.connection_type = "optical".to_string();
+        params.port = "COM5".to_string();
+        assert_eq!(Link::from_params(&params).unwrap(), Link::Serial { port: "COM5".to_string() });
+    }
+}
@@ -0,0 +1,226 @@
+//! Virtual meter configuration
+//!
+//! Every field is optional in the JSON file; missing ones fall back to a
+//! three-phase MKS meter with one day of load profile.
+
+use crate::error::AppError;
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Identity, data and line behaviour of the virtual meter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MeterConfig {
+    /// Manufacturer flag, e.g. "MKS"
+    pub manufacturer: String,
+    /// Highest baud rate offered in the identification ('0'-'6')
+    pub baud_char: char,
+    /// MASS generation, sent as `<n>` (empty: no marker)
+    pub generation: String,
+    pub edas_id: String,
+    pub model: String,
+    /// Device address; requests for another address are ignored (empty: answer all)
+    pub address: String,
+    /// Programming mode password of level 1 (P1)
+    pub password: String,
+    /// Passwords of levels 2 and 3 (P2, P3); a missing level uses `password`
+    pub level_passwords: Vec<String>,
+    /// Mode 0 readout lines, e.g. "1.8.0(001234.567*kWh)"
+    pub readout: Vec<String>,
+    /// Lines of the MASS packets by option character ("5"-"9")
+    pub packets: BTreeMap<String, Vec<String>>,
+    /// Load profile entries by profile number ("1"-"3"), e.g. "(24-06-14,00:15)(0.125)"
+    pub load_profiles: BTreeMap<String, Vec<String>>,
+    /// Codes W2 refuses with NAK
+    pub read_only: Vec<String>,
+    /// Codes W2 writes without sending the ACK
+    pub unacknowledged_writes: Vec<String>,
+    /// Split responses into partial blocks of this many bytes (0: one block)
+    pub block_size: usize,
+    /// Blocks of each response (0-based) sent once with a wrong BCC
+    pub bcc_error_blocks: Vec<usize>,
+    /// Delay before every response
+    pub response_delay_ms: u64,
+}
+
+impl MeterConfig {
+    /// Load a configuration from a JSON file
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let file_error = |reason: String| AppError::File { path: path.display().to_string(), reason };
+        let content = std::fs::read_to_string(path).map_err(|e| file_error(e.to_string()))?;
+        serde_json::from_str(&content).map_err(|e| file_error(e.to_string()))
+    }
+
+    /// Identification message without CR LF, e.g. "/MKS5<2>ADM(M550.2251)"
+    pub fn identification(&self) -> String {
+        let mut ident = format!("/{}{}", self.manufacturer, self.baud_char);
+        if !self.generation.is_empty() {
+            ident.push_str(&format!("<{}>", self.generation));
+        }
+        ident.push_str(&self.edas_id);
+        if !self.model.is_empty() {
+            ident.push_str(&format!("({})", self.model));
+        }
+        ident
+    }
+
+    /// Password of `level` (1-3)
+    pub fn password(&self, level: u8) -> &str {
+        match level {
+            0 | 1 => &self.password,
+            _ => self.level_passwords.get(level as usize - 2).unwrap_or(&self.password),
+        }
+    }
+
+    /// Serial number for the P0 password prompt
+    pub fn serial_number(&self) -> String {
+        self.readout.iter()
+            .find_map(|line| line.strip_prefix("0.0.0(").and_then(|v| v.split(')').next()))
+            .unwrap_or(&self.address)
+            .to_string()
+    }
+}
+
+impl Default for MeterConfig {
+    fn default() -> Self {
+        let lines = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let readout = lines(&[
+            "0.0.0(12345678)",
+            "0.2.0(V1.02)",
+            "0.9.1(12:30:45)",
+            "0.9.2(24-06-15)",
+            "0.9.5(6)",
+            "96.1.3(21-03-10)",
+            "96.2.5(21-03-12)",
+            "1.8.0(001234.567*kWh)",
+            "1.8.1(000800.100*kWh)",
+            "1.8.2(000300.200*kWh)",
+            "1.8.3(000134.267*kWh)",
+            "1.8.4(000000.000*kWh)",
+            "1.6.0(002.350*kW)(24-06-10,18:45)",
+            "32.7.0(230.1*V)",
+            "52.7.0(229.8*V)",
+            "72.7.0(231.0*V)",
+            "31.7.0(004.20*A)",
+            "51.7.0(003.90*A)",
+            "71.7.0(004.05*A)",
+            "14.7.0(50.0*Hz)",
+            "33.7.0(0.98)",
+            "53.7.0(0.97)",
+            "73.7.0(0.99)",
+            "96.6.1(1)",
+            "96.3.10(1)",
+        ]);
+
+        let mut packets = BTreeMap::new();
+        packets.insert("5".to_string(), lines(&[
+            "0.0.0(12345678)",
+            "32.7.0(230.1*V)",
+            "52.7.0(229.8*V)",
+            "72.7.0(231.0*V)",
+            "31.7.0(004.20*A)",
+            "51.7.0(003.90*A)",
+            "71.7.0(004.05*A)",
+            "14.7.0(50.0*Hz)",
+            "13.7.0(0.98)",
+            "33.7.0(0.98)",
+            "53.7.0(0.97)",
+            "73.7.0(0.99)",
+            "1.7.0(2.783*kW)",
+            "21.7.0(0.947*kW)",
+            "41.7.0(0.869*kW)",
+            "61.7.0(0.922*kW)",
+            "3.7.0(0.412*kvar)",
+            "9.7.0(2.846*kVA)",
+            "81.7.10(120.1*deg)",
+            "81.7.20(239.8*deg)",
+            "81.7.40(11.5*deg)",
+            "81.7.51(14.1*deg)",
+            "81.7.62(8.1*deg)",
+            "32.7.124(2.1*%)",
+            "52.7.124(2.4*%)",
+            "72.7.124(1.9*%)",
+            "31.7.124(8.5*%)",
+            "51.7.124(9.2*%)",
+            "71.7.124(7.8*%)",
+        ]));
+        packets.insert("6".to_string(), lines(&[
+            "0.0.0(12345678)",
+            "0.9.1(12:30:45)",
+            "0.9.2(24-06-15)",
+            "1.8.0(001234.567*kWh)",
+            "1.8.1(000800.100*kWh)",
+            "1.8.2(000300.200*kWh)",
+            "1.8.3(000134.267*kWh)",
+            "96.6.1(1)",
+        ]));
+        packets.insert("7".to_string(), lines(&[
+            "0.0.0(12345678)",
+            "1.8.0*1(001180.230*kWh)",
+            "1.8.1*1(000760.120*kWh)",
+            "1.8.2*1(000290.004*kWh)",
+            "1.8.3*1(000130.106*kWh)",
+            "1.6.0*1(000.850*kW)(24-05-14,18:30)",
+            "1.8.0*2(001120.904*kWh)",
+        ]));
+        packets.insert("8".to_string(), lines(&[
+            "0.0.0(12345678)",
+            "F.F(00000000)",
+            "96.7.4(1)",
+            "96.77.4*1(24-06-01,10:00;24-06-01,10:05)",
+            "96.7.5(0)",
+            "96.7.6(0)",
+            "96.70(1)",
+            "96.71*6(1)",
+        ]));
+        packets.insert("9".to_string(), lines(&[
+            "0.0.0(12345678)",
+            "96.7.0(2)",
+            "96.7.10*1(24-06-02,03:10;24-06-02,03:42)",
+            "96.7.10*2(24-05-20,22:00;24-05-21,01:15)",
+            "96.77.1(0)",
+            "96.77.2(0)",
+            "96.77.3(0)",
+        ]));
+
+        // One day of 15-minute P.01 entries, hourly P.02
+        let start = NaiveDate::from_ymd_opt(2024, 6, 14).unwrap_or_default().and_hms_opt(0, 0, 0).unwrap_or_default();
+        let profile = |step_min: i64, count: i64, values: &dyn Fn(i64) -> String| {
+            (1..=count)
+                .map(|i| {
+                    let ts = start + Duration::minutes(step_min * i);
+                    format!("({}){}", ts.format("%y-%m-%d,%H:%M"), values(i))
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut load_profiles = BTreeMap::new();
+        load_profiles.insert("1".to_string(), profile(15, 96, &|i| {
+            format!("({:.3})({:.3})", 0.100 + (i % 8) as f64 * 0.025, 0.010)
+        }));
+        load_profiles.insert("2".to_string(), profile(60, 24, &|i| {
+            format!("({:.1})({:.1})({:.1})", 229.0 + (i % 3) as f64, 230.0, 231.0 - (i % 2) as f64)
+        }));
+
+        Self {
+            manufacturer: "MKS".to_string(),
+            baud_char: '5',
+            generation: "2".to_string(),
+            edas_id: "ADM".to_string(),
+            model: "M550.2251".to_string(),
+            address: String::new(),
+            password: "12345678".to_string(),
+            level_passwords: Vec::new(),
+            readout,
+            packets,
+            load_profiles,
+            read_only: lines(&["0.0.0", "1.8.0", "1.8.1", "1.8.2", "1.8.3", "1.8.4"]),
+            unacknowledged_writes: Vec::new(),
+            block_size: 0,
+            bcc_error_blocks: Vec::new(),
+            response_delay_ms: 50,
+        }
+    }
+}
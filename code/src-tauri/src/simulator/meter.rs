@@ -0,0 +1,320 @@
+//! Meter side of IEC 62056-21 Mode C
+//!
+//! Answers the request message with the identification, then serves the
+//! readout or MASS packet chosen by option select, or enters programming mode
+//! (P0 prompt, P1-P3 password, R2/W2, load profiles, B0).
+
+use super::config::MeterConfig;
+use crate::serial::iec62056::{self, control, Frame, FrameDecoder};
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+/// Time the meter waits for ACK/NAK after a partial block
+const BLOCK_ACK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Register W2 writes the P1 password to
+const PASSWORD_REGISTER: &str = "96.96";
+
+/// Where the meter is in the session
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    /// Waiting for a request message
+    Idle,
+    /// Identification sent, waiting for option select
+    Identified,
+    /// Programming mode; `authorized` after an accepted password
+    Programming { authorized: bool },
+}
+
+/// Emulated MASS meter answering on a byte stream
+///
+/// The stream must have a read timeout (reads then fail with `TimedOut` or
+/// `WouldBlock`); a read of 0 bytes ends the session. The BCC of commands is
+/// not verified; the client checks the BCC of every response.
+pub struct VirtualMeter {
+    config: MeterConfig,
+    state: State,
+    baud_rate: u32,
+    decoder: FrameDecoder,
+    /// Last (final) block sent, repeated when the client answers NAK
+    last_block: Vec<u8>,
+}
+
+impl VirtualMeter {
+    pub fn new(config: MeterConfig) -> Self {
+        Self {
+            config,
+            state: State::Idle,
+            baud_rate: 300,
+            decoder: FrameDecoder::new(),
+            last_block: Vec::new(),
+        }
+    }
+
+    /// Current configuration, including values written with W2
+    pub fn config(&self) -> &MeterConfig {
+        &self.config
+    }
+
+    /// Serve one client until it disconnects
+    pub fn serve<S: Read + Write>(&mut self, stream: &mut S) -> io::Result<()> {
+        self.state = State::Idle;
+        self.baud_rate = 300;
+        self.decoder = FrameDecoder::new();
+        self.last_block.clear();
+
+        loop {
+            match self.read_frame(stream, None) {
+                Ok(Some(frame)) => self.handle(stream, frame)?,
+                Ok(None) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Next frame from the client; `None` once `deadline` passes
+    fn read_frame<S: Read>(&mut self, stream: &mut S, deadline: Option<Instant>) -> io::Result<Option<Frame>> {
+        let mut buf = [0u8; 1024];
+        loop {
+            if let Some(frame) = self.decoder.next_frame() {
+                return Ok(Some(frame));
+            }
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return Ok(None);
+            }
+            match stream.read(&mut buf) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "client disconnected")),
+                Ok(n) => self.decoder.push(&buf[..n]),
+                Err(e) if matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn handle<S: Read + Write>(&mut self, stream: &mut S, frame: Frame) -> io::Result<()> {
+        match (self.state, frame) {
+            (_, Frame::Request { address }) => {
+                if !self.config.address.is_empty() && !address.is_empty() && address != self.config.address {
+                    log::debug!("Request for address {} ignored", address);
+                    self.state = State::Idle;
+                    return Ok(());
+                }
+                self.baud_rate = 300;
+                let mut ident = self.config.identification().into_bytes();
+                ident.extend_from_slice(b"\r\n");
+                log::info!("Request {:?} -> {}", address, self.config.identification());
+                self.send(stream, &ident)?;
+                self.state = State::Identified;
+            }
+            (State::Identified, Frame::OptionSelect { baud_char, mode, .. }) => {
+                if let Some(baud) = iec62056::baud_rate_from_char(baud_char) {
+                    log::info!("Option select '{}', baud {} -> {}", mode, self.baud_rate, baud);
+                    self.baud_rate = baud;
+                }
+                match mode {
+                    '0' => {
+                        let payload = readout_payload(&self.config.readout);
+                        self.send_response(stream, &payload)?;
+                        self.state = State::Idle;
+                    }
+                    '5'..='9' => {
+                        let lines = self.config.packets.get(&mode.to_string()).cloned().unwrap_or_default();
+                        self.send_response(stream, &readout_payload(&lines))?;
+                        self.state = State::Idle;
+                    }
+                    '1' => {
+                        let prompt = command_message("P0", format!("({})", self.config.serial_number()).as_bytes());
+                        self.send(stream, &prompt)?;
+                        self.state = State::Programming { authorized: false };
+                    }
+                    _ => self.state = State::Idle,
+                }
+            }
+            (State::Programming { authorized }, Frame::Command { command, data, .. }) => {
+                let data = String::from_utf8_lossy(&data).to_string();
+                match command.as_str() {
+                    "P1" | "P2" | "P3" => {
+                        let level = command.as_bytes()[1] - b'0';
+                        let accepted = data.trim_start_matches('(').trim_end_matches(')') == self.config.password(level);
+                        log::info!("{} password {}", command, if accepted { "accepted" } else { "rejected" });
+                        self.state = State::Programming { authorized: authorized || accepted };
+                        self.send(stream, &[if accepted { control::ACK } else { control::NAK }])?;
+                    }
+                    "R2" => self.handle_read(stream, &data)?,
+                    "W2" => {
+                        let ok = authorized && self.write(&data);
+                        let code = split_line(&data).0;
+                        if ok && self.config.unacknowledged_writes.iter().any(|c| c == code) {
+                            log::info!("W2 {} -> no reply", data);
+                        } else {
+                            log::info!("W2 {} -> {}", data, if ok { "ACK" } else { "NAK" });
+                            self.send(stream, &[if ok { control::ACK } else { control::NAK }])?;
+                        }
+                    }
+                    _ => self.send(stream, &[control::NAK])?,
+                }
+            }
+            (_, Frame::Break) => {
+                log::info!("Break, session ended");
+                self.state = State::Idle;
+            }
+            (_, Frame::Nak) if !self.last_block.is_empty() => {
+                let block = self.last_block.clone();
+                self.send(stream, &block)?;
+            }
+            (_, other) => log::debug!("Ignored {:?} in state {:?}", other, self.state),
+        }
+        Ok(())
+    }
+
+    /// R2: a register ("0.9.1()") or a load profile ("P.01(start;end)")
+    fn handle_read<S: Read + Write>(&mut self, stream: &mut S, data: &str) -> io::Result<()> {
+        let (code, param) = split_line(data);
+        let payload = match code.strip_prefix("P.").and_then(|n| n.parse::<u8>().ok()) {
+            Some(profile) => self.load_profile(profile, param),
+            None => self.find_line(code).map(|line| line.to_string().into_bytes()),
+        };
+        log::info!("R2 {} -> {}", data, if payload.is_some() { "data" } else { "NAK" });
+        match payload {
+            Some(payload) => self.send_response(stream, &payload),
+            None => self.send(stream, &[control::NAK]),
+        }
+    }
+
+    /// Entries of a load profile within "yy-mm-dd,hh:mm;yy-mm-dd,hh:mm" (";" or empty: all)
+    fn load_profile(&self, profile: u8, range: &str) -> Option<Vec<u8>> {
+        let entries = self.config.load_profiles.get(&profile.to_string())?;
+        let (start, end) = range.split_once(';').unwrap_or(("", ""));
+        let mut payload = Vec::new();
+        for entry in entries {
+            let timestamp = entry.trim_start_matches('(').split(')').next().unwrap_or("");
+            if (!start.is_empty() && timestamp < start) || (!end.is_empty() && timestamp > end) {
+                continue;
+            }
+            payload.extend_from_slice(format!("P.{:02}{}\r\n", profile, entry).as_bytes());
+        }
+        Some(payload)
+    }
+
+    fn find_line(&self, code: &str) -> Option<&str> {
+        self.config.readout.iter()
+            .chain(self.config.packets.values().flatten())
+            .find(|line| split_line(line).0 == code)
+            .map(String::as_str)
+    }
+
+    /// W2 "code(value)": replace the line everywhere it appears; `96.96`
+    /// sets the P1 password instead
+    fn write(&mut self, data: &str) -> bool {
+        let (code, value) = split_line(data);
+        if code.is_empty() || self.config.read_only.iter().any(|c| c == code) {
+            return false;
+        }
+        if code == PASSWORD_REGISTER {
+            self.config.password = value.to_string();
+            return true;
+        }
+        let line = format!("{}({})", code, value);
+        let mut found = false;
+        for existing in self.config.readout.iter_mut().chain(self.config.packets.values_mut().flatten()) {
+            if split_line(existing).0 == code {
+                *existing = line.clone();
+                found = true;
+            }
+        }
+        if !found {
+            self.config.readout.push(line);
+        }
+        true
+    }
+
+    /// Send a response as one data block, or as partial blocks with ACK flow control
+    fn send_response<S: Read + Write>(&mut self, stream: &mut S, payload: &[u8]) -> io::Result<()> {
+        let size = self.config.block_size;
+        let chunks: Vec<&[u8]> = if size == 0 || payload.len() <= size {
+            vec![payload]
+        } else {
+            payload.chunks(size).collect()
+        };
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            let last = i + 1 == chunks.len();
+            let block = data_block(chunk, if last { control::ETX } else { control::EOT });
+            let mut corrupt = self.config.bcc_error_blocks.contains(&i);
+            loop {
+                if corrupt {
+                    let mut bad = block.clone();
+                    if let Some(bcc) = bad.last_mut() {
+                        *bcc ^= 0xFF;
+                    }
+                    log::info!("Block {} sent with a wrong BCC", i);
+                    self.send(stream, &bad)?;
+                    corrupt = false;
+                } else {
+                    self.send(stream, &block)?;
+                }
+                if last {
+                    // A NAK for the final block arrives as a new frame
+                    self.last_block = block;
+                    break;
+                }
+                match self.read_frame(stream, Some(Instant::now() + BLOCK_ACK_TIMEOUT))? {
+                    Some(Frame::Ack) => break,
+                    Some(Frame::Nak) => continue,
+                    other => {
+                        log::info!("Transfer aborted after block {}: {:?}", i, other);
+                        self.state = State::Idle;
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn send<W: Write>(&self, stream: &mut W, bytes: &[u8]) -> io::Result<()> {
+        std::thread::sleep(Duration::from_millis(self.config.response_delay_ms));
+        stream.write_all(bytes)?;
+        stream.flush()
+    }
+}
+
+/// Split "code(value)" into code and the value of the first group
+fn split_line(line: &str) -> (&str, &str) {
+    match line.split_once('(') {
+        Some((code, rest)) => (code.trim(), rest.split(')').next().unwrap_or("")),
+        None => (line.trim(), ""),
+    }
+}
+
+/// Readout data: lines, then the "!" end marker
+fn readout_payload(lines: &[String]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for line in lines {
+        payload.extend_from_slice(line.as_bytes());
+        payload.extend_from_slice(b"\r\n");
+    }
+    payload.extend_from_slice(b"!\r\n");
+    payload
+}
+
+/// `STX data ETX|EOT BCC`
+fn data_block(data: &[u8], end: u8) -> Vec<u8> {
+    let mut msg = vec![control::STX];
+    msg.extend_from_slice(data);
+    msg.push(end);
+    msg.push(iec62056::calculate_bcc(&msg[1..]));
+    msg
+}
+
+/// `SOH Cd STX data ETX BCC`
+fn command_message(command: &str, data: &[u8]) -> Vec<u8> {
+    let mut msg = vec![control::SOH];
+    msg.extend_from_slice(command.as_bytes());
+    msg.push(control::STX);
+    msg.extend_from_slice(data);
+    msg.push(control::ETX);
+    msg.push(iec62056::calculate_bcc(&msg[1..]));
+    msg
+}
@@ -0,0 +1,168 @@
+//! Virtual MASS meter
+//!
+//! Emulates an IEC 62056-21 Mode C meter on a TCP socket or (on Unix) a
+//! pseudo-terminal, so the commands can be exercised without hardware.
+//! Used by the `meter_sim` binary.
+
+pub mod config;
+pub mod meter;
+#[cfg(test)]
+pub mod test_support;
+
+pub use config::MeterConfig;
+pub use meter::VirtualMeter;
+
+use std::io;
+use std::net::TcpListener;
+use std::time::Duration;
+
+/// Read timeout of the simulator side of the link
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Accept clients on `listener` one after another, keeping the meter's state
+/// (values written with W2) between connections
+pub fn serve_tcp(listener: TcpListener, meter: &mut VirtualMeter) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        stream.set_read_timeout(Some(POLL_INTERVAL))?;
+        stream.set_nodelay(true)?;
+        log::info!("Client connected: {}", stream.peer_addr().map(|a| a.to_string()).unwrap_or_default());
+        if let Err(e) = meter.serve(&mut stream) {
+            log::warn!("Session ended with error: {}", e);
+        }
+        log::info!("Client disconnected");
+    }
+    Ok(())
+}
+
+/// Pseudo-terminal the app can open as a serial port
+#[cfg(unix)]
+pub struct Pty {
+    master: serialport::TTYPort,
+    path: String,
+    // Kept open so the master does not see a hangup between clients. A plain
+    // file rather than a TTYPort, which would hold a flock on the device and
+    // stop the app from opening it exclusively.
+    _slave: std::fs::File,
+}
+
+#[cfg(unix)]
+impl Pty {
+    pub fn open() -> io::Result<Self> {
+        let (mut master, slave) = serialport::TTYPort::pair().map_err(io::Error::from)?;
+        let path = serialport::SerialPort::name(&slave).unwrap_or_default();
+        let _slave = std::fs::OpenOptions::new().read(true).write(true).open(&path)?;
+        drop(slave);
+        serialport::SerialPort::set_timeout(&mut master, POLL_INTERVAL).map_err(io::Error::from)?;
+        Ok(Self { master, path, _slave })
+    }
+
+    /// Device path for the app, e.g. "/dev/pts/3"
+    pub fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    /// Serve clients on the pseudo-terminal until an I/O error
+    pub fn serve(&mut self, meter: &mut VirtualMeter) -> io::Result<()> {
+        loop {
+            meter.serve(&mut self.master)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::io::{self as cmd_io, ReadConfig};
+    use crate::serial::iec62056::{self, Frame, FrameDecoder, ProtocolMode};
+    use crate::serial::transport::TcpTransport;
+    use std::io::Write;
+    use super::test_support::serve;
+
+    fn handshake(port: u16, mode: ProtocolMode) -> (TcpTransport, FrameDecoder) {
+        let mut link = TcpTransport::connect("127.0.0.1", port, 300, 2000).unwrap();
+        let mut decoder = FrameDecoder::new();
+        let config = ReadConfig::response(2000);
+        link.write_all(&iec62056::build_request_message(None)).unwrap();
+        let ident = cmd_io::read_identification_with(&mut link, &mut decoder, None, &config).unwrap();
+        assert_eq!(ident, "/MKS5<2>ADM(M550.2251)");
+        link.write_all(&iec62056::build_ack_message(mode, '5')).unwrap();
+        (link, decoder)
+    }
+
+    #[test]
+    fn test_simulator_readout_with_partial_blocks() {
+        let port = serve(MeterConfig { block_size: 64, bcc_error_blocks: vec![1], ..Default::default() });
+        let (mut link, mut decoder) = handshake(port, ProtocolMode::Readout);
+
+        let transfer = cmd_io::read_data_blocks(&mut link, &mut decoder, None, &ReadConfig::response(2000), |_, _| {}, |_: &cmd_io::BlockTransfer| {}).unwrap();
+        assert!(transfer.complete);
+        assert_eq!(transfer.naks, 1);
+        assert!(transfer.blocks > 2);
+        let items = iec62056::parse_data_block(&String::from_utf8_lossy(&transfer.data));
+        assert!(items.iter().any(|i| i.code == "1.8.0" && i.value == "001234.567"));
+    }
+
+    #[test]
+    fn test_simulator_programming_session() {
+        let port = serve(MeterConfig::default());
+        let (mut link, mut decoder) = handshake(port, ProtocolMode::Programming);
+        let config = ReadConfig::response(2000);
+        let mut reply = |link: &mut TcpTransport, msg: &[u8]| {
+            link.write_all(msg).unwrap();
+            cmd_io::read_reply(link, &mut decoder, None, &config).unwrap()
+        };
+
+        assert!(matches!(reply(&mut link, &[]), Some(Frame::PasswordPrompt { .. })));
+        // Writes need the password
+        assert_eq!(reply(&mut link, &iec62056::build_write_command("0.9.1", "08:00:00")), Some(Frame::Nak));
+        assert_eq!(reply(&mut link, &iec62056::build_password_command(1, "00000000")), Some(Frame::Nak));
+        assert_eq!(reply(&mut link, &iec62056::build_password_command(1, "12345678")), Some(Frame::Ack));
+        assert_eq!(reply(&mut link, &iec62056::build_write_command("0.9.1", "08:00:00")), Some(Frame::Ack));
+        assert_eq!(reply(&mut link, &iec62056::build_write_command("1.8.0", "0")), Some(Frame::Nak));
+        assert_eq!(
+            reply(&mut link, &iec62056::build_read_command("0.9.1")),
+            Some(Frame::DataBlock { data: b"0.9.1(08:00:00)".to_vec(), bcc_ok: true })
+        );
+
+        let profile = reply(&mut link, &iec62056::build_load_profile_command(1, Some("24-06-14,01:00"), Some("24-06-14,02:00")));
+        match profile {
+            Some(Frame::DataBlock { data, bcc_ok: true }) => {
+                let text = String::from_utf8(data).unwrap();
+                assert_eq!(text.lines().count(), 5);
+                assert!(text.starts_with("P.01(24-06-14,01:00)"));
+            }
+            other => panic!("unexpected frame {:?}", other),
+        }
+        assert_eq!(reply(&mut link, &iec62056::build_read_command("9.9.9")), Some(Frame::Nak));
+    }
+
+    #[test]
+    fn test_simulator_password_change() {
+        let config = MeterConfig { level_passwords: vec!["22222222".to_string()], ..Default::default() };
+        let port = serve(config);
+        let session = || {
+            let (mut link, mut decoder) = handshake(port, ProtocolMode::Programming);
+            let config = ReadConfig::response(2000);
+            let mut reply = move |msg: &[u8]| {
+                link.write_all(msg).unwrap();
+                cmd_io::read_reply(&mut link, &mut decoder, None, &config).unwrap()
+            };
+            assert!(matches!(reply(&[]), Some(Frame::PasswordPrompt { .. })));
+            reply
+        };
+
+        // 96.96 holds the P1 password; 96.96.2 is an ordinary register
+        let mut reply = session();
+        assert_eq!(reply(&iec62056::build_password_command(1, "12345678")), Some(Frame::Ack));
+        assert_eq!(reply(&iec62056::build_write_command("96.96", "87654321")), Some(Frame::Ack));
+        assert_eq!(reply(&iec62056::build_write_command("96.96.2", "33333333")), Some(Frame::Ack));
+        drop(reply);
+
+        let mut reply = session();
+        assert_eq!(reply(&iec62056::build_password_command(1, "12345678")), Some(Frame::Nak));
+        assert_eq!(reply(&iec62056::build_password_command(1, "87654321")), Some(Frame::Ack));
+        assert_eq!(reply(&iec62056::build_password_command(2, "33333333")), Some(Frame::Nak));
+        assert_eq!(reply(&iec62056::build_password_command(2, "22222222")), Some(Frame::Ack));
+    }
+}
@@ -0,0 +1,29 @@
+//! Virtual meters for the tests of the command and serial layers
+
+use super::{serve_tcp, MeterConfig, VirtualMeter};
+use crate::commands::SessionOptions;
+use crate::serial::Link;
+use std::net::TcpListener;
+
+/// Serve a meter with `config`, without its response delay, on a free local
+/// port and return the port
+pub fn serve(config: MeterConfig) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let config = MeterConfig { response_delay_ms: 0, ..config };
+    std::thread::spawn(move || serve_tcp(listener, &mut VirtualMeter::new(config)));
+    port
+}
+
+/// Options of a session with a meter served as by `serve`
+pub fn start_meter(config: MeterConfig) -> SessionOptions {
+    SessionOptions {
+        link: Link::Tcp { host: "127.0.0.1".to_string(), port: serve(config), baud_switch: false },
+        meter_address: None,
+        connection_type: "tcp".to_string(),
+        baud_rate: 300,
+        timeout_ms: 2000,
+        bcc_retries: 3,
+        protocol_mode: None,
+    }
+}
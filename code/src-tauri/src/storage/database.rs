@@ -1,5 +1,6 @@
 //! SQLite database for storing meter sessions and reports
 
+use crate::error::AppError;
 use rusqlite::{Connection, Result as SqlResult, params};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -36,6 +37,24 @@ pub struct Report {
     pub created_at: String,
 }
 
+/// Audit log record of one register write
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub meter_serial: String,
+    pub meter_flag: String,
+    pub operator: String,
+    pub obis_code: String,
+    /// Value before the write, `None` when it was not read
+    pub previous_value: Option<String>,
+    pub new_value: String,
+    /// "ACK", "NAK" or "ERROR"
+    pub result: String,
+    pub app_version: String,
+}
+
 /// App settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
@@ -99,6 +118,23 @@ impl Database {
             [],
         )?;
 
+        // Audit log table
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                meter_serial TEXT NOT NULL,
+                meter_flag TEXT NOT NULL,
+                operator TEXT NOT NULL,
+                obis_code TEXT NOT NULL,
+                previous_value TEXT,
+                new_value TEXT NOT NULL,
+                result TEXT NOT NULL,
+                app_version TEXT NOT NULL
+            )",
+            [],
+        )?;
+
         // Create indexes
         self.conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_sessions_meter_serial ON sessions(meter_serial)",
@@ -108,6 +144,10 @@ impl Database {
             "CREATE INDEX IF NOT EXISTS idx_sessions_timestamp ON sessions(timestamp DESC)",
             [],
         )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_audit_log_meter_serial ON audit_log(meter_serial)",
+            [],
+        )?;
 
         Ok(())
     }
@@ -293,6 +333,51 @@ impl Database {
         rows.collect()
     }
 
+    /// Append an audit log entry
+    pub fn save_audit_entry(&self, entry: &AuditEntry) -> SqlResult<i64> {
+        self.conn.execute(
+            "INSERT INTO audit_log (timestamp, meter_serial, meter_flag, operator, obis_code, previous_value, new_value, result, app_version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                entry.timestamp,
+                entry.meter_serial,
+                entry.meter_flag,
+                entry.operator,
+                entry.obis_code,
+                entry.previous_value,
+                entry.new_value,
+                entry.result,
+                entry.app_version,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get the most recent audit log entries, optionally of one meter only
+    pub fn get_audit_log(&self, meter_serial: Option<&str>, limit: u32) -> SqlResult<Vec<AuditEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, meter_serial, meter_flag, operator, obis_code, previous_value, new_value, result, app_version
+             FROM audit_log WHERE ?1 IS NULL OR meter_serial = ?1 ORDER BY id DESC LIMIT ?2"
+        )?;
+
+        let rows = stmt.query_map(params![meter_serial, limit], |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                meter_serial: row.get(2)?,
+                meter_flag: row.get(3)?,
+                operator: row.get(4)?,
+                obis_code: row.get(5)?,
+                previous_value: row.get(6)?,
+                new_value: row.get(7)?,
+                result: row.get(8)?,
+                app_version: row.get(9)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
     /// Get a setting value
     pub fn get_setting(&self, key: &str) -> SqlResult<Option<String>> {
         let mut stmt = self.conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
@@ -322,17 +407,20 @@ impl Database {
 }
 
 /// Initialize the database
-pub fn init_database(app_data_dir: &PathBuf) -> Result<(), String> {
+pub fn init_database(app_data_dir: &PathBuf) -> Result<(), AppError> {
     let db_path = app_data_dir.join("omnicore.db");
 
     // Create directory if it doesn't exist
     if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(parent).map_err(|e| AppError::File {
+            path: parent.display().to_string(),
+            reason: e.to_string(),
+        })?;
     }
 
-    let db = Database::new(&db_path).map_err(|e| e.to_string())?;
+    let db = Database::new(&db_path)?;
 
-    let mut guard = DATABASE.lock().map_err(|e| e.to_string())?;
+    let mut guard = DATABASE.lock()?;
     *guard = Some(db);
 
     log::info!("Database initialized at {:?}", db_path);
@@ -340,8 +428,13 @@ pub fn init_database(app_data_dir: &PathBuf) -> Result<(), String> {
 }
 
 /// Get database instance
-pub fn get_database() -> Result<std::sync::MutexGuard<'static, Option<Database>>, String> {
-    DATABASE.lock().map_err(|e| e.to_string())
+pub fn get_database() -> Result<std::sync::MutexGuard<'static, Option<Database>>, AppError> {
+    Ok(DATABASE.lock()?)
+}
+
+/// Error for database commands called before `init_database`
+pub fn not_initialized() -> AppError {
+    AppError::Database { reason: "reason_database_not_initialized".to_string() }
 }
 
 #[cfg(test)]
@@ -355,6 +448,7 @@ mod tests {
         let temp_dir = std::env::temp_dir().join("omnicore_test");
         fs::create_dir_all(&temp_dir).unwrap();
         let db_path = temp_dir.join("test.db");
+        fs::remove_file(&db_path).ok();
 
         let db = Database::new(&db_path).unwrap();
 
@@ -383,6 +477,29 @@ mod tests {
         let value = db.get_setting("theme").unwrap();
         assert_eq!(value, Some("dark".to_string()));
 
+        // Test audit log
+        let entry = |meter_serial: &str, obis_code: &str| AuditEntry {
+            id: 0,
+            timestamp: "2024-12-15 14:31:00".to_string(),
+            meter_serial: meter_serial.to_string(),
+            meter_flag: "MKS".to_string(),
+            operator: "test".to_string(),
+            obis_code: obis_code.to_string(),
+            previous_value: None,
+            new_value: "00000600999999999999999999999999".to_string(),
+            result: "ACK".to_string(),
+            app_version: "0.1.0".to_string(),
+        };
+        db.save_audit_entry(&entry("123456789", "96.50")).unwrap();
+        db.save_audit_entry(&entry("987654321", "96.50")).unwrap();
+        db.save_audit_entry(&entry("123456789", "96.60")).unwrap();
+
+        let log = db.get_audit_log(Some("123456789"), 10).unwrap();
+        let codes: Vec<&str> = log.iter().map(|e| e.obis_code.as_str()).collect();
+        assert_eq!(codes, vec!["96.60", "96.50"]);
+        assert_eq!(db.get_audit_log(None, 10).unwrap().len(), 3);
+        assert_eq!(db.get_audit_log(None, 1).unwrap()[0].meter_serial, "123456789");
+
         // Cleanup
         fs::remove_file(&db_path).ok();
     }
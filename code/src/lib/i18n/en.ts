@@ -58,6 +58,9 @@ export const en = {
   opticalProbe: "Optical Probe (IEC 62056-21)",
   serialDirect: "Serial Direct (RS485/RS232)",
   tcpIpNetwork: "TCP/IP Network",
+  tcpHost: "Host / IP Address",
+  tcpPort: "TCP Port",
+  tcpBaudSwitch: "Converter follows baud switch",
   comPort: "COM Port",
   baudRate: "Baud Rate",
   timeout: "Timeout (ms)",
@@ -225,6 +228,7 @@ export const en = {
   dataReceived: "Data",
   lines: "Lines",
   elapsed: "Elapsed",
+  operationCancelled: "Operation cancelled, partial data shown",
 
   // Events
   voltageWarnings: "Voltage Warnings",
@@ -273,6 +277,12 @@ export const en = {
   importantWarning: "Important Warning",
   passwordWarning:
     "3 wrong attempts will lock the meter for 6 hours. Be careful!",
+  passwordChangeDescription: "Change the meter programming password.",
+  passwordSameAsCurrent: "The new password must differ from the current one",
+  passwordChangeConfirm: "The new password will be written and tested with a new login. Continue?",
+  passwordChangeSuccess: "Password changed and verified with a new login",
+  passwordOldKept: "The meter refused the new password, the current password is still active",
+  passwordChangeUnknown: "The new password was not accepted and the current one was not tried. Check the meter before another attempt: 3 wrong passwords lock it for 6 hours!",
 
   dstStatus: "DST Status",
   enabled: "Enabled",
@@ -511,6 +521,14 @@ export const en = {
   energyExporting: "Export",
   neutralCurrent: "Neutral Current",
   refreshMeasurements: "Refresh Measurements",
+  readTechnicalQuality: "Read Technical Quality",
+  activePower: "Active Power",
+  reactivePower: "Reactive Power",
+  apparentPower: "Apparent Power",
+  voltageAngle: "Voltage Angle",
+  currentAngle: "Current Angle",
+  voltageThd: "Voltage THD",
+  currentThd: "Current THD",
 
   // Energy Page
   energyDescription: "Current and 12-month energy consumption data.",
@@ -537,6 +555,7 @@ export const en = {
   tariffChanges: "Tariff Changes",
   noWarnings: "No warning records",
   warningCount: "Total Warnings",
+  readWarningLog: "Read Warning Log",
 
   // Outages Page
   outagesDescription: "Phase outages and durations.",
@@ -546,6 +565,7 @@ export const en = {
   shortOutages: "Short Outages",
   outageCount: "Outage Count",
   totalDuration: "Total Duration",
+  readOutageLog: "Read Outage Log",
   noOutages: "No outage records",
   noData: "No data available",
   fullReadRequired: "Perform a full readout to view this data.",
@@ -574,6 +594,12 @@ export const en = {
   disconnectRelay: "Disconnect Energy",
   relayWarning: "This operation remotely controls the meter. Use with caution!",
   confirmDisconnect: "Are you sure you want to disconnect energy?",
+  confirmConnect: "Are you sure you want to connect energy?",
+  relayTargetMeter: "Target meter",
+  relaySerialRequired: "Read the meter first so its serial number can be checked before switching",
+  relaySwitchSuccess: "Relay switched and state confirmed",
+  relayNotConfirmed: "The relay state read back does not match, check the meter",
+  relayMeterMismatch: "The connected meter is not the meter that was read. Read the meter again.",
   demandButtonStatus: "Demand Button Status",
   demandButtonEnabled: "Enabled",
   demandButtonDisabled: "Disabled",
@@ -592,6 +618,7 @@ export const en = {
   presetStatus: "Status Codes",
   obisPlaceholder: "E.g.: 1.8.0",
   noValue: "No value",
+  untrustedValue: "Unreliable value: BCC error persisted after retransmission",
   presets: "Presets",
   noObisSelected: "No OBIS code selected to read",
   obisReadComplete: "OBIS read complete",
@@ -614,15 +641,21 @@ export const en = {
   saveDstSettings: "Save DST Settings",
   savingDstSettings: "Saving DST settings...",
   dstSaveSuccess: "DST settings saved successfully!",
+  dstVerifyFailed: "DST settings written, but the read-back values differ",
+  dstRuleMarchOctober: "Last Sunday of March / October",
+  dstRuleDisabled: "Disabled (Turkey since 2016)",
+  fillPeriods: "Fill Periods",
   writeToMeter: "Write To Meter",
   tariffSettingsDescription: "Configure tariff time zones for weekdays, Saturday, and Sunday.",
   savingTariffSettings: "Saving tariff settings...",
   tariffSaveSuccess: "Tariff settings saved successfully!",
+  tariffVerifyFailed: "Tariff tables written, but the read-back values differ",
   addTimeSlot: "Add Time Slot",
   tariff: "Tariff",
   periodSettingsDescription: "Configure demand period, load profile period, and outage threshold.",
   savingPeriodSettings: "Saving period settings...",
   periodSaveSuccess: "Period settings saved successfully!",
+  programRolledBack: "Programming failed, the previous values were restored where possible",
   demandResetDescription: "Reset the maximum demand value. This action cannot be undone.",
   demandResetExecuting: "Executing demand reset...",
   demandResetSuccess: "Demand reset completed successfully!",
@@ -58,6 +58,9 @@ export const tr = {
   opticalProbe: "Optik Prob (IEC 62056-21)",
   serialDirect: "Seri Direkt (RS485/RS232)",
   tcpIpNetwork: "TCP/IP Ağ",
+  tcpHost: "Sunucu / IP Adresi",
+  tcpPort: "TCP Portu",
+  tcpBaudSwitch: "Dönüştürücü baud değişimini izler",
   comPort: "COM Port",
   baudRate: "Baud Hızı",
   timeout: "Zaman Aşımı (ms)",
@@ -225,6 +228,7 @@ export const tr = {
   dataReceived: "Veri",
   lines: "Satır",
   elapsed: "Süre",
+  operationCancelled: "İşlem iptal edildi, kısmi veri gösteriliyor",
 
   // Events
   voltageWarnings: "Gerilim Uyarıları",
@@ -273,6 +277,12 @@ export const tr = {
   importantWarning: "Önemli Uyarı",
   passwordWarning:
     "3 yanlış deneme sonrası sayaç 6 saat kilitlenir. Dikkatli olun!",
+  passwordChangeDescription: "Sayaç programlama şifresini değiştirin.",
+  passwordSameAsCurrent: "Yeni şifre mevcut şifreden farklı olmalıdır",
+  passwordChangeConfirm: "Yeni şifre yazılacak ve yeni bir girişle denenecek. Devam edilsin mi?",
+  passwordChangeSuccess: "Şifre değiştirildi ve yeni girişle doğrulandı",
+  passwordOldKept: "Sayaç yeni şifreyi reddetti, mevcut şifre geçerli",
+  passwordChangeUnknown: "Yeni şifre kabul edilmedi, mevcut şifre denenmedi. Tekrar denemeden önce sayacı kontrol edin: 3 hatalı şifre sayacı 6 saat kilitler!",
 
   dstStatus: "Yaz Saati Durumu",
   enabled: "Aktif",
@@ -513,6 +523,14 @@ export const tr = {
   energyExporting: "Verme",
   neutralCurrent: "Nötr Akımı",
   refreshMeasurements: "Ölçümleri Yenile",
+  readTechnicalQuality: "Teknik Kaliteyi Oku",
+  activePower: "Aktif Güç",
+  reactivePower: "Reaktif Güç",
+  apparentPower: "Görünür Güç",
+  voltageAngle: "Gerilim Açısı",
+  currentAngle: "Akım Açısı",
+  voltageThd: "Gerilim THD",
+  currentThd: "Akım THD",
 
   // Energy Page
   energyDescription: "Güncel ve 12 aylık enerji tüketim bilgileri.",
@@ -539,6 +557,7 @@ export const tr = {
   tariffChanges: "Tarife Değişiklikleri",
   noWarnings: "Uyarı kaydı yok",
   warningCount: "Toplam Uyarı",
+  readWarningLog: "Uyarı Kaydını Oku",
 
   // Outages Page
   outagesDescription: "Faz kesintileri ve süreleri.",
@@ -548,6 +567,7 @@ export const tr = {
   shortOutages: "Kısa Kesintiler",
   outageCount: "Kesinti Sayısı",
   totalDuration: "Toplam Süre",
+  readOutageLog: "Kesinti Kaydını Oku",
   noOutages: "Kesinti kaydı yok",
   noData: "Veri mevcut değil",
   fullReadRequired: "Bu veriyi görüntülemek için tam okuma yapın.",
@@ -576,6 +596,12 @@ export const tr = {
   disconnectRelay: "Enerjiyi Kes",
   relayWarning: "Bu işlem sayacı uzaktan kontrol eder. Dikkatli kullanın!",
   confirmDisconnect: "Enerjiyi kesmek istediğinizden emin misiniz?",
+  confirmConnect: "Enerjiyi açmak istediğinizden emin misiniz?",
+  relayTargetMeter: "Hedef sayaç",
+  relaySerialRequired: "Anahtarlamadan önce seri numarasının kontrol edilebilmesi için sayacı okuyun",
+  relaySwitchSuccess: "Röle anahtarlandı ve durumu doğrulandı",
+  relayNotConfirmed: "Geri okunan röle durumu uyuşmuyor, sayacı kontrol edin",
+  relayMeterMismatch: "Bağlı sayaç okunan sayaç değil. Sayacı tekrar okuyun.",
   demandButtonStatus: "Talep Butonu Durumu",
   demandButtonEnabled: "Aktif",
   demandButtonDisabled: "Pasif",
@@ -594,6 +620,7 @@ export const tr = {
   presetStatus: "Durum Kodları",
   obisPlaceholder: "Örn: 1.8.0",
   noValue: "Değer yok",
+  untrustedValue: "Güvenilmez değer: BCC hatası tekrar sonrasında da devam etti",
   presets: "Hazır Setler",
   noObisSelected: "Okunacak OBIS kodu seçilmedi",
   obisReadComplete: "OBIS okuma tamamlandı",
@@ -616,15 +643,21 @@ export const tr = {
   saveDstSettings: "YSU Ayarlarını Kaydet",
   savingDstSettings: "YSU ayarları kaydediliyor...",
   dstSaveSuccess: "YSU ayarları başarıyla kaydedildi!",
+  dstVerifyFailed: "YSU ayarları yazıldı, ancak geri okunan değerler farklı",
+  dstRuleMarchOctober: "Mart / Ekim ayının son pazarı",
+  dstRuleDisabled: "Kapalı (2016'dan beri Türkiye)",
+  fillPeriods: "Dönemleri Doldur",
   writeToMeter: "Sayacıya Yaz",
   tariffSettingsDescription: "Hafta içi, Cumartesi ve Pazar için tarife zaman dilimlerini yapılandırın.",
   savingTariffSettings: "Tarife ayarları kaydediliyor...",
   tariffSaveSuccess: "Tarife ayarları başarıyla kaydedildi!",
+  tariffVerifyFailed: "Tarife tabloları yazıldı, ancak geri okunan değerler farklı",
   addTimeSlot: "Zaman Dilimi Ekle",
   tariff: "Tarife",
   periodSettingsDescription: "Demant periyodu, yük profili periyodu ve kesinti eşiği ayarları.",
   savingPeriodSettings: "Periyot ayarları kaydediliyor...",
   periodSaveSuccess: "Periyot ayarları başarıyla kaydedildi!",
+  programRolledBack: "Programlama başarısız, önceki değerler mümkün olduğunca geri yüklendi",
   demandResetDescription: "Maksimum demant değerini sıfırlar. Bu işlem geri alınamaz.",
   demandResetExecuting: "Demant sıfırlama yapılıyor...",
   demandResetSuccess: "Demant sıfırlama başarıyla tamamlandı!",
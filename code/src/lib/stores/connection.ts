@@ -7,12 +7,14 @@ export interface SerialPort {
 }
 
 export interface ConnectionParams {
-  connectionType: "optical" | "serial" | "auto" | "tcpip";
+  connectionType: "optical" | "serial" | "auto" | "tcp" | "replay";
   port: string;
   baudRate: number;
   timeout: number;
   meterAddress?: string;
   password?: string;
+  host?: string;
+  tcpPort?: number;
 }
 
 export interface ConnectionState {
@@ -1,6 +1,7 @@
 import { writable, derived } from "svelte/store";
 import { tr } from "$lib/i18n/tr";
 import { en } from "$lib/i18n/en";
+import { setLanguage } from "$lib/utils/tauri";
 
 export type Locale = "tr" | "en";
 
@@ -20,11 +21,13 @@ function createLocaleStore() {
     setLocale: (locale: Locale) => {
       localStorage.setItem("locale", locale);
       set(locale);
+      setLanguage(locale).catch(() => {});
     },
     init: () => {
       const stored = localStorage.getItem("locale") as Locale | null;
       if (stored && (stored === "tr" || stored === "en")) {
         set(stored);
+        setLanguage(stored).catch(() => {});
       }
     },
   };
@@ -1,4 +1,5 @@
 import { writable, derived } from "svelte/store";
+import type { MassData, ObisDataItem } from "$lib/utils/tauri";
 
 export interface ShortReadData {
   // Meter Identity
@@ -20,23 +21,23 @@ export interface ShortReadData {
   activeEnergyImportT4: number;
 
   // Active Energy Export (-) - bidirectional only
-  activeEnergyExportTotal?: number;
-  activeEnergyExportT1?: number;
-  activeEnergyExportT2?: number;
-  activeEnergyExportT3?: number;
-  activeEnergyExportT4?: number;
+  activeEnergyExportTotal?: number | null;
+  activeEnergyExportT1?: number | null;
+  activeEnergyExportT2?: number | null;
+  activeEnergyExportT3?: number | null;
+  activeEnergyExportT4?: number | null;
 
   // Reactive Energy - Kombi only
-  reactiveEnergyInductiveImport?: number;
-  reactiveEnergyCapacitiveImport?: number;
-  reactiveEnergyInductiveExport?: number;
-  reactiveEnergyCapacitiveExport?: number;
+  reactiveEnergyInductiveImport?: number | null;
+  reactiveEnergyCapacitiveImport?: number | null;
+  reactiveEnergyInductiveExport?: number | null;
+  reactiveEnergyCapacitiveExport?: number | null;
 
   // Maximum Demand
   maxDemandImport: number;
   maxDemandImportTimestamp: string;
-  maxDemandExport?: number;
-  maxDemandExportTimestamp?: string;
+  maxDemandExport?: number | null;
+  maxDemandExportTimestamp?: string | null;
 
   // Instantaneous Values
   voltageL1: number;
@@ -61,6 +62,12 @@ export interface ShortReadData {
 
   // Timestamp (epoch ms) captured when 0.9.1 and 0.9.2 were received from meter
   timeOf09xRead?: number | null;
+
+  // All OBIS lines of the readout with typed values (absent in older session files)
+  obisItems?: ObisDataItem[];
+
+  // Event logs, DST and tariff tables and demand resets (absent in older session files)
+  mass?: MassData;
 }
 
 export interface FullReadData extends ShortReadData {
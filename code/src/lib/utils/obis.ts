@@ -0,0 +1,90 @@
+/**
+ * Helpers for typed OBIS values returned by the backend
+ */
+import type { ObisDataItem, ObisValue } from "./tauri";
+
+// Numeric OBIS group (0-255) without leading zeros, or null
+function obisValue(group: string): number | null {
+  const g = group.trim();
+  if (!/^\d+$/.test(g)) return null;
+  const value = Number(g);
+  return value <= 255 ? value : null;
+}
+
+// Reduced canonical form of an OBIS code, the same as `ObisCode::reduced` in
+// serial/obis.rs: "1-0:1.8.0*255" -> "1.8.0", "1.8" -> "1.8.0", "f.f" -> "F.F.0".
+// obis-vectors.json holds the cases both must agree on (run by the Rust tests).
+// Invalid codes come back trimmed and upper-cased.
+export function normalizeObis(code: string): string {
+  const c = code.trim().toUpperCase();
+  const [body, period, ...extra] = c.split("*");
+  if (extra.length > 0) return c;
+
+  let f = period !== undefined ? obisValue(period) : null;
+  if (period !== undefined && f === null) return c;
+
+  const colon = body.indexOf(":");
+  if (colon >= 0) {
+    const ab = body.slice(0, colon).split("-");
+    if (ab.length !== 2 || ab.some((g) => obisValue(g) === null)) return c;
+  }
+  let groups = body.slice(colon + 1).split(".");
+
+  // Dotted full form A.B.C.D.E(.F)
+  if (colon < 0 && period === undefined && (groups.length === 5 || groups.length === 6)) {
+    if (groups.slice(0, 2).some((g) => obisValue(g) === null)) return c;
+    if (groups.length === 6) {
+      f = obisValue(groups[5]);
+      if (f === null) return c;
+    }
+    groups = groups.slice(2, 5);
+  }
+  if (groups.length !== 2 && groups.length !== 3) return c;
+
+  const parsed: string[] = [];
+  for (const group of groups) {
+    const g = group.trim();
+    const value = obisValue(g);
+    if (value !== null) parsed.push(String(value));
+    else if (/^[A-Z]$/.test(g)) parsed.push(g);
+    else return c;
+  }
+
+  // "1.8" is the same object as "1.8.0"
+  if (parsed.length === 2) parsed.push("0");
+  // Load profile codes keep their two-digit form: P.01
+  if (parsed[0] === "P" && /^\d+$/.test(parsed[1])) parsed[1] = parsed[1].padStart(2, "0");
+
+  const reduced = parsed.join(".");
+  return f !== null && f !== 255 ? `${reduced}*${f}` : reduced;
+}
+
+// Find an item by OBIS code in any notation (e.g. "1.8.1*3", "1-0:1.8.0")
+export function findObis(items: ObisDataItem[] | undefined | null, code: string): ObisDataItem | undefined {
+  const wanted = normalizeObis(code);
+  return items?.find((item) => normalizeObis(item.code) === wanted);
+}
+
+// Numeric value, taking the first element of a multi-value
+export function obisNumber(value: ObisValue | undefined): number | null {
+  if (!value) return null;
+  if (value.type === "number") return value.value;
+  if (value.type === "multi" && value.values.length > 0) return obisNumber(value.values[0]);
+  return null;
+}
+
+// Date-time value as "yy-mm-dd,hh:mm" (meter notation), or the first one in a multi-value
+export function obisDateTime(value: ObisValue | undefined): string | null {
+  if (!value) return null;
+  if (value.type === "dateTime") {
+    // "2024-12-01T13:30:00" -> "24-12-01,13:30"
+    return `${value.datetime.slice(2, 10)},${value.datetime.slice(11, 16)}`;
+  }
+  if (value.type === "multi") {
+    for (const v of value.values) {
+      const dt = obisDateTime(v);
+      if (dt) return dt;
+    }
+  }
+  return null;
+}
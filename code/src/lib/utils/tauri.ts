@@ -1,7 +1,7 @@
 /**
  * Tauri API wrapper for meter communication
  */
-import { invoke } from "@tauri-apps/api/core";
+import { invoke as tauriInvoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
 // Types
@@ -18,6 +18,8 @@ export interface MeterIdentity {
   baudRateChar: string;
   generation: string;
   serialNumber: string | null;
+  protocolMode: string;
+  rawIdent: string;
 }
 
 export interface ConnectionParams {
@@ -27,6 +29,31 @@ export interface ConnectionParams {
   timeoutMs: number;
   meterAddress: string | null;
   password: string | null;
+  bccRetries?: number | null;
+  protocolMode?: string | null;
+  host?: string | null;
+  tcpPort?: number | null;
+  tcpBaudSwitch?: boolean;
+}
+
+// Typed OBIS value (mirrors serial::obis::ObisValue)
+export type ObisValue =
+  | { type: "number"; value: number; unit: string | null }
+  | { type: "date"; date: string }
+  | { type: "time"; time: string }
+  | { type: "dateTime"; datetime: string }
+  | { type: "interval"; start: ObisValue; end: ObisValue }
+  | { type: "multi"; values: ObisValue[] }
+  | { type: "hex"; value: string }
+  | { type: "empty" }
+  | { type: "text"; value: string };
+
+export interface ObisDataItem {
+  code: string;
+  value: string;
+  unit: string | null;
+  parsed: ObisValue;
+  untrusted: boolean;
 }
 
 export interface ShortReadResult {
@@ -59,6 +86,34 @@ export interface ShortReadResult {
   batteryStatus: "full" | "low" | "";
   relayStatus: "active" | "passive" | "";
   rawData: string | null;
+  untrustedCodes: string[];
+  obisItems: ObisDataItem[];
+  mass: MassData;
+  cancelled: boolean;
+}
+
+export interface FullReadResult extends ShortReadResult {
+  activeEnergyExportTotal: number | null;
+  activeEnergyExportT1: number | null;
+  activeEnergyExportT2: number | null;
+  activeEnergyExportT3: number | null;
+  activeEnergyExportT4: number | null;
+  reactiveEnergyImportTotal: number | null;
+  reactiveEnergyExportTotal: number | null;
+  reactiveEnergyInductiveImport: number | null;
+  reactiveEnergyCapacitiveExport: number | null;
+  reactiveEnergyInductiveExport: number | null;
+  reactiveEnergyCapacitiveImport: number | null;
+  maxDemandExport: number | null;
+  maxDemandExportTimestamp: string | null;
+  billingPeriods: BillingPeriodValues[];
+  demandResetCount: number | null;
+  outageCount: number | null;
+  voltageWarningCount: number | null;
+  currentWarningCount: number | null;
+  magneticWarningCount: number | null;
+  topCoverOpenings: number | null;
+  terminalCoverOpenings: number | null;
 }
 
 export interface ProgressEvent {
@@ -79,6 +134,73 @@ export function isTauri(): boolean {
   return typeof window !== "undefined" && "__TAURI_INTERNALS__" in window;
 }
 
+// Structured backend error (mirrors error::AppError)
+export type AppErrorCode =
+  | "NotConnected"
+  | "PortOpen"
+  | "Io"
+  | "BaudSwitch"
+  | "Timeout"
+  | "Cancelled"
+  | "NoIdentification"
+  | "InvalidIdentification"
+  | "UnsupportedMode"
+  | "BccMismatch"
+  | "Nak"
+  | "PasswordRejected"
+  | "InvalidPassword"
+  | "NotInProgrammingMode"
+  | "MeterMismatch"
+  | "InvalidInput"
+  | "InvalidResponse"
+  | "Database"
+  | "File"
+  | "Internal";
+
+export interface AppErrorPayload {
+  code: AppErrorCode;
+  message: string;
+  context: Record<string, unknown>;
+}
+
+/**
+ * Error thrown by command wrappers; `${error}` gives the localized message
+ */
+export class CommandError extends Error {
+  code: AppErrorCode;
+  context: Record<string, unknown>;
+
+  constructor(payload: AppErrorPayload) {
+    super(payload.message);
+    this.name = "CommandError";
+    this.code = payload.code;
+    this.context = payload.context;
+  }
+
+  toString(): string {
+    return this.message;
+  }
+}
+
+function isAppErrorPayload(value: unknown): value is AppErrorPayload {
+  return typeof value === "object" && value !== null && "code" in value && "message" in value;
+}
+
+async function invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
+  try {
+    return await tauriInvoke<T>(cmd, args);
+  } catch (e) {
+    throw isAppErrorPayload(e) ? new CommandError(e) : e;
+  }
+}
+
+export async function setLanguage(lang: string): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+  return invoke<void>("set_language", { lang });
+}
+
 // Connection commands
 export async function listSerialPorts(): Promise<PortInfo[]> {
   if (!isTauri()) {
@@ -102,6 +224,8 @@ export async function connect(params: ConnectionParams): Promise<MeterIdentity>
       baudRateChar: "5",
       generation: "2",
       serialNumber: "123456789",
+      protocolMode: "C",
+      rawIdent: "/MKS5<2>ADM(M550.2251)",
     };
   }
   return invoke<MeterIdentity>("connect", { params });
@@ -129,7 +253,7 @@ export async function getMeterIdentity(): Promise<MeterIdentity | null> {
 }
 
 // Reading commands
-export async function readShort(): Promise<ShortReadResult> {
+export async function readShort(operationId?: string): Promise<ShortReadResult> {
   if (!isTauri()) {
     // Mock data for development
     await new Promise((r) => setTimeout(r, 2000));
@@ -163,36 +287,320 @@ export async function readShort(): Promise<ShortReadResult> {
       batteryStatus: "full",
       relayStatus: "active",
       rawData: null,
+      untrustedCodes: [],
+      obisItems: [],
+      mass: {
+        outages: emptyOutageRecords(),
+        warnings: emptyWarningRecords(),
+        dst: { enabled: true, periods: [{ id: 1, offset: "+01:00", forward: "2025-03-30 03:00", backward: "2025-10-26 04:00" }] },
+        tariffs: {
+          weekdays: [
+            { start: "00:00", tariff: 3 },
+            { start: "06:00", tariff: 1 },
+            { start: "17:00", tariff: 2 },
+            { start: "22:00", tariff: 3 },
+          ],
+          saturday: [{ start: "00:00", tariff: 3 }],
+          sunday: [{ start: "00:00", tariff: 3 }],
+        },
+        demandResets: {
+          count: 1,
+          periods: [{ period: 1, resetTime: "2024-02-01 00:00", maxDemand: 123.456, maxDemandTime: "2024-01-18 13:30" }],
+        },
+      },
+      cancelled: false,
     };
   }
-  return invoke<ShortReadResult>("read_short");
+  return invoke<ShortReadResult>("read_short", { operationId });
 }
 
-export async function readFull(): Promise<ShortReadResult> {
+export async function readFull(operationId?: string): Promise<FullReadResult> {
   if (!isTauri()) {
-    return readShort(); // Use same mock for now
+    return {
+      ...(await readShort(operationId)),
+      activeEnergyExportTotal: null,
+      activeEnergyExportT1: null,
+      activeEnergyExportT2: null,
+      activeEnergyExportT3: null,
+      activeEnergyExportT4: null,
+      reactiveEnergyImportTotal: null,
+      reactiveEnergyExportTotal: null,
+      reactiveEnergyInductiveImport: null,
+      reactiveEnergyCapacitiveExport: null,
+      reactiveEnergyInductiveExport: null,
+      reactiveEnergyCapacitiveImport: null,
+      maxDemandExport: null,
+      maxDemandExportTimestamp: null,
+      billingPeriods: [],
+      demandResetCount: 3,
+      outageCount: 1,
+      voltageWarningCount: 0,
+      currentWarningCount: 0,
+      magneticWarningCount: 0,
+      topCoverOpenings: 0,
+      terminalCoverOpenings: 0,
+    };
   }
-  return invoke<ShortReadResult>("read_full");
+  return invoke<FullReadResult>("read_full", { operationId });
+}
+
+// Value of a single OBIS register; untrusted when its BCC never matched
+export interface ObisReading {
+  value: string;
+  untrusted: boolean;
 }
 
-export async function readObis(obisCode: string): Promise<string> {
+export async function readObis(obisCode: string): Promise<ObisReading> {
   if (!isTauri()) {
-    return "mock-value";
+    return { value: "mock-value", untrusted: false };
   }
-  return invoke<string>("read_obis", { obisCode });
+  return invoke<ObisReading>("read_obis", { obisCode });
 }
 
-export async function readObisBatch(obisCodes: string[]): Promise<Record<string, string>> {
+export async function readObisBatch(obisCodes: string[], operationId?: string): Promise<Record<string, ObisReading>> {
   if (!isTauri()) {
     // Mock data for development
     await new Promise((r) => setTimeout(r, 1500));
-    const result: Record<string, string> = {};
+    const result: Record<string, ObisReading> = {};
     for (const code of obisCodes) {
-      result[code] = `${(Math.random() * 1000).toFixed(3)}*kWh`;
+      result[code] = { value: `${(Math.random() * 1000).toFixed(3)}*kWh`, untrusted: false };
     }
     return result;
   }
-  return invoke<Record<string, string>>("read_obis_batch", { obisCodes });
+  return invoke<Record<string, ObisReading>>("read_obis_batch", { obisCodes, operationId });
+}
+
+// MASS packet types (Packets 5, 7, 8 and 9)
+export interface Harmonic {
+  order: number;
+  value: number;
+}
+
+export interface PhaseQuality {
+  voltage: number | null;
+  current: number | null;
+  powerFactor: number | null;
+  activePower: number | null;
+  reactivePower: number | null;
+  apparentPower: number | null;
+  voltageAngle: number | null;
+  currentAngle: number | null;
+  voltageThd: number | null;
+  currentThd: number | null;
+  voltageHarmonics: Harmonic[];
+  currentHarmonics: Harmonic[];
+}
+
+export interface TechQualityData {
+  l1: PhaseQuality;
+  l2: PhaseQuality;
+  l3: PhaseQuality;
+  neutralCurrent: number | null;
+  frequency: number | null;
+  powerFactor: number | null;
+  activePower: number | null;
+  reactivePower: number | null;
+  apparentPower: number | null;
+}
+
+export interface EventRecord {
+  index: number;
+  start: string;
+  end: string;
+  durationMinutes: number | null;
+  untrusted?: boolean;
+}
+
+export interface EventLog {
+  count: number;
+  records: EventRecord[];
+}
+
+export interface BillingPeriodValues {
+  period: number;
+  activeEnergyImportTotal: number;
+  activeEnergyImportT1: number;
+  activeEnergyImportT2: number;
+  activeEnergyImportT3: number;
+  activeEnergyImportT4: number;
+  maxDemandImport: number;
+  maxDemandImportTimestamp: string;
+}
+
+export interface HistoricalData {
+  periods: BillingPeriodValues[];
+}
+
+export interface WarningRecords {
+  voltage: EventLog;
+  current: EventLog;
+  magnetic: EventLog;
+  topCoverCount: number;
+  terminalCover: { month: number; count: number }[];
+  tariffChanges: string[];
+  ffCode: string;
+  gfCode: string;
+}
+
+export interface OutageRecords {
+  threePhase: EventLog;
+  phaseL1: EventLog;
+  phaseL2: EventLog;
+  phaseL3: EventLog;
+}
+
+export interface DstPeriod {
+  id: number;
+  offset: string;
+  forward: string;
+  backward: string;
+}
+
+export interface DstSchedule {
+  enabled: boolean;
+  periods: DstPeriod[];
+}
+
+export interface TariffSlot {
+  start: string;
+  tariff: number;
+}
+
+export interface TariffSchedule {
+  weekdays: TariffSlot[];
+  saturday: TariffSlot[];
+  sunday: TariffSlot[];
+}
+
+export interface DemandPeriod {
+  period: number;
+  resetTime: string;
+  maxDemand: number | null;
+  maxDemandTime: string;
+}
+
+export interface DemandResetHistory {
+  count: number | null;
+  periods: DemandPeriod[];
+}
+
+/**
+ * Event logs, DST and tariff tables and demand resets parsed by the backend from a readout
+ */
+export interface MassData {
+  outages: OutageRecords;
+  warnings: WarningRecords;
+  dst: DstSchedule | null;
+  tariffs: TariffSchedule | null;
+  demandResets: DemandResetHistory;
+}
+
+function emptyOutageRecords(): OutageRecords {
+  const emptyLog: EventLog = { count: 0, records: [] };
+  return { threePhase: emptyLog, phaseL1: emptyLog, phaseL2: emptyLog, phaseL3: emptyLog };
+}
+
+function emptyWarningRecords(): WarningRecords {
+  const emptyLog: EventLog = { count: 0, records: [] };
+  return {
+    voltage: emptyLog,
+    current: emptyLog,
+    magnetic: emptyLog,
+    topCoverCount: 0,
+    terminalCover: [],
+    tariffChanges: [],
+    ffCode: "",
+    gfCode: "",
+  };
+}
+
+export type PacketData =
+  | ({ packet: "techQuality" } & TechQualityData)
+  | ({ packet: "historical" } & HistoricalData)
+  | ({ packet: "warnings" } & WarningRecords)
+  | ({ packet: "outages" } & OutageRecords);
+
+export interface PacketReadResult {
+  mode: number;
+  serialNumber: string;
+  data: PacketData;
+  rawData: string;
+  untrustedCodes: string[];
+  obisItems: ObisDataItem[];
+  cancelled: boolean;
+}
+
+/**
+ * Read a single MASS packet: 5 (technical quality), 7 (historical), 8 (warnings) or 9 (outages)
+ */
+export async function readPacket(mode: 5 | 7 | 8 | 9, operationId?: string): Promise<PacketReadResult> {
+  if (!isTauri()) {
+    // Mock data for development
+    await new Promise((r) => setTimeout(r, 1000));
+    const emptyLog: EventLog = { count: 0, records: [] };
+    const mockPhase = (voltage: number, current: number, voltageAngle: number): PhaseQuality => ({
+      voltage,
+      current,
+      powerFactor: 0.97,
+      activePower: (voltage * current * 0.97) / 1000,
+      reactivePower: 0.2,
+      apparentPower: (voltage * current) / 1000,
+      voltageAngle,
+      currentAngle: 14.1,
+      voltageThd: 2.1,
+      currentThd: 8.5,
+      voltageHarmonics: [],
+      currentHarmonics: [],
+    });
+    const data: PacketData =
+      mode === 5
+        ? {
+            packet: "techQuality",
+            l1: mockPhase(220.5, 16.5, 0),
+            l2: mockPhase(221.3, 15.8, 120),
+            l3: mockPhase(219.8, 17.2, 240),
+            neutralCurrent: 0.4,
+            frequency: 49.9,
+            powerFactor: 0.97,
+            activePower: 10.5,
+            reactivePower: 0.6,
+            apparentPower: 10.8,
+          }
+        : mode === 7
+        ? { packet: "historical", periods: [] }
+        : mode === 8
+          ? {
+              packet: "warnings",
+              voltage: emptyLog,
+              current: emptyLog,
+              magnetic: emptyLog,
+              topCoverCount: 0,
+              terminalCover: [],
+              tariffChanges: [],
+              ffCode: "0000000000000000",
+              gfCode: "0000000000000000",
+            }
+          : {
+              packet: "outages",
+              threePhase: {
+                count: 1,
+                records: [{ index: 1, start: "2024-12-01 03:10", end: "2024-12-01 03:42", durationMinutes: 32 }],
+              },
+              phaseL1: emptyLog,
+              phaseL2: emptyLog,
+              phaseL3: emptyLog,
+            };
+    return {
+      mode,
+      serialNumber: "123456789",
+      data,
+      rawData: "",
+      untrustedCodes: [],
+      obisItems: [],
+      cancelled: false,
+    };
+  }
+  return invoke<PacketReadResult>("read_packet", { mode, operationId });
 }
 
 // Load profile types
@@ -200,18 +608,21 @@ export interface LoadProfileEntry {
   timestamp: string;
   values: number[];
   status: string | null;
+  untrusted?: boolean;
 }
 
 export interface LoadProfileResult {
   profileNumber: number;
   entries: LoadProfileEntry[];
   rawData: string;
+  cancelled: boolean;
 }
 
 export async function readLoadProfile(
   profileNumber: number,
   startTime: string | null,
-  endTime: string | null
+  endTime: string | null,
+  operationId?: string
 ): Promise<LoadProfileResult> {
   if (!isTauri()) {
     // Mock data for development
@@ -240,9 +651,25 @@ export async function readLoadProfile(
       profileNumber,
       entries,
       rawData: "",
+      cancelled: false,
     };
   }
-  return invoke<LoadProfileResult>("read_load_profile", { profileNumber, startTime, endTime });
+  return invoke<LoadProfileResult>("read_load_profile", { profileNumber, startTime, endTime, operationId });
+}
+
+// Operation commands
+/**
+ * New ID for a long-running command, passed to it so it can be cancelled
+ */
+export function newOperationId(): string {
+  return crypto.randomUUID();
+}
+
+export async function cancelOperation(operationId: string): Promise<boolean> {
+  if (!isTauri()) {
+    return false;
+  }
+  return invoke<boolean>("cancel_operation", { operationId });
 }
 
 // Programming commands
@@ -260,11 +687,15 @@ export async function writeObis(obisCode: string, value: string): Promise<void>
   return invoke("write_obis", { obisCode, value });
 }
 
-export async function syncTime(): Promise<void> {
+/**
+ * Set the meter clock to the computer time (0.9.1, 0.9.2, 0.9.5) as one
+ * `programMeter` transaction
+ */
+export async function syncTime(password: string): Promise<ProgramMeterResult> {
   if (!isTauri()) {
-    return;
+    return { registers: [], success: true, rolledBack: false };
   }
-  return invoke("sync_time");
+  return invoke<ProgramMeterResult>("sync_time", { password });
 }
 
 export async function endSession(): Promise<void> {
@@ -274,6 +705,182 @@ export async function endSession(): Promise<void> {
   return invoke("end_session");
 }
 
+export interface WriteVerification {
+  obisCode: string;
+  written: string;
+  readBack: string | null;
+  verified: boolean;
+}
+
+export interface ProgramResult {
+  writes: WriteVerification[];
+  verified: boolean;
+}
+
+/**
+ * Validate, write and read back all tariff tables in one programming session
+ */
+export async function programTariffs(password: string, schedule: TariffSchedule): Promise<ProgramResult> {
+  if (!isTauri()) {
+    await new Promise((r) => setTimeout(r, 1000));
+    return { writes: [], verified: true };
+  }
+  return invoke<ProgramResult>("program_tariffs", { password, schedule });
+}
+
+/**
+ * Validate, write and read back the DST enable flag and all 12 periods in one programming session
+ */
+export async function programDst(password: string, schedule: DstSchedule): Promise<ProgramResult> {
+  if (!isTauri()) {
+    await new Promise((r) => setTimeout(r, 1000));
+    return { writes: [], verified: true };
+  }
+  return invoke<ProgramResult>("program_dst", { password, schedule });
+}
+
+export interface RegisterWrite {
+  obisCode: string;
+  value: string;
+}
+
+export type WriteReply = "ack" | "nak" | "failed" | "notSent";
+
+export interface RegisterReport {
+  obisCode: string;
+  oldValue: string | null;
+  newValue: string;
+  reply: WriteReply;
+  readBack: string | null;
+  verified: boolean;
+  restored: boolean;
+}
+
+export interface ProgramMeterResult {
+  registers: RegisterReport[];
+  success: boolean;
+  rolledBack: boolean;
+}
+
+/**
+ * Write several registers as one transaction: snapshot, write, read back and
+ * restore the old values if any step fails
+ */
+export async function programMeter(password: string, writes: RegisterWrite[]): Promise<ProgramMeterResult> {
+  if (!isTauri()) {
+    await new Promise((r) => setTimeout(r, 1000));
+    return {
+      registers: writes.map((w) => ({
+        obisCode: w.obisCode,
+        oldValue: null,
+        newValue: w.value,
+        reply: "ack",
+        readBack: w.value,
+        verified: true,
+        restored: false,
+      })),
+      success: true,
+      rolledBack: false,
+    };
+  }
+  return invoke<ProgramMeterResult>("program_meter", { password, writes });
+}
+
+export type RelayState = "connected" | "disconnected";
+
+export interface RelayResult {
+  serialNumber: string;
+  previousState: RelayState | null;
+  requestedState: RelayState;
+  state: RelayState | null;
+  confirmed: boolean;
+}
+
+/**
+ * Token confirming a relay switch of one meter, built only after the user confirms
+ */
+export function relayConfirmation(state: RelayState, serialNumber: string): string {
+  return `${state === "connected" ? "CONNECT" : "DISCONNECT"}:${serialNumber.trim()}`;
+}
+
+async function switchRelay(state: RelayState, password: string, expectedSerial: string, confirmation: string): Promise<RelayResult> {
+  if (!isTauri()) {
+    await new Promise((r) => setTimeout(r, 1000));
+    return {
+      serialNumber: expectedSerial,
+      previousState: state === "connected" ? "disconnected" : "connected",
+      requestedState: state,
+      state,
+      confirmed: true,
+    };
+  }
+  const cmd = state === "connected" ? "relay_connect" : "relay_disconnect";
+  return invoke<RelayResult>(cmd, { password, expectedSerial, confirmation });
+}
+
+/**
+ * Connect the relay (96.3.10 = 1); refused if the meter's serial is not expectedSerial
+ */
+export async function relayConnect(password: string, expectedSerial: string, confirmation: string): Promise<RelayResult> {
+  return switchRelay("connected", password, expectedSerial, confirmation);
+}
+
+/**
+ * Disconnect the relay (96.3.10 = 0); refused if the meter's serial is not expectedSerial
+ */
+export async function relayDisconnect(password: string, expectedSerial: string, confirmation: string): Promise<RelayResult> {
+  return switchRelay("disconnected", password, expectedSerial, confirmation);
+}
+
+export interface PasswordChangeResult {
+  level: number;
+  written: boolean;
+  newPasswordAccepted: boolean;
+  oldPasswordKept: boolean;
+}
+
+/**
+ * Change the password of a level (P1 is 96.96) and prove it with a new login;
+ * oldPasswordKept tells when the meter refused the write
+ */
+export async function changePassword(level: number, oldPassword: string, newPassword: string): Promise<PasswordChangeResult> {
+  if (!isTauri()) {
+    await new Promise((r) => setTimeout(r, 1000));
+    return { level, written: true, newPasswordAccepted: true, oldPasswordKept: false };
+  }
+  return invoke<PasswordChangeResult>("change_password", { level, oldPassword, newPassword });
+}
+
+export type DstRule =
+  | { kind: "disabled" }
+  | {
+      kind: "lastSunday";
+      offset: string;
+      forwardMonth: number;
+      forwardHour: number;
+      backwardMonth: number;
+      backwardHour: number;
+    };
+
+/**
+ * DST periods generated from a rule, one per year from firstYear
+ */
+export async function generateDstSchedule(rule: DstRule, firstYear: number): Promise<DstSchedule> {
+  if (!isTauri()) {
+    if (rule.kind === "disabled") return { enabled: false, periods: [] };
+    return {
+      enabled: true,
+      periods: Array.from({ length: 12 }, (_, i) => ({
+        id: i + 1,
+        offset: rule.offset,
+        forward: `${firstYear + i}-03-29 03:00`,
+        backward: `${firstYear + i}-10-25 04:00`,
+      })),
+    };
+  }
+  return invoke<DstSchedule>("generate_dst_schedule", { rule, firstYear });
+}
+
 // Event listeners
 export async function onReadProgress(
   callback: (event: ProgressEvent) => void
@@ -394,6 +1001,49 @@ export async function setSetting(key: string, value: string): Promise<void> {
   return invoke("set_setting", { key, value });
 }
 
+// Audit log of meter programming, recorded by the backend on every write
+export interface AuditEntry {
+  id: number;
+  timestamp: string;
+  meterSerial: string;
+  meterFlag: string;
+  operator: string;
+  obisCode: string;
+  previousValue: string | null;
+  newValue: string;
+  result: "ACK" | "NAK" | "ERROR";
+  appVersion: string;
+}
+
+export async function getAuditLog(meterSerial?: string, limit: number = 100): Promise<AuditEntry[]> {
+  if (!isTauri()) {
+    // Mock data for development
+    return [
+      {
+        id: 1,
+        timestamp: "2024-12-15 14:40:00",
+        meterSerial: "123456789",
+        meterFlag: "MKS",
+        operator: "operator",
+        obisCode: "0.9.1",
+        previousValue: null,
+        newValue: "14:40:00",
+        result: "ACK",
+        appVersion: "0.1.0",
+      },
+    ];
+  }
+  return invoke<AuditEntry[]>("get_audit_log", { meterSerial: meterSerial ?? null, limit });
+}
+
+/** Writes the audit log to a CSV file next to the executable and returns its path */
+export async function exportAuditLog(meterSerial?: string): Promise<string> {
+  if (!isTauri()) {
+    return "omnicore-audit-logs/audit-mock.csv";
+  }
+  return invoke<string>("export_audit_log", { meterSerial: meterSerial ?? null });
+}
+
 // Session file commands (file-based storage next to executable)
 export interface SessionFileData {
   flag: string;
@@ -478,3 +1128,26 @@ export async function deleteSessionFile(filename: string): Promise<void> {
   }
   return invoke("delete_session_file", { filename });
 }
+
+// Capture (record and replay) commands
+
+export async function startCapture(): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+  return invoke("start_capture");
+}
+
+export async function stopCapture(): Promise<string | null> {
+  if (!isTauri()) {
+    return null;
+  }
+  return invoke<string | null>("stop_capture");
+}
+
+export async function getCaptureStatus(): Promise<boolean> {
+  if (!isTauri()) {
+    return false;
+  }
+  return invoke<boolean>("get_capture_status");
+}